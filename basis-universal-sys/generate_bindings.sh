@@ -14,11 +14,21 @@ bindgen vendor/transcoding_wrapper.cpp -o src/transcoding_bindings.rs \
   --allowlist-function basis_is_format_supported \
   --allowlist-function basis_validate_output_buffer_size \
   \
+  --allowlist-function low_level_etc1s_transcoder_new \
+  --allowlist-function low_level_etc1s_transcoder_delete \
+  --allowlist-function low_level_etc1s_transcoder_decode_palettes \
+  --allowlist-function low_level_etc1s_transcoder_decode_tables \
+  --allowlist-function low_level_etc1s_transcoder_transcode_slice \
+  --allowlist-function low_level_etc1s_transcoder_clear \
+  \
   --allowlist-function low_level_uastc_transcoder_new \
   --allowlist-function low_level_uastc_transcoder_delete \
   \
   --allowlist-function low_level_uastc_transcoder_transcode_slice \
   \
+  --allowlist-function transcoder_state_new \
+  --allowlist-function transcoder_state_delete \
+  \
   --allowlist-function transcoder_new \
   --allowlist-function transcoder_delete \
   \
@@ -35,14 +45,20 @@ bindgen vendor/transcoding_wrapper.cpp -o src/transcoding_bindings.rs \
   --allowlist-function transcoder_get_image_info \
   --allowlist-function transcoder_get_image_level_info \
   --allowlist-function transcoder_get_file_info \
+  --allowlist-function transcoder_get_slice_info \
+  --allowlist-function transcoder_get_image_mipmap_levels \
   \
   --allowlist-function transcoder_start_transcoding \
   --allowlist-function transcoder_stop_transcoding \
   --allowlist-function transcoder_get_ready_to_transcode \
   --allowlist-function transcoder_transcode_image_level \
+  --allowlist-function transcoder_find_slice \
+  --allowlist-function transcoder_transcode_slice \
+  --allowlist-function transcoder_write_opaque_alpha_blocks \
   \
   --allowlist-function basisu_transcoder_init \
   \
+  --opaque-type LowLevelEtc1sTranscoder \
   --opaque-type LowLevelUastcTranscoder \
   --opaque-type basist::block_format \
   \
@@ -52,6 +68,8 @@ bindgen vendor/transcoding_wrapper.cpp -o src/transcoding_bindings.rs \
   -- -x c++ -std=c++14
 
 bindgen vendor/encoding_wrapper.cpp -o src/encoding_bindings.rs \
+  --allowlist-function image_new \
+  --allowlist-function image_delete \
   --allowlist-function image_clear \
   --allowlist-function image_resize_with_pitch \
   --allowlist-function image_resize \
@@ -66,16 +84,20 @@ bindgen vendor/encoding_wrapper.cpp -o src/encoding_bindings.rs \
   --allowlist-function image_get_block_height \
   --allowlist-function image_get_total_blocks \
   --allowlist-function image_get_pixel_data \
+  --allowlist-function image_load_png \
+  --allowlist-function image_load_jpeg \
   \
   --allowlist-function compressor_params_new \
   --allowlist-function compressor_params_delete \
   --allowlist-function compressor_params_clear \
   \
   --allowlist-function compressor_params_get_or_create_source_image \
+  --allowlist-function compressor_params_set_source_image \
   --allowlist-function compressor_params_resize_source_image_list \
   --allowlist-function compressor_params_clear_source_image_list \
   \
   --allowlist-function compressor_params_get_or_create_source_mipmap_image \
+  --allowlist-function compressor_params_set_source_mipmap_image \
   --allowlist-function compressor_params_resize_source_mipmap_image_list \
   --allowlist-function compressor_params_clear_source_mipmap_image_list \
   --allowlist-function compressor_params_resize_source_mipmap_image_level_list \
@@ -94,12 +116,20 @@ bindgen vendor/encoding_wrapper.cpp -o src/encoding_bindings.rs \
   --allowlist-function compressor_params_set_generate_mipmaps \
   --allowlist-function compressor_params_set_mip_smallest_dimension \
   --allowlist-function compressor_params_set_userdata \
+  --allowlist-function compressor_params_set_create_ktx2_file \
+  \
+  --allowlist-function job_pool_new \
+  --allowlist-function job_pool_delete \
+  --allowlist-function job_pool_get_total_threads \
   \
   --allowlist-function compressor_new \
+  --allowlist-function compressor_new_with_job_pool \
   --allowlist-function compressor_delete \
+  --allowlist-function compressor_reset \
   --allowlist-function compressor_init \
   --allowlist-function compressor_process \
   --allowlist-function compressor_get_output_basis_file \
+  --allowlist-function compressor_get_output_ktx2_file \
   \
   --allowlist-function compressor_get_basis_file_size \
   --allowlist-function compressor_get_basis_bits_per_texel \
@@ -124,6 +154,7 @@ bindgen vendor/encoding_wrapper.cpp -o src/encoding_bindings.rs \
   --allowlist-var basisu::TOTAL_PACK_UASTC_LEVELS \
   \
   --opaque-type CompressorParams \
+  --opaque-type JobPool \
   --opaque-type Compressor \
   --opaque-type basisu::image \
   \
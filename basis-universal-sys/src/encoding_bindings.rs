@@ -320,6 +320,12 @@ pub const UastcPackFlags_PackUASTCETC1FasterHints: UastcPackFlags = 64;
 pub const UastcPackFlags_PackUASTCETC1FastestHints: UastcPackFlags = 128;
 pub const UastcPackFlags_PackUASTCETC1DisableFlipAndIndividual: UastcPackFlags = 256;
 pub type UastcPackFlags = ::std::os::raw::c_int;
+extern "C" {
+    pub fn image_new() -> *mut basisu_image;
+}
+extern "C" {
+    pub fn image_delete(image: *mut basisu_image);
+}
 extern "C" {
     pub fn image_clear(image: *mut basisu_image);
 }
@@ -437,6 +443,20 @@ fn bindgen_test_layout_PixelData() {
 extern "C" {
     pub fn image_get_pixel_data(image: *mut basisu_image) -> PixelData;
 }
+extern "C" {
+    pub fn image_load_png(
+        image: *mut basisu_image,
+        pData: *const u8,
+        data_size: u32,
+    ) -> bool;
+}
+extern "C" {
+    pub fn image_load_jpeg(
+        image: *mut basisu_image,
+        pData: *const u8,
+        data_size: u32,
+    ) -> bool;
+}
 #[repr(C)]
 #[repr(align(8))]
 #[derive(Debug, Copy, Clone)]
@@ -471,6 +491,13 @@ extern "C" {
         index: u32,
     ) -> *mut basisu_image;
 }
+extern "C" {
+    pub fn compressor_params_set_source_image(
+        params: *mut CompressorParams,
+        index: u32,
+        image: *mut basisu_image,
+    );
+}
 extern "C" {
     pub fn compressor_params_resize_source_image_list(
         params: *mut CompressorParams,
@@ -487,6 +514,14 @@ extern "C" {
         level: u32,
     ) -> *mut basisu_image;
 }
+extern "C" {
+    pub fn compressor_params_set_source_mipmap_image(
+        params: *mut CompressorParams,
+        index: u32,
+        level: u32,
+        image: *mut basisu_image,
+    );
+}
 extern "C" {
     pub fn compressor_params_resize_source_mipmap_image_list(
         params: *mut CompressorParams,
@@ -585,17 +620,51 @@ extern "C" {
         userdata1: u32,
     );
 }
+extern "C" {
+    pub fn compressor_params_set_create_ktx2_file(
+        params: *mut CompressorParams,
+        create_ktx2_file: bool,
+    );
+}
+#[repr(C)]
+#[repr(align(8))]
+#[derive(Debug, Copy, Clone)]
+pub struct JobPool {
+    pub _bindgen_opaque_blob: u64,
+}
+#[test]
+fn bindgen_test_layout_JobPool() {
+    assert_eq!(
+        ::std::mem::size_of::<JobPool>(),
+        8usize,
+        concat!("Size of: ", stringify!(JobPool))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<JobPool>(),
+        8usize,
+        concat!("Alignment of ", stringify!(JobPool))
+    );
+}
+extern "C" {
+    pub fn job_pool_new(num_threads: ::std::os::raw::c_int) -> *mut JobPool;
+}
+extern "C" {
+    pub fn job_pool_delete(job_pool: *mut JobPool);
+}
+extern "C" {
+    pub fn job_pool_get_total_threads(job_pool: *const JobPool) -> u32;
+}
 #[repr(C)]
 #[repr(align(8))]
 #[derive(Debug, Copy, Clone)]
 pub struct Compressor {
-    pub _bindgen_opaque_blob: [u64; 2usize],
+    pub _bindgen_opaque_blob: [u64; 3usize],
 }
 #[test]
 fn bindgen_test_layout_Compressor() {
     assert_eq!(
         ::std::mem::size_of::<Compressor>(),
-        16usize,
+        24usize,
         concat!("Size of: ", stringify!(Compressor))
     );
     assert_eq!(
@@ -607,9 +676,15 @@ fn bindgen_test_layout_Compressor() {
 extern "C" {
     pub fn compressor_new(num_threads: ::std::os::raw::c_int) -> *mut Compressor;
 }
+extern "C" {
+    pub fn compressor_new_with_job_pool(job_pool: *mut JobPool) -> *mut Compressor;
+}
 extern "C" {
     pub fn compressor_delete(compressor: *mut Compressor);
 }
+extern "C" {
+    pub fn compressor_reset(compressor: *mut Compressor);
+}
 extern "C" {
     pub fn compressor_init(
         compressor: *mut Compressor,
@@ -663,6 +738,9 @@ fn bindgen_test_layout_CompressorBasisFile() {
 extern "C" {
     pub fn compressor_get_output_basis_file(compressor: *mut Compressor) -> CompressorBasisFile;
 }
+extern "C" {
+    pub fn compressor_get_output_ktx2_file(compressor: *mut Compressor) -> CompressorBasisFile;
+}
 extern "C" {
     pub fn compressor_get_basis_file_size(compressor: *const Compressor) -> u32;
 }
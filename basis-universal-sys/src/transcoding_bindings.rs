@@ -554,6 +554,7 @@ pub struct FileInfo {
     pub m_slices_size: u32,
     pub m_tex_type: basist_basis_texture_type,
     pub m_us_per_frame: u32,
+    pub m_total_slices: u32,
     pub m_total_images: u32,
     pub m_userdata0: u32,
     pub m_userdata1: u32,
@@ -568,7 +569,7 @@ fn bindgen_test_layout_FileInfo() {
     let ptr = UNINIT.as_ptr();
     assert_eq!(
         ::std::mem::size_of::<FileInfo>(),
-        72usize,
+        76usize,
         concat!("Size of: ", stringify!(FileInfo))
     );
     assert_eq!(
@@ -707,8 +708,18 @@ fn bindgen_test_layout_FileInfo() {
         )
     );
     assert_eq!(
-        unsafe { ::std::ptr::addr_of!((*ptr).m_total_images) as usize - ptr as usize },
+        unsafe { ::std::ptr::addr_of!((*ptr).m_total_slices) as usize - ptr as usize },
         52usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(FileInfo),
+            "::",
+            stringify!(m_total_slices)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_total_images) as usize - ptr as usize },
+        56usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -718,7 +729,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_userdata0) as usize - ptr as usize },
-        56usize,
+        60usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -728,7 +739,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_userdata1) as usize - ptr as usize },
-        60usize,
+        64usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -738,7 +749,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_tex_format) as usize - ptr as usize },
-        64usize,
+        68usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -748,7 +759,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_y_flipped) as usize - ptr as usize },
-        68usize,
+        72usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -758,7 +769,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_etc1s) as usize - ptr as usize },
-        69usize,
+        73usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -768,7 +779,7 @@ fn bindgen_test_layout_FileInfo() {
     );
     assert_eq!(
         unsafe { ::std::ptr::addr_of!((*ptr).m_has_alpha_slices) as usize - ptr as usize },
-        70usize,
+        74usize,
         concat!(
             "Offset of field: ",
             stringify!(FileInfo),
@@ -777,6 +788,190 @@ fn bindgen_test_layout_FileInfo() {
         )
     );
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SliceInfo {
+    pub m_orig_width: u32,
+    pub m_orig_height: u32,
+    pub m_width: u32,
+    pub m_height: u32,
+    pub m_num_blocks_x: u32,
+    pub m_num_blocks_y: u32,
+    pub m_total_blocks: u32,
+    pub m_compressed_size: u32,
+    pub m_file_ofs: u32,
+    pub m_slice_index: u32,
+    pub m_image_index: u32,
+    pub m_level_index: u32,
+    pub m_unpacked_slice_crc16: u32,
+    pub m_alpha_flag: bool,
+    pub m_iframe_flag: bool,
+}
+#[test]
+fn bindgen_test_layout_SliceInfo() {
+    const UNINIT: ::std::mem::MaybeUninit<SliceInfo> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<SliceInfo>(),
+        56usize,
+        concat!("Size of: ", stringify!(SliceInfo))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<SliceInfo>(),
+        4usize,
+        concat!("Alignment of ", stringify!(SliceInfo))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_orig_width) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_orig_width)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_orig_height) as usize - ptr as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_orig_height)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_width) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_width)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_height) as usize - ptr as usize },
+        12usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_height)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_num_blocks_x) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_num_blocks_x)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_num_blocks_y) as usize - ptr as usize },
+        20usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_num_blocks_y)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_total_blocks) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_total_blocks)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_compressed_size) as usize - ptr as usize },
+        28usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_compressed_size)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_file_ofs) as usize - ptr as usize },
+        32usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_file_ofs)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_slice_index) as usize - ptr as usize },
+        36usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_slice_index)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_image_index) as usize - ptr as usize },
+        40usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_image_index)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_level_index) as usize - ptr as usize },
+        44usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_level_index)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_unpacked_slice_crc16) as usize - ptr as usize },
+        48usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_unpacked_slice_crc16)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_alpha_flag) as usize - ptr as usize },
+        52usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_alpha_flag)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).m_iframe_flag) as usize - ptr as usize },
+        53usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SliceInfo),
+            "::",
+            stringify!(m_iframe_flag)
+        )
+    );
+}
 extern "C" {
     pub fn basis_get_bytes_per_block_or_pixel(fmt: basist_transcoder_texture_format) -> u32;
 }
@@ -838,6 +1033,74 @@ extern "C" {
 #[repr(C)]
 #[repr(align(8))]
 #[derive(Debug, Copy, Clone)]
+pub struct LowLevelEtc1sTranscoder {
+    pub _bindgen_opaque_blob: u64,
+}
+#[test]
+fn bindgen_test_layout_LowLevelEtc1sTranscoder() {
+    assert_eq!(
+        ::std::mem::size_of::<LowLevelEtc1sTranscoder>(),
+        8usize,
+        concat!("Size of: ", stringify!(LowLevelEtc1sTranscoder))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<LowLevelEtc1sTranscoder>(),
+        8usize,
+        concat!("Alignment of ", stringify!(LowLevelEtc1sTranscoder))
+    );
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_new() -> *mut LowLevelEtc1sTranscoder;
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_delete(transcoder: *mut LowLevelEtc1sTranscoder);
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_decode_palettes(
+        transcoder: *mut LowLevelEtc1sTranscoder,
+        num_endpoints: u32,
+        pEndpoints_data: *const u8,
+        endpoints_data_size: u32,
+        num_selectors: u32,
+        pSelectors_data: *const u8,
+        selectors_data_size: u32,
+    ) -> bool;
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_decode_tables(
+        transcoder: *mut LowLevelEtc1sTranscoder,
+        pTable_data: *const u8,
+        table_data_size: u32,
+    ) -> bool;
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_transcode_slice(
+        transcoder: *mut LowLevelEtc1sTranscoder,
+        pDst_blocks: *mut ::std::os::raw::c_void,
+        num_blocks_x: u32,
+        num_blocks_y: u32,
+        pImage_data: *const u8,
+        image_data_size: u32,
+        fmt: basist_block_format,
+        output_block_or_pixel_stride_in_bytes: u32,
+        bc1_allow_threecolor_blocks: bool,
+        is_video: bool,
+        is_alpha_slice: bool,
+        level_index: u32,
+        orig_width: u32,
+        orig_height: u32,
+        output_row_pitch_in_blocks_or_pixels: u32,
+        pState: *mut basist_basisu_transcoder_state,
+        astc_transcode_alpha: bool,
+        output_rows_in_pixels: u32,
+    ) -> bool;
+}
+extern "C" {
+    pub fn low_level_etc1s_transcoder_clear(transcoder: *mut LowLevelEtc1sTranscoder);
+}
+#[repr(C)]
+#[repr(align(8))]
+#[derive(Debug, Copy, Clone)]
 pub struct LowLevelUastcTranscoder {
     pub _bindgen_opaque_blob: u64,
 }
@@ -882,6 +1145,12 @@ extern "C" {
         decode_flags: i32,
     ) -> bool;
 }
+extern "C" {
+    pub fn transcoder_state_new() -> *mut basist_basisu_transcoder_state;
+}
+extern "C" {
+    pub fn transcoder_state_delete(state: *mut basist_basisu_transcoder_state);
+}
 #[repr(C)]
 #[repr(align(8))]
 #[derive(Debug, Copy, Clone)]
@@ -999,6 +1268,24 @@ extern "C" {
         file_info: *mut FileInfo,
     ) -> bool;
 }
+extern "C" {
+    pub fn transcoder_get_slice_info(
+        transcoder: *const Transcoder,
+        pData: *const ::std::os::raw::c_void,
+        data_size: u32,
+        pSlice_info: *mut SliceInfo,
+        slice_info_count: u32,
+    ) -> bool;
+}
+extern "C" {
+    pub fn transcoder_get_image_mipmap_levels(
+        transcoder: *const Transcoder,
+        pData: *const ::std::os::raw::c_void,
+        data_size: u32,
+        pImage_mipmap_levels: *mut u32,
+        image_count: u32,
+    ) -> bool;
+}
 extern "C" {
     pub fn transcoder_start_transcoding(
         transcoder: *mut Transcoder,
@@ -1028,6 +1315,44 @@ extern "C" {
         output_rows_in_pixels: u32,
     ) -> bool;
 }
+extern "C" {
+    pub fn transcoder_find_slice(
+        transcoder: *const Transcoder,
+        pData: *const ::std::os::raw::c_void,
+        data_size: u32,
+        image_index: u32,
+        level_index: u32,
+        alpha_data: bool,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn transcoder_transcode_slice(
+        transcoder: *const Transcoder,
+        pData: *const ::std::os::raw::c_void,
+        data_size: u32,
+        slice_index: u32,
+        pOutput_blocks: *mut ::std::os::raw::c_void,
+        output_blocks_buf_size_in_blocks_or_pixels: u32,
+        fmt: basist_block_format,
+        output_block_stride_in_bytes: u32,
+        decode_flags: u32,
+        output_row_pitch_in_blocks_or_pixels: u32,
+        pState: *mut basist_basisu_transcoder_state,
+        output_rows_in_pixels: u32,
+        channel0: ::std::os::raw::c_int,
+        channel1: ::std::os::raw::c_int,
+    ) -> bool;
+}
+extern "C" {
+    pub fn transcoder_write_opaque_alpha_blocks(
+        num_blocks_x: u32,
+        num_blocks_y: u32,
+        pOutput_blocks: *mut ::std::os::raw::c_void,
+        fmt: basist_block_format,
+        block_stride_in_bytes: u32,
+        output_row_pitch_in_blocks_or_pixels: u32,
+    );
+}
 extern "C" {
     pub fn basisu_transcoder_init();
 }
@@ -1,8 +1,7 @@
 use basis_universal::{
-    BasisTextureFormat, Compressor, CompressorParams, TranscodeParameters, Transcoder,
-    TranscoderTextureFormat,
+    BasisTextureFormat, Compressor, CompressorParams, JobPool, SourceImageData,
+    TranscodeParameters, Transcoder, TranscoderTextureFormat,
 };
-use image::{DynamicImage, GenericImageView};
 use std::io::Write;
 
 // This is not a proper benchmark, just a quick program for feeling out how options affect
@@ -29,21 +28,8 @@ pub fn main() {
         source_file_format, source_file_size, source_file_decode_time
     );
 
-    // We need to know how many color channels are in the image
-    use image::ColorType;
-    let channel_count = match &image_data.color() {
-        ColorType::L8 => 1,
-        ColorType::La8 => 2,
-        ColorType::Rgb8 => 3,
-        ColorType::Rgba8 => 4,
-        ColorType::L16 => 1,
-        ColorType::La16 => 2,
-        ColorType::Rgb16 => 3,
-        ColorType::Rgba16 => 4,
-        ColorType::Bgr8 => 3,
-        ColorType::Bgra8 => 4,
-        _ => unimplemented!(),
-    };
+    // Converts 16-bit and BGR images to the 8-bit layout the encoder expects
+    let source_image = SourceImageData::from(&image_data);
 
     let compression_tests = vec![
         // (BasisTextureFormat::ETC1S, basis_universal::ETC1S_QUALITY_MIN, None),
@@ -73,14 +59,11 @@ pub fn main() {
     );
     println!(
         "size: {}x{} channels: {}",
-        image_data.width(),
-        image_data.height(),
-        channel_count
+        source_image.width, source_image.height, source_image.channel_count
     );
     for (format, quality, rdo_scalar) in compression_tests {
         benchmark_encode(
-            &image_data,
-            channel_count,
+            &source_image,
             format,
             quality,
             rdo_scalar,
@@ -88,6 +71,12 @@ pub fn main() {
         );
     }
 
+    for thread_count in [1, 4] {
+        benchmark_compressor_reuse(&source_image, thread_count);
+    }
+
+    benchmark_shared_job_pool(&source_image, 4, 4);
+
     let transcode_tests = vec![
         (
             BasisTextureFormat::ETC1S,
@@ -113,8 +102,7 @@ pub fn main() {
 
     for (format, quality, rdo_scalar) in transcode_tests {
         benchmark_transcode(
-            &image_data,
-            channel_count,
+            &source_image,
             format,
             quality,
             rdo_scalar,
@@ -124,8 +112,7 @@ pub fn main() {
 }
 
 fn benchmark_encode(
-    image_data: &DynamicImage,
-    channel_count: u8,
+    source_image: &SourceImageData,
     basis_texture_format: BasisTextureFormat,
     quality: u32,
     rdo_scalar: Option<f32>,
@@ -147,12 +134,7 @@ fn benchmark_encode(
     // Set the source image in the params
     //
     let mut compressor_image = compressor_params.source_image_mut(0);
-    compressor_image.init(
-        image_data.as_bytes(),
-        image_data.width(),
-        image_data.height(),
-        channel_count,
-    );
+    compressor_image.init_from(source_image.clone());
 
     //
     // Create the compressor and compress
@@ -201,9 +183,171 @@ fn benchmark_encode(
     }
 }
 
+// Compares creating a compressor per texture with reusing one. A reused compressor keeps the encoder,
+// its buffers and the threads, so it makes fewer allocations and doesn't start threads per texture.
+fn benchmark_compressor_reuse(
+    source_image: &SourceImageData,
+    compressor_thread_count: u32,
+) {
+    const TEXTURE_COUNT: u32 = 20;
+
+    let mut compressor_params = CompressorParams::new();
+    compressor_params.set_etc1s_quality_level(basis_universal::ETC1S_QUALITY_MIN);
+    let mut compressor_image = compressor_params.source_image_mut(0);
+    compressor_image.init_from(source_image.clone());
+
+    let a0 = allocation_counter::allocation_count();
+    let t0 = std::time::Instant::now();
+    for _ in 0..TEXTURE_COUNT {
+        let mut compressor = Compressor::new(compressor_thread_count);
+        unsafe {
+            compressor.init(&compressor_params);
+            compressor.process().unwrap();
+        }
+    }
+    let t1 = std::time::Instant::now();
+    let a1 = allocation_counter::allocation_count();
+
+    let mut compressor = Compressor::new(compressor_thread_count);
+    for _ in 0..TEXTURE_COUNT {
+        unsafe {
+            compressor.init(&compressor_params);
+            compressor.process().unwrap();
+        }
+    }
+    let t2 = std::time::Instant::now();
+    let a2 = allocation_counter::allocation_count();
+
+    println!(
+        "Compressor reuse with {} threads, {} textures: new compressor per texture {}ms, reused compressor {}ms",
+        compressor_thread_count,
+        TEXTURE_COUNT,
+        (t1 - t0).as_secs_f32() * 1000.0,
+        (t2 - t1).as_secs_f32() * 1000.0
+    );
+    match (a0, a1, a2) {
+        (Some(a0), Some(a1), Some(a2)) => println!(
+            "  allocations: new compressor per texture {}, reused compressor {}",
+            a1 - a0,
+            a2 - a1
+        ),
+        _ => println!("  allocations are only counted on Linux with glibc"),
+    }
+}
+
+// Compares compressors that each start their own threads with compressors sharing one JobPool. The
+// shared pool bounds the encoder threads, but compressors sharing it take turns in process(), so
+// only one texture is encoded at a time.
+fn benchmark_shared_job_pool(
+    source_image: &SourceImageData,
+    compressor_count: u32,
+    thread_count: u32,
+) {
+    const TEXTURES_PER_COMPRESSOR: u32 = 5;
+
+    let compress_all = |new_compressor: &(dyn Fn() -> Compressor + Sync)| {
+        std::thread::scope(|scope| {
+            for _ in 0..compressor_count {
+                scope.spawn(|| {
+                    // CompressorParams can't be shared between threads, so each has its own
+                    let mut compressor_params = CompressorParams::new();
+                    compressor_params.set_etc1s_quality_level(basis_universal::ETC1S_QUALITY_MIN);
+                    let mut compressor_image = compressor_params.source_image_mut(0);
+                    compressor_image.init_from(source_image.clone());
+
+                    let mut compressor = new_compressor();
+                    for _ in 0..TEXTURES_PER_COMPRESSOR {
+                        unsafe {
+                            compressor.init(&compressor_params);
+                            compressor.process().unwrap();
+                        }
+                    }
+                });
+            }
+        });
+    };
+
+    let t0 = std::time::Instant::now();
+    compress_all(&|| Compressor::new(thread_count));
+    let t1 = std::time::Instant::now();
+
+    let job_pool = JobPool::new(thread_count);
+    compress_all(&|| Compressor::with_job_pool(&job_pool));
+    let t2 = std::time::Instant::now();
+
+    println!(
+        "{} compressors, {} textures each: {} threads per compressor ({} threads) {}ms, shared job pool ({} threads) {}ms",
+        compressor_count,
+        TEXTURES_PER_COMPRESSOR,
+        thread_count,
+        compressor_count * thread_count,
+        (t1 - t0).as_secs_f32() * 1000.0,
+        thread_count,
+        (t2 - t1).as_secs_f32() * 1000.0
+    );
+}
+
+// Counts calls to malloc/calloc/realloc, which includes the allocations made by the C++ encoder as
+// well as Rust's. glibc lets a program replace these, and exports its own implementations as
+// __libc_* so the replacements can forward to them. (free is left alone, so it pairs up with the
+// forwarded calls.)
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+mod allocation_counter {
+    use std::os::raw::c_void;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" {
+        fn __libc_malloc(size: usize) -> *mut c_void;
+        fn __libc_calloc(
+            count: usize,
+            size: usize,
+        ) -> *mut c_void;
+        fn __libc_realloc(
+            ptr: *mut c_void,
+            size: usize,
+        ) -> *mut c_void;
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        __libc_malloc(size)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn calloc(
+        count: usize,
+        size: usize,
+    ) -> *mut c_void {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        __libc_calloc(count, size)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn realloc(
+        ptr: *mut c_void,
+        size: usize,
+    ) -> *mut c_void {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        __libc_realloc(ptr, size)
+    }
+
+    pub fn allocation_count() -> Option<usize> {
+        Some(ALLOCATION_COUNT.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+mod allocation_counter {
+    pub fn allocation_count() -> Option<usize> {
+        None
+    }
+}
+
 fn benchmark_transcode(
-    image_data: &DynamicImage,
-    channel_count: u8,
+    source_image: &SourceImageData,
     basis_texture_format: BasisTextureFormat,
     quality: u32,
     rdo_scalar: Option<f32>,
@@ -225,12 +369,7 @@ fn benchmark_transcode(
     // Set the source image in the params
     //
     let mut compressor_image = compressor_params.source_image_mut(0);
-    compressor_image.init(
-        image_data.as_bytes(),
-        image_data.width(),
-        image_data.height(),
-        channel_count,
-    );
+    compressor_image.init_from(source_image.clone());
 
     //
     // Create the compressor and compress
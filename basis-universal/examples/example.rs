@@ -1,8 +1,7 @@
 use basis_universal::{
-    BasisTextureFormat, Compressor, CompressorParams, TranscodeParameters, Transcoder,
-    TranscoderTextureFormat, UserData,
+    BasisTextureFormat, Compressor, CompressorParams, SourceImageData, TranscodeParameters,
+    Transcoder, TranscoderTextureFormat, UserData,
 };
-use image::GenericImageView;
 
 // This example:
 // - Loads a PNG file
@@ -25,28 +24,15 @@ pub fn main() {
         (t1 - t0).as_secs_f64() * 1000.0
     );
 
-    // We need to know how many color channels are in the image
-    use image::ColorType;
-    let channel_count = match &image_data.color() {
-        ColorType::L8 => 1,
-        ColorType::La8 => 2,
-        ColorType::Rgb8 => 3,
-        ColorType::Rgba8 => 4,
-        ColorType::L16 => 1,
-        ColorType::La16 => 2,
-        ColorType::Rgb16 => 3,
-        ColorType::Rgba16 => 4,
-        ColorType::Bgr8 => 3,
-        ColorType::Bgra8 => 4,
-        _ => unimplemented!(),
-    };
+    // Converts 16-bit and BGR images to the 8-bit layout the encoder expects
+    let source_image = SourceImageData::from(&image_data);
 
     println!(
         "Going to encode {}x{} image with {} channels ({} uncompressed bytes)",
-        image_data.width(),
-        image_data.height(),
-        channel_count,
-        image_data.as_bytes().len()
+        source_image.width,
+        source_image.height,
+        source_image.channel_count,
+        source_image.data.len()
     );
 
     //
@@ -68,12 +54,7 @@ pub fn main() {
     // Set the source image in the params
     //
     let mut compressor_image = compressor_params.source_image_mut(0);
-    compressor_image.init(
-        image_data.as_bytes(),
-        image_data.width(),
-        image_data.height(),
-        channel_count,
-    );
+    compressor_image.init_from(source_image);
 
     //
     // Create the compressor and compress
@@ -92,7 +73,7 @@ pub fn main() {
     // std::fs::write("example_encoded_image.basis", basis_file).unwrap();
 
     let mut transcoder = Transcoder::new();
-    let mip_level_count = transcoder.image_level_count(basis_file, 0);
+    let mip_level_count = transcoder.image_level_count(basis_file, 0).unwrap();
     println!(
         "Compressed {} mip levels to {} total bytes in {} ms",
         mip_level_count,
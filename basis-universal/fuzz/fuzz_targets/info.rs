@@ -0,0 +1,35 @@
+#![no_main]
+use basis_universal::Transcoder;
+use libfuzzer_sys::fuzz_target;
+
+// Keeps each run fast on headers that claim a huge number of images
+const MAX_IMAGES: u32 = 64;
+
+fuzz_target!(|data: &[u8]| {
+    let transcoder = Transcoder::new();
+
+    // Everything here must be safe to call on arbitrary data
+    let _ = transcoder.validate_header(data);
+    let _ = transcoder.basis_texture_type(data);
+    let _ = transcoder.basis_texture_format(data);
+    let _ = transcoder.user_data(data);
+    let _ = transcoder.file_info(data);
+
+    let image_count = transcoder.image_count(data).unwrap_or(0);
+    for image_index in 0..image_count.min(MAX_IMAGES) {
+        let _ = transcoder.image_info(data, image_index);
+        let level_count = transcoder.image_level_count(data, image_index).unwrap_or(0);
+        for level_index in 0..level_count {
+            let _ = transcoder.image_level_description(data, image_index, level_index);
+            let _ = transcoder.image_level_info(data, image_index, level_index);
+            for &alpha_data in &[false, true] {
+                let _ = transcoder.find_slice(data, image_index, level_index, alpha_data);
+            }
+        }
+    }
+
+    // Out of range queries must fail cleanly
+    let _ = transcoder.image_level_count(data, u32::MAX);
+    let _ = transcoder.image_info(data, u32::MAX);
+    let _ = transcoder.image_level_description(data, 0, u32::MAX);
+});
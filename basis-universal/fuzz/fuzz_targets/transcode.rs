@@ -0,0 +1,65 @@
+#![no_main]
+use basis_universal::{TranscodeParameters, Transcoder, TranscoderTextureFormat};
+use libfuzzer_sys::fuzz_target;
+
+const FORMATS: [TranscoderTextureFormat; 21] = [
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::BC4_R,
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::ETC2_EAC_R11,
+    TranscoderTextureFormat::ETC2_EAC_RG11,
+    TranscoderTextureFormat::RGBA32,
+    TranscoderTextureFormat::RGB565,
+    TranscoderTextureFormat::BGR565,
+    TranscoderTextureFormat::RGBA4444,
+];
+
+// Headers can claim huge or many images, so skip anything that would need more memory or time than
+// libFuzzer allows
+const MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
+const MAX_IMAGES: u32 = 4;
+
+fuzz_target!(|data: &[u8]| {
+    let mut transcoder = Transcoder::new();
+    if transcoder.prepare_transcoding(data).is_err() {
+        return;
+    }
+
+    let mut levels = Vec::default();
+    let image_count = transcoder.image_count(data).unwrap_or(0);
+    for image_index in 0..image_count.min(MAX_IMAGES) {
+        let level_count = transcoder.image_level_count(data, image_index).unwrap_or(0);
+        for level_index in 0..level_count {
+            levels.push(TranscodeParameters {
+                image_index,
+                level_index,
+                ..Default::default()
+            });
+        }
+    }
+
+    for transcode_parameters in levels {
+        for &format in &FORMATS {
+            match transcoder.required_output_size(data, format, &transcode_parameters) {
+                Ok(size) if size <= MAX_OUTPUT_BYTES => {}
+                _ => continue,
+            }
+
+            let _ = transcoder.transcode_image_level(data, format, transcode_parameters.clone());
+        }
+    }
+
+    transcoder.end_transcoding();
+});
@@ -0,0 +1,26 @@
+#![no_main]
+use basis_universal::file_format::BasisFileView;
+use basis_universal::Transcoder;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(view) = BasisFileView::parse(data) {
+        let _ = view.validate_checksums();
+        for slice_index in 0..view.slice_count() {
+            let _ = view.slice_data(slice_index);
+        }
+        for image_index in 0..view.image_count() {
+            if let Ok(level_count) = view.image_level_count(image_index) {
+                for level_index in 0..level_count {
+                    let _ = view.image_level_description(image_index, level_index);
+                }
+            }
+        }
+    }
+
+    let transcoder = Transcoder::new();
+    if transcoder.validate_header(data).is_ok() {
+        let _ = transcoder.validate_file_checksums(data, false);
+        let _ = transcoder.validate_file_checksums(data, true);
+    }
+});
@@ -0,0 +1,421 @@
+//! Command line tool for compressing images to basis-universal and inspecting/unpacking the result.
+//!
+//! ```text
+//! basisu-rs compress input.png -o output.basis --uastc --mipmaps
+//! basisu-rs info output.basis
+//! basisu-rs validate output.basis --full
+//! basisu-rs unpack output.basis -o unpacked --format bc7_rgba
+//! ```
+
+use basis_universal::dds::DdsError;
+use basis_universal::ktx::KtxVersion;
+use basis_universal::{
+    BasisTextureFormat, ColorSpace, Compressor, CompressorParams, TranscodeError, Transcoder,
+    TranscoderTextureFormat, UserData, ETC1S_QUALITY_MAX, ETC1S_QUALITY_MIN, UASTC_QUALITY_MAX,
+    UASTC_QUALITY_MIN,
+};
+use clap::{Args, Parser, Subcommand};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "basisu-rs",
+    version,
+    about = "Compress, inspect and unpack basis-universal files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compress PNG/JPEG images into a .basis or .ktx2 file. Each input becomes one image.
+    Compress(CompressArgs),
+    /// Print the file header and per-image/per-level info of a .basis file
+    Info { file: PathBuf },
+    /// Validate the checksums of a .basis file
+    Validate {
+        file: PathBuf,
+        /// Also validate the checksum of the compressed data, not just the header
+        #[arg(long)]
+        full: bool,
+    },
+    /// Transcode a .basis file and write it out as PNG (uncompressed formats) or DDS/KTX2
+    /// (compressed formats)
+    Unpack(UnpackArgs),
+}
+
+#[derive(Args)]
+struct CompressArgs {
+    /// PNG or JPEG files to compress
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Output file. Writes .KTX2 if the extension is .ktx2
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Always write a .KTX2 file, regardless of the output extension
+    #[arg(long)]
+    ktx2: bool,
+    /// Use UASTC instead of ETC1S (higher quality, larger files)
+    #[arg(long)]
+    uastc: bool,
+    /// ETC1S quality level
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u32).range(ETC1S_QUALITY_MIN as i64..=ETC1S_QUALITY_MAX as i64)
+    )]
+    quality: Option<u32>,
+    /// UASTC quality level
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(UASTC_QUALITY_MIN as i64..=UASTC_QUALITY_MAX as i64)
+    )]
+    uastc_quality: Option<u32>,
+    /// The source images are linear rather than sRGB
+    #[arg(long)]
+    linear: bool,
+    /// Filter mipmaps in linear space, even if the images are sRGB
+    #[arg(long)]
+    mip_linear: bool,
+    /// Generate mipmaps for each image
+    #[arg(long)]
+    mipmaps: bool,
+    /// Smallest dimension of a generated mipmap
+    #[arg(long)]
+    mip_smallest: Option<u32>,
+    /// Disable selector rate distortion optimizations
+    #[arg(long)]
+    no_selector_rdo: bool,
+    /// Disable endpoint rate distortion optimizations
+    #[arg(long)]
+    no_endpoint_rdo: bool,
+    /// Enable UASTC RDO post-processing with the given quality scalar (good range is .2-4)
+    #[arg(long)]
+    rdo_uastc: Option<f32>,
+    /// Tune the settings for normal maps (linear, no RDO)
+    #[arg(long)]
+    normal_map: bool,
+    #[arg(long, default_value_t = 0)]
+    userdata0: u32,
+    #[arg(long, default_value_t = 0)]
+    userdata1: u32,
+    /// Total number of threads used for compression, including the calling thread
+    #[arg(long)]
+    threads: Option<u32>,
+    /// Print the encoder's status output
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Args)]
+struct UnpackArgs {
+    file: PathBuf,
+    /// Directory the unpacked files are written to
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Format to transcode to (for example RGBA32 or BC7_RGBA, case insensitive)
+    #[arg(short, long, default_value = "RGBA32", value_parser = parse_format)]
+    format: TranscoderTextureFormat,
+    /// Write compressed formats as sRGB
+    #[arg(long)]
+    srgb: bool,
+    /// Always write compressed formats as .KTX2, even if DDS supports them
+    #[arg(long)]
+    ktx2: bool,
+}
+
+const TRANSCODER_TEXTURE_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::BC4_R,
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::ETC2_EAC_R11,
+    TranscoderTextureFormat::ETC2_EAC_RG11,
+    TranscoderTextureFormat::RGBA32,
+    TranscoderTextureFormat::RGB565,
+    TranscoderTextureFormat::BGR565,
+    TranscoderTextureFormat::RGBA4444,
+];
+
+fn parse_format(name: &str) -> Result<TranscoderTextureFormat, String> {
+    TRANSCODER_TEXTURE_FORMATS
+        .iter()
+        .copied()
+        .find(|format| format!("{:?}", format).eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<_> = TRANSCODER_TEXTURE_FORMATS
+                .iter()
+                .map(|format| format!("{:?}", format))
+                .collect();
+            format!("unknown format, expected one of: {}", names.join(", "))
+        })
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Compress(args) => compress(args),
+        Command::Info { file } => info(&file),
+        Command::Validate { file, full } => validate(&file, full),
+        Command::Unpack(args) => unpack(args),
+    };
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn compress(args: CompressArgs) -> CliResult {
+    let mut params = CompressorParams::new();
+    params.set_print_status_to_stdout(args.verbose);
+
+    params.resize_source_image_list(args.inputs.len() as u32);
+    for (image_index, input) in args.inputs.iter().enumerate() {
+        params
+            .source_image_mut(image_index as u32)
+            .load_file(input)
+            .map_err(|error| format!("{}: {}", input.display(), error))?;
+    }
+
+    if args.uastc {
+        params.set_basis_format(BasisTextureFormat::UASTC4x4);
+    }
+    if let Some(quality) = args.quality {
+        params.set_etc1s_quality_level(quality);
+    }
+    if let Some(quality) = args.uastc_quality {
+        params.set_uastc_quality_level(quality);
+    }
+    if args.linear {
+        params.set_color_space(ColorSpace::Linear);
+    }
+    if args.mip_linear {
+        params.set_mip_color_space(ColorSpace::Linear);
+    }
+    params.set_generate_mipmaps(args.mipmaps);
+    if let Some(smallest_dimension) = args.mip_smallest {
+        params.set_mipmap_smallest_dimension(smallest_dimension);
+    }
+    params.set_no_selector_rdo(args.no_selector_rdo);
+    params.set_no_endpoint_rdo(args.no_endpoint_rdo);
+    params.set_rdo_uastc(args.rdo_uastc);
+    if args.normal_map {
+        params.tune_for_normal_maps();
+    }
+    params.set_userdata(UserData {
+        userdata0: args.userdata0,
+        userdata1: args.userdata1,
+    });
+
+    let write_ktx2 = args.ktx2 || has_extension(&args.output, "ktx2");
+    params.set_create_ktx2_file(write_ktx2);
+
+    let thread_count = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|count| count.get() as u32)
+            .unwrap_or(1)
+    });
+    if thread_count == 0 {
+        return Err("--threads must be at least 1".into());
+    }
+
+    let mut compressor = Compressor::new(thread_count);
+    let t0 = std::time::Instant::now();
+    unsafe {
+        if !compressor.init(&params) {
+            return Err("failed to initialize the compressor".into());
+        }
+        compressor
+            .process()
+            .map_err(|error| format!("compression failed: {:?}", error))?;
+    }
+    let t1 = std::time::Instant::now();
+
+    let output = if write_ktx2 {
+        compressor.ktx2_file()
+    } else {
+        compressor.basis_file()
+    };
+    std::fs::write(&args.output, output)?;
+
+    println!(
+        "Wrote {} ({} bytes, {:.3} bits per texel) in {:.1} ms",
+        args.output.display(),
+        output.len(),
+        compressor.bits_per_texel(),
+        (t1 - t0).as_secs_f64() * 1000.0
+    );
+    Ok(())
+}
+
+fn info(file: &Path) -> CliResult {
+    let data = std::fs::read(file)?;
+    let transcoder = Transcoder::new();
+    let file_info = transcoder.file_info(&data)?;
+    let summary = &file_info.summary;
+
+    println!("File: {}", file.display());
+    println!("  Version: {:#x}", summary.m_version);
+    println!(
+        "  Texture type: {:?}",
+        transcoder.basis_texture_type(&data)?
+    );
+    println!(
+        "  Texture format: {:?}",
+        transcoder.basis_texture_format(&data)?
+    );
+    println!("  Total images: {}", summary.m_total_images);
+    println!("  Total slices: {}", summary.m_total_slices);
+    println!("  Has alpha slices: {}", summary.m_has_alpha_slices);
+    println!("  Y flipped: {}", summary.m_y_flipped);
+    println!(
+        "  Userdata: {} {}",
+        summary.m_userdata0, summary.m_userdata1
+    );
+    println!("  Header size: {}", summary.m_total_header_size);
+    println!(
+        "  Selector codebook: {} selectors, {} bytes",
+        summary.m_total_selectors, summary.m_selector_codebook_size
+    );
+    println!(
+        "  Endpoint codebook: {} endpoints, {} bytes",
+        summary.m_total_endpoints, summary.m_endpoint_codebook_size
+    );
+    println!("  Tables size: {}", summary.m_tables_size);
+    println!("  Slices size: {}", summary.m_slices_size);
+
+    for image_index in 0..summary.m_total_images {
+        let image_info = transcoder.image_info(&data, image_index)?;
+        println!(
+            "Image {}: {}x{}, {} levels, alpha: {}, iframe: {}",
+            image_index,
+            image_info.m_orig_width,
+            image_info.m_orig_height,
+            image_info.m_total_levels,
+            image_info.m_alpha_flag,
+            image_info.m_iframe_flag
+        );
+
+        for level_index in 0..image_info.m_total_levels {
+            let level_info = transcoder.image_level_info(&data, image_index, level_index)?;
+            println!(
+                "  Level {}: {}x{}, {}x{} blocks, rgb {} bytes at {}, alpha {} bytes at {}",
+                level_index,
+                level_info.m_orig_width,
+                level_info.m_orig_height,
+                level_info.m_num_blocks_x,
+                level_info.m_num_blocks_y,
+                level_info.m_rgb_file_len,
+                level_info.m_rgb_file_ofs,
+                level_info.m_alpha_file_len,
+                level_info.m_alpha_file_ofs
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(
+    file: &Path,
+    full: bool,
+) -> CliResult {
+    let data = std::fs::read(file)?;
+    Transcoder::new().validate_file_checksums(&data, full)?;
+    println!("{}: OK", file.display());
+    Ok(())
+}
+
+fn unpack(args: UnpackArgs) -> CliResult {
+    let data = std::fs::read(&args.file)?;
+    let mut transcoder = Transcoder::new();
+    let basis_format = transcoder.basis_texture_format(&data)?;
+    if !basis_format.can_transcode_to_format(args.format) {
+        return Err(TranscodeError::TranscodeFormatNotSupported {
+            basis_format,
+            transcode_format: args.format,
+        }
+        .into());
+    }
+
+    std::fs::create_dir_all(&args.output)?;
+    let stem = args
+        .file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "unpacked".to_string());
+    let format_name = format!("{:?}", args.format);
+
+    transcoder.prepare_transcoding(&data)?;
+
+    if !args.format.is_compressed() {
+        for image_index in 0..transcoder.image_count(&data)? {
+            for level_index in 0..transcoder.image_level_count(&data, image_index)? {
+                let image = transcoder.decode_to_rgba_image_with_format(
+                    &data,
+                    image_index,
+                    level_index,
+                    args.format,
+                )?;
+                let path = args.output.join(format!(
+                    "{}_{}_image{}_level{}.png",
+                    stem, format_name, image_index, level_index
+                ));
+                image.save(&path)?;
+                println!("Wrote {}", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    if !args.ktx2 {
+        let path = args.output.join(format!("{}_{}.dds", stem, format_name));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match transcoder.write_dds(&data, args.format, args.srgb, &mut writer) {
+            Ok(()) => {
+                println!("Wrote {}", path.display());
+                return Ok(());
+            }
+            Err(DdsError::FormatNotSupported(_)) => {
+                drop(writer);
+                std::fs::remove_file(&path)?;
+                println!("DDS does not support {}, writing KTX2 instead", format_name);
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    let path = args.output.join(format!("{}_{}.ktx2", stem, format_name));
+    let mut writer = BufWriter::new(File::create(&path)?);
+    transcoder.write_ktx(&data, args.format, args.srgb, KtxVersion::Ktx2, &mut writer)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn has_extension(
+    path: &Path,
+    extension: &str,
+) -> bool {
+    path.extension()
+        .map(|path_extension| path_extension.eq_ignore_ascii_case(extension))
+        .unwrap_or(false)
+}
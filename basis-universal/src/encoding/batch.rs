@@ -0,0 +1,501 @@
+use super::*;
+use crate::{BasisTextureFormat, UserData};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Settings for compressing one image in a [BatchCompressor]. Unlike [CompressorParams], these are
+/// plain values, so they can be hashed to find the output of a previous run in the cache.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionSettings {
+    pub basis_format: BasisTextureFormat,
+    /// See [CompressorParams::set_etc1s_quality_level]
+    pub etc1s_quality_level: u32,
+    /// See [CompressorParams::set_uastc_quality_level]
+    pub uastc_quality_level: u32,
+    pub color_space: ColorSpace,
+    /// The color space used for mipmap filtering. Defaults to `color_space`.
+    pub mip_color_space: Option<ColorSpace>,
+    pub generate_mipmaps: bool,
+    pub mipmap_smallest_dimension: Option<u32>,
+    pub no_selector_rdo: bool,
+    pub no_endpoint_rdo: bool,
+    /// See [CompressorParams::set_rdo_uastc]
+    pub rdo_uastc: Option<f32>,
+    /// Applies [CompressorParams::tune_for_normal_maps] after the other settings
+    pub normal_map: bool,
+    pub userdata: UserData,
+    /// Write a .KTX2 file instead of a .basis file
+    pub create_ktx2_file: bool,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        CompressionSettings {
+            basis_format: BasisTextureFormat::ETC1S,
+            etc1s_quality_level: crate::ETC1S_QUALITY_DEFAULT,
+            uastc_quality_level: crate::UASTC_QUALITY_DEFAULT,
+            color_space: ColorSpace::Srgb,
+            mip_color_space: None,
+            generate_mipmaps: false,
+            mipmap_smallest_dimension: None,
+            no_selector_rdo: false,
+            no_endpoint_rdo: false,
+            rdo_uastc: None,
+            normal_map: false,
+            userdata: UserData::default(),
+            create_ktx2_file: false,
+        }
+    }
+}
+
+impl CompressionSettings {
+    /// Sets every option on the params. The source images are not changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a quality level is out of range, like the setters on [CompressorParams]
+    pub fn apply(
+        &self,
+        params: &mut CompressorParams,
+    ) {
+        params.set_basis_format(self.basis_format);
+        params.set_etc1s_quality_level(self.etc1s_quality_level);
+        params.set_uastc_quality_level(self.uastc_quality_level);
+        params.set_color_space(self.color_space);
+        params.set_mip_color_space(self.mip_color_space.unwrap_or(self.color_space));
+        params.set_generate_mipmaps(self.generate_mipmaps);
+        if let Some(smallest_dimension) = self.mipmap_smallest_dimension {
+            params.set_mipmap_smallest_dimension(smallest_dimension);
+        }
+        params.set_no_selector_rdo(self.no_selector_rdo);
+        params.set_no_endpoint_rdo(self.no_endpoint_rdo);
+        params.set_rdo_uastc(self.rdo_uastc);
+        if self.normal_map {
+            params.tune_for_normal_maps();
+        }
+        params.set_userdata(self.userdata);
+        params.set_create_ktx2_file(self.create_ktx2_file);
+    }
+
+    // The values that would make apply() panic
+    fn validate(&self) -> Result<(), BatchJobError> {
+        if self.etc1s_quality_level < crate::ETC1S_QUALITY_MIN
+            || self.etc1s_quality_level > crate::ETC1S_QUALITY_MAX
+        {
+            return Err(BatchJobError::InvalidSettings(format!(
+                "ETC1S quality level {} is outside of {}-{}",
+                self.etc1s_quality_level,
+                crate::ETC1S_QUALITY_MIN,
+                crate::ETC1S_QUALITY_MAX
+            )));
+        }
+
+        if self.uastc_quality_level < crate::UASTC_QUALITY_MIN
+            || self.uastc_quality_level > crate::UASTC_QUALITY_MAX
+        {
+            return Err(BatchJobError::InvalidSettings(format!(
+                "UASTC quality level {} is outside of {}-{}",
+                self.uastc_quality_level,
+                crate::UASTC_QUALITY_MIN,
+                crate::UASTC_QUALITY_MAX
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The file extension of the compressed output, without the leading dot
+    pub fn output_extension(&self) -> &'static str {
+        if self.create_ktx2_file {
+            "ktx2"
+        } else {
+            "basis"
+        }
+    }
+
+    // Every field in a fixed order. Changing this invalidates existing caches, so bump
+    // CACHE_VERSION when it changes.
+    fn serialize(&self) -> Vec<u8> {
+        fn push_u32(
+            bytes: &mut Vec<u8>,
+            value: u32,
+        ) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        fn push_color_space(
+            bytes: &mut Vec<u8>,
+            color_space: ColorSpace,
+        ) {
+            bytes.push(match color_space {
+                ColorSpace::Linear => 0,
+                ColorSpace::Srgb => 1,
+            });
+        }
+
+        let mut bytes = Vec::new();
+        push_u32(&mut bytes, self.basis_format as u32);
+        push_u32(&mut bytes, self.etc1s_quality_level);
+        push_u32(&mut bytes, self.uastc_quality_level);
+        push_color_space(&mut bytes, self.color_space);
+        match self.mip_color_space {
+            Some(color_space) => {
+                bytes.push(1);
+                push_color_space(&mut bytes, color_space);
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(self.generate_mipmaps as u8);
+        push_u32(&mut bytes, self.mipmap_smallest_dimension.unwrap_or(0));
+        bytes.push(self.mipmap_smallest_dimension.is_some() as u8);
+        bytes.push(self.no_selector_rdo as u8);
+        bytes.push(self.no_endpoint_rdo as u8);
+        push_u32(&mut bytes, self.rdo_uastc.map(f32::to_bits).unwrap_or(0));
+        bytes.push(self.rdo_uastc.is_some() as u8);
+        bytes.push(self.normal_map as u8);
+        push_u32(&mut bytes, self.userdata.userdata0);
+        push_u32(&mut bytes, self.userdata.userdata1);
+        bytes.push(self.create_ktx2_file as u8);
+        bytes
+    }
+}
+
+/// A PNG or JPEG file to compress and where to write the result
+#[derive(Debug, Clone)]
+pub struct CompressionJob {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub settings: CompressionSettings,
+}
+
+impl CompressionJob {
+    /// Creates a job for every .png, .jpg and .jpeg file in `input_dir` (not recursive). Outputs
+    /// are written to `output_dir` with the same file stem. Jobs are sorted by input path.
+    pub fn from_directory<P: AsRef<Path>, Q: AsRef<Path>>(
+        input_dir: P,
+        output_dir: Q,
+        settings: &CompressionSettings,
+    ) -> std::io::Result<Vec<CompressionJob>> {
+        let mut inputs = Vec::new();
+        for entry in std::fs::read_dir(input_dir)? {
+            let path = entry?.path();
+            let is_image = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| {
+                    ["png", "jpg", "jpeg"]
+                        .iter()
+                        .any(|image_extension| extension.eq_ignore_ascii_case(image_extension))
+                })
+                .unwrap_or(false);
+
+            if is_image && path.is_file() {
+                inputs.push(path);
+            }
+        }
+        inputs.sort();
+
+        Ok(inputs
+            .into_iter()
+            .map(|input| {
+                // Not with_extension(), which would replace part of a stem like "brick.normal"
+                let output = output_dir.as_ref().join(format!(
+                    "{}.{}",
+                    input.file_stem().unwrap().to_string_lossy(),
+                    settings.output_extension()
+                ));
+                CompressionJob {
+                    input,
+                    output,
+                    settings: settings.clone(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Timing and size of a job that completed successfully
+#[derive(Debug, Copy, Clone)]
+pub struct CompressionJobReport {
+    /// True if the output was copied from the cache instead of being compressed
+    pub cache_hit: bool,
+    /// Time spent on the job, including reading the input and writing the output
+    pub duration: Duration,
+    pub input_size: usize,
+    pub output_size: usize,
+}
+
+/// Error result from a single job run by a [BatchCompressor]
+#[derive(Debug)]
+pub enum BatchJobError {
+    /// Failed to read the input or write the output or cache files
+    Io(std::io::Error),
+    /// The input is not a valid PNG or JPEG file
+    ImageLoad(ImageLoadError),
+    /// The job's settings are out of range
+    InvalidSettings(String),
+    /// The compressor rejected the settings
+    InitFailed,
+    /// The compressor failed to encode the image
+    Compress(CompressorErrorCode),
+}
+
+impl std::fmt::Display for BatchJobError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            BatchJobError::Io(error) => write!(f, "{}", error),
+            BatchJobError::ImageLoad(error) => write!(f, "{}", error),
+            BatchJobError::InvalidSettings(reason) => write!(f, "invalid settings: {}", reason),
+            BatchJobError::InitFailed => write!(f, "failed to initialize the compressor"),
+            BatchJobError::Compress(error_code) => {
+                write!(f, "compression failed: {:?}", error_code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchJobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchJobError::Io(error) => Some(error),
+            BatchJobError::ImageLoad(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for BatchJobError {
+    fn from(error: std::io::Error) -> Self {
+        BatchJobError::Io(error)
+    }
+}
+
+impl From<ImageLoadError> for BatchJobError {
+    fn from(error: ImageLoadError) -> Self {
+        BatchJobError::ImageLoad(error)
+    }
+}
+
+impl From<CompressorErrorCode> for BatchJobError {
+    fn from(error_code: CompressorErrorCode) -> Self {
+        BatchJobError::Compress(error_code)
+    }
+}
+
+// Part of every cache key, so outputs from older versions of this crate (or of the key format)
+// are never reused
+const CACHE_VERSION: &[u8] =
+    concat!("basis-universal-batch-1-", env!("CARGO_PKG_VERSION")).as_bytes();
+
+/// Compresses many images across a set of worker threads, each with its own [Compressor].
+///
+/// If a cache directory is set, the output of each job is stored there under a hash of the input
+/// file's contents and the job's settings. Later jobs with the same input and settings copy the
+/// cached output instead of compressing again.
+#[derive(Debug, Clone)]
+pub struct BatchCompressor {
+    /// Number of jobs that are compressed at the same time. 0 is treated as 1.
+    pub worker_count: usize,
+    /// Passed to [Compressor::new] for each worker. Ignored if `job_pool` is set. 0 is treated as
+    /// 1.
+    pub threads_per_compressor: u32,
+    /// Shared by the compressors of every worker instead of each starting its own threads
+    ///
+    /// The workers still load images and initialize their compressors in parallel, but take turns
+    /// in [Compressor::process] (see [JobPool]), so only one image is encoded at a time. This
+    /// bounds the encoder threads to the size of the pool at the cost of throughput: encoding
+    /// takes about as long as with one worker whose compressor has every thread of the pool.
+    /// Without a pool, `worker_count` images are encoded at the same time with
+    /// `threads_per_compressor` threads each. The benchmark example compares the two.
+    pub job_pool: Option<JobPool>,
+    /// Directory for cached outputs. It is created if it doesn't exist.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for BatchCompressor {
+    fn default() -> Self {
+        BatchCompressor {
+            worker_count: std::thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            threads_per_compressor: 1,
+            job_pool: None,
+            cache_dir: None,
+        }
+    }
+}
+
+impl BatchCompressor {
+    /// Runs every job and returns the results in the same order as the jobs. A failed job does not
+    /// stop the others.
+    pub fn run(
+        &self,
+        jobs: &[CompressionJob],
+    ) -> Vec<Result<CompressionJobReport, BatchJobError>> {
+        if let Some(cache_dir) = &self.cache_dir {
+            if let Err(error) = std::fs::create_dir_all(cache_dir) {
+                return jobs
+                    .iter()
+                    .map(|_| Err(BatchJobError::Io(clone_io_error(&error))))
+                    .collect();
+            }
+        }
+
+        let next_job_index = AtomicUsize::new(0);
+        let mut results: Vec<_> = jobs.iter().map(|_| None).collect();
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.worker_count.max(1).min(jobs.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        // Only created once a job misses the cache
+                        let mut compressor = None;
+                        let mut worker_results = Vec::new();
+                        loop {
+                            let job_index = next_job_index.fetch_add(1, Ordering::Relaxed);
+                            if job_index >= jobs.len() {
+                                break;
+                            }
+
+                            let result = self.run_job(job_index, &jobs[job_index], &mut compressor);
+                            worker_results.push((job_index, result));
+                        }
+                        worker_results
+                    })
+                })
+                .collect();
+
+            for worker in workers {
+                for (job_index, result) in worker.join().unwrap() {
+                    results[job_index] = Some(result);
+                }
+            }
+        });
+
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn run_job(
+        &self,
+        job_index: usize,
+        job: &CompressionJob,
+        compressor: &mut Option<Compressor>,
+    ) -> Result<CompressionJobReport, BatchJobError> {
+        let t0 = Instant::now();
+        job.settings.validate()?;
+        let input = std::fs::read(&job.input)?;
+
+        let cache_path = self.cache_dir.as_ref().map(|cache_dir| {
+            cache_dir
+                .join(format!("{:032x}", cache_key(&input, &job.settings)))
+                .with_extension(job.settings.output_extension())
+        });
+
+        if let Some(cache_path) = &cache_path {
+            match std::fs::read(cache_path) {
+                Ok(cached) => {
+                    write_output(&job.output, &cached)?;
+                    return Ok(CompressionJobReport {
+                        cache_hit: true,
+                        duration: t0.elapsed(),
+                        input_size: input.len(),
+                        output_size: cached.len(),
+                    });
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        let format = SourceFileFormat::detect(&input).ok_or(ImageLoadError::UnknownFormat)?;
+        let mut params = CompressorParams::new();
+        job.settings.apply(&mut params);
+        let mut source_image = params.source_image_mut(0);
+        match format {
+            SourceFileFormat::Png => source_image.load_png_bytes(&input)?,
+            SourceFileFormat::Jpeg => source_image.load_jpeg_bytes(&input)?,
+        }
+
+        let compressor = compressor.get_or_insert_with(|| match &self.job_pool {
+            Some(job_pool) => Compressor::with_job_pool(job_pool),
+            None => Compressor::new(self.threads_per_compressor.max(1)),
+        });
+        unsafe {
+            if !compressor.init(&params) {
+                return Err(BatchJobError::InitFailed);
+            }
+            compressor.process()?;
+        }
+
+        let output = if job.settings.create_ktx2_file {
+            compressor.ktx2_file()
+        } else {
+            compressor.basis_file()
+        };
+        write_output(&job.output, output)?;
+
+        if let Some(cache_path) = &cache_path {
+            // Write to a temporary file first so that a cache entry is never partially written,
+            // even if two jobs with the same key finish at the same time
+            let temp_path = cache_path.with_extension(format!(
+                "{}.{}-{}.tmp",
+                job.settings.output_extension(),
+                std::process::id(),
+                job_index
+            ));
+            std::fs::write(&temp_path, output)?;
+            std::fs::rename(&temp_path, cache_path)?;
+        }
+
+        Ok(CompressionJobReport {
+            cache_hit: false,
+            duration: t0.elapsed(),
+            input_size: input.len(),
+            output_size: output.len(),
+        })
+    }
+}
+
+fn write_output(
+    path: &Path,
+    data: &[u8],
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, data)
+}
+
+// io::Error isn't Clone
+fn clone_io_error(error: &std::io::Error) -> std::io::Error {
+    std::io::Error::new(error.kind(), error.to_string())
+}
+
+// 128-bit FNV-1a over the cache version, the settings and the input file. This must stay stable
+// across builds and platforms, so std's Hasher can't be used.
+fn cache_key(
+    input: &[u8],
+    settings: &CompressionSettings,
+) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013B;
+
+    let settings = settings.serialize();
+    let mut hash = OFFSET_BASIS;
+    for bytes in [
+        CACHE_VERSION,
+        &(settings.len() as u64).to_le_bytes()[..],
+        &settings[..],
+        &(input.len() as u64).to_le_bytes()[..],
+        input,
+    ] {
+        for byte in bytes {
+            hash ^= *byte as u128;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
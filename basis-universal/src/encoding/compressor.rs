@@ -1,39 +1,116 @@
 use super::*;
 use basis_universal_sys as sys;
 pub use basis_universal_sys::ColorU8;
+use std::convert::TryFrom;
 
 /// Error codes that can be returned when encoding basis-universal data with a [Compressor]
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug, PartialEq)]
-#[repr(i32)]
 pub enum CompressorErrorCode {
-    cECFailedReadingSourceImages =
-        sys::basisu_basis_compressor_error_code_cECFailedReadingSourceImages,
-    cECFailedValidating = sys::basisu_basis_compressor_error_code_cECFailedValidating,
-    cECFailedEncodeUASTC = sys::basisu_basis_compressor_error_code_cECFailedEncodeUASTC,
-    cECFailedFrontEnd = sys::basisu_basis_compressor_error_code_cECFailedFrontEnd,
-    cECFailedFontendExtract = sys::basisu_basis_compressor_error_code_cECFailedFontendExtract,
-    cECFailedBackend = sys::basisu_basis_compressor_error_code_cECFailedBackend,
-    cECFailedCreateBasisFile = sys::basisu_basis_compressor_error_code_cECFailedCreateBasisFile,
-    cECFailedWritingOutput = sys::basisu_basis_compressor_error_code_cECFailedWritingOutput,
-    cECFailedUASTCRDOPostProcess =
-        sys::basisu_basis_compressor_error_code_cECFailedUASTCRDOPostProcess,
+    cECFailedInitializing,
+    cECFailedReadingSourceImages,
+    cECFailedValidating,
+    cECFailedEncodeUASTC,
+    cECFailedFrontEnd,
+    cECFailedFontendExtract,
+    cECFailedBackend,
+    cECFailedCreateBasisFile,
+    cECFailedWritingOutput,
+    cECFailedUASTCRDOPostProcess,
+    cECFailedCreateKTX2File,
+    /// An error code that is not known to this version of the bindings
+    Unknown(sys::basisu_basis_compressor_error_code),
 }
 
 impl Into<sys::basisu_basis_compressor_error_code> for CompressorErrorCode {
     fn into(self) -> sys::basisu_basis_compressor_error_code {
-        self as sys::basisu_basis_compressor_error_code
+        match self {
+            CompressorErrorCode::cECFailedInitializing => {
+                sys::basisu_basis_compressor_error_code_cECFailedInitializing
+            }
+            CompressorErrorCode::cECFailedReadingSourceImages => {
+                sys::basisu_basis_compressor_error_code_cECFailedReadingSourceImages
+            }
+            CompressorErrorCode::cECFailedValidating => {
+                sys::basisu_basis_compressor_error_code_cECFailedValidating
+            }
+            CompressorErrorCode::cECFailedEncodeUASTC => {
+                sys::basisu_basis_compressor_error_code_cECFailedEncodeUASTC
+            }
+            CompressorErrorCode::cECFailedFrontEnd => {
+                sys::basisu_basis_compressor_error_code_cECFailedFrontEnd
+            }
+            CompressorErrorCode::cECFailedFontendExtract => {
+                sys::basisu_basis_compressor_error_code_cECFailedFontendExtract
+            }
+            CompressorErrorCode::cECFailedBackend => {
+                sys::basisu_basis_compressor_error_code_cECFailedBackend
+            }
+            CompressorErrorCode::cECFailedCreateBasisFile => {
+                sys::basisu_basis_compressor_error_code_cECFailedCreateBasisFile
+            }
+            CompressorErrorCode::cECFailedWritingOutput => {
+                sys::basisu_basis_compressor_error_code_cECFailedWritingOutput
+            }
+            CompressorErrorCode::cECFailedUASTCRDOPostProcess => {
+                sys::basisu_basis_compressor_error_code_cECFailedUASTCRDOPostProcess
+            }
+            CompressorErrorCode::cECFailedCreateKTX2File => {
+                sys::basisu_basis_compressor_error_code_cECFailedCreateKTX2File
+            }
+            CompressorErrorCode::Unknown(value) => value,
+        }
     }
 }
 
-impl From<sys::basisu_basis_compressor_error_code> for CompressorErrorCode {
-    fn from(value: sys::basisu_basis_compressor_error_code) -> Self {
-        unsafe { std::mem::transmute(value as u32) }
+/// Fails only for `cECSuccess`, which is not an error. Values that are not known to this version of
+/// the bindings are returned as [CompressorErrorCode::Unknown].
+impl TryFrom<sys::basisu_basis_compressor_error_code> for CompressorErrorCode {
+    type Error = sys::basisu_basis_compressor_error_code;
+
+    fn try_from(value: sys::basisu_basis_compressor_error_code) -> Result<Self, Self::Error> {
+        match value {
+            sys::basisu_basis_compressor_error_code_cECSuccess => Err(value),
+            sys::basisu_basis_compressor_error_code_cECFailedInitializing => {
+                Ok(CompressorErrorCode::cECFailedInitializing)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedReadingSourceImages => {
+                Ok(CompressorErrorCode::cECFailedReadingSourceImages)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedValidating => {
+                Ok(CompressorErrorCode::cECFailedValidating)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedEncodeUASTC => {
+                Ok(CompressorErrorCode::cECFailedEncodeUASTC)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedFrontEnd => {
+                Ok(CompressorErrorCode::cECFailedFrontEnd)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedFontendExtract => {
+                Ok(CompressorErrorCode::cECFailedFontendExtract)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedBackend => {
+                Ok(CompressorErrorCode::cECFailedBackend)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedCreateBasisFile => {
+                Ok(CompressorErrorCode::cECFailedCreateBasisFile)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedWritingOutput => {
+                Ok(CompressorErrorCode::cECFailedWritingOutput)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedUASTCRDOPostProcess => {
+                Ok(CompressorErrorCode::cECFailedUASTCRDOPostProcess)
+            }
+            sys::basisu_basis_compressor_error_code_cECFailedCreateKTX2File => {
+                Ok(CompressorErrorCode::cECFailedCreateKTX2File)
+            }
+            _ => Ok(CompressorErrorCode::Unknown(value)),
+        }
     }
 }
 
 /// Used to encode raw image data to basis-universal form
-pub struct Compressor(pub *mut sys::Compressor);
+pub struct Compressor(pub *mut sys::Compressor, Option<JobPool>);
 
 unsafe impl Send for Compressor {}
 
@@ -46,14 +123,36 @@ impl Compressor {
         encoder_init();
         unsafe {
             assert!(total_thread_count > 0);
-            Compressor(sys::compressor_new(total_thread_count as _))
+            Compressor(sys::compressor_new(total_thread_count as _), None)
+        }
+    }
+
+    /// Create a compressor that uses the threads of a shared [JobPool] instead of starting its own.
+    /// The compressor keeps the pool alive.
+    pub fn with_job_pool(job_pool: &JobPool) -> Self {
+        encoder_init();
+        unsafe {
+            Compressor(
+                sys::compressor_new_with_job_pool(job_pool.as_ptr()),
+                Some(job_pool.clone()),
+            )
         }
     }
 
+    /// The shared job pool this compressor was created with, if any
+    pub fn job_pool(&self) -> Option<&JobPool> {
+        self.1.as_ref()
+    }
+
     /// Configure the compressor to compress images. `CompressorParams` includes both the image data
     /// and parameters that affect compression (such as quality or whether mipmaps should be
     /// generated)
     ///
+    /// A compressor can be reused for any number of `init()`/`process()` cycles. Calling `init()`
+    /// again discards the previous output, so nothing from the previous images carries over. The
+    /// underlying encoder, its buffers and the threads are kept, so later cycles reuse the memory of
+    /// earlier ones instead of allocating it again.
+    ///
     /// # Safety
     ///
     /// Passing invalid parameters may cause undefined behavior. (The underlying C++ library does
@@ -65,6 +164,15 @@ impl Compressor {
         sys::compressor_init(self.0, params.0)
     }
 
+    /// Clears the output of previous `init()`/`process()` calls and frees the memory it used. The
+    /// underlying encoder and the threads (or shared [JobPool]) are kept. This is not required
+    /// before calling `init()` again, but releases the memory held by the last output.
+    pub fn reset(&mut self) {
+        unsafe {
+            sys::compressor_reset(self.0);
+        }
+    }
+
     /// Encodes the images as configured when calling `init()`
     ///
     /// # Safety
@@ -72,18 +180,38 @@ impl Compressor {
     /// Compressing with invalid parameters may cause undefined behavior. (The underlying C++
     /// library does not thoroughly validate parameters)
     pub unsafe fn process(&mut self) -> Result<(), CompressorErrorCode> {
+        let _process_lock = self.1.as_ref().map(|job_pool| job_pool.lock_for_process());
         let result = sys::compressor_process(self.0);
         if result == sys::basisu_basis_compressor_error_code_cECSuccess {
             Ok(())
         } else {
-            Err(result.into())
+            Err(CompressorErrorCode::try_from(result)
+                .unwrap_or(CompressorErrorCode::Unknown(result)))
         }
     }
 
-    /// Access the compressed data. May be empty if `process()` was not yet called
+    /// Access the compressed data. Empty if `process()` was not yet called since the last `init()`
+    /// or `reset()`
     pub fn basis_file(&self) -> &[u8] {
         unsafe {
             let result = sys::compressor_get_output_basis_file(self.0);
+            // An empty std::vector may return a null pointer, which a slice must not have
+            if result.length == 0 {
+                return &[];
+            }
+            std::slice::from_raw_parts(result.pData, result.length as usize)
+        }
+    }
+
+    /// Access the compressed data as a .KTX2 file. Empty unless
+    /// [CompressorParams::set_create_ktx2_file] was enabled before calling `process()`
+    pub fn ktx2_file(&self) -> &[u8] {
+        unsafe {
+            let result = sys::compressor_get_output_ktx2_file(self.0);
+            // An empty std::vector may return a null pointer, which a slice must not have
+            if result.length == 0 {
+                return &[];
+            }
             std::slice::from_raw_parts(result.pData, result.length as usize)
         }
     }
@@ -1,5 +1,9 @@
+use super::CompressorParams;
 use basis_universal_sys as sys;
 pub use basis_universal_sys::ColorU8;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::path::Path;
 
 // foreign_types::foreign_type! {
 //     /// A Foo.
@@ -11,162 +15,603 @@ pub use basis_universal_sys::ColorU8;
 //     }
 // }
 
-/// A reference to an image being stored by [CompressorParams](super::CompressorParams). Generally
-/// used to insert the source data that is to be encoded by a [Compressor](super::Compressor).
-pub struct CompressorImageRef(pub *mut sys::basisu_image);
+/// Uncompressed pixel data in the layout expected by [CompressorImageRef::init]: 8 bits per
+/// channel, 1-4 channels, rows tightly packed
+#[derive(Debug, Clone)]
+pub struct SourceImageData<'a> {
+    pub data: Cow<'a, [u8]>,
+    pub width: u32,
+    pub height: u32,
+    pub channel_count: u8,
+}
 
-impl CompressorImageRef {
-    /// Sets the image to be completely empty (i.e. 0 width, 0 height). (This was called `clear` in
-    /// the upstream API.)
-    pub fn invalidate(&mut self) {
-        unsafe {
-            sys::image_clear(self.0);
+/// 8-bit images are borrowed as-is. Other images (16-bit, BGR) are converted to 8-bit RGBA.
+#[cfg(feature = "image")]
+impl<'a> From<&'a image::DynamicImage> for SourceImageData<'a> {
+    fn from(image: &'a image::DynamicImage) -> Self {
+        use image::{DynamicImage, GenericImageView};
+        let (data, channel_count) = match image {
+            DynamicImage::ImageLuma8(buffer) => (Cow::Borrowed(&buffer.as_raw()[..]), 1),
+            DynamicImage::ImageLumaA8(buffer) => (Cow::Borrowed(&buffer.as_raw()[..]), 2),
+            DynamicImage::ImageRgb8(buffer) => (Cow::Borrowed(&buffer.as_raw()[..]), 3),
+            DynamicImage::ImageRgba8(buffer) => (Cow::Borrowed(&buffer.as_raw()[..]), 4),
+            _ => (Cow::Owned(image.to_rgba8().into_raw()), 4),
+        };
+
+        SourceImageData {
+            data,
+            width: image.width(),
+            height: image.height(),
+            channel_count,
         }
     }
+}
 
-    /// Resizes the image to the given width/height
-    ///
-    /// By default the pitch will be equal to the width. To customize this, use `resize_with_pitch`
-    pub fn resize(
-        &mut self,
-        width: u32,
-        height: u32,
-    ) {
-        unsafe {
-            sys::image_resize(self.0, width, height);
+/// Source file formats that can be read by the decoders bundled with the encoder
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SourceFileFormat {
+    Png,
+    Jpeg,
+}
+
+impl SourceFileFormat {
+    /// Detects the format from the signature at the start of the file
+    pub fn detect(data: &[u8]) -> Option<SourceFileFormat> {
+        if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(SourceFileFormat::Png)
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(SourceFileFormat::Jpeg)
+        } else {
+            None
         }
     }
+}
 
-    /// Resize the image to the given width/height with a custom "pitch". The pitch is the
-    /// offset between rows and is not needed for all formats. By default, the pitch will be equal
-    /// to the width
-    pub fn resize_with_pitch(
-        &mut self,
-        width: u32,
-        height: u32,
-        pitch: u32,
-    ) {
-        unsafe {
-            sys::image_resize_with_pitch(self.0, width, height, pitch);
+/// Error result from loading a source image file
+#[derive(Debug)]
+pub enum ImageLoadError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file is neither a PNG nor a JPEG file
+    UnknownFormat,
+    /// The data does not start with the signature of the expected format
+    InvalidSignature(SourceFileFormat),
+    /// The file is corrupt, truncated or uses features the decoder doesn't support
+    DecodeFailed(SourceFileFormat),
+}
+
+impl std::fmt::Display for ImageLoadError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            ImageLoadError::Io(error) => write!(f, "failed to read image file: {}", error),
+            ImageLoadError::UnknownFormat => write!(f, "image is neither a PNG nor a JPEG file"),
+            ImageLoadError::InvalidSignature(format) => {
+                write!(f, "image is not a {:?} file", format)
+            }
+            ImageLoadError::DecodeFailed(format) => {
+                write!(f, "failed to decode {:?} image", format)
+            }
         }
     }
+}
 
-    /// Resize the image and populate it with the given data.
-    ///
-    /// channel_count should be the number of channels in the image (so >=1 and <= 4)
-    pub fn init(
-        &mut self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        channel_count: u8,
-    ) {
-        unsafe {
-            sys::image_init(self.0, data.as_ptr(), width, height, channel_count as _);
+impl std::error::Error for ImageLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageLoadError::Io(error) => Some(error),
+            _ => None,
         }
     }
+}
 
-    /// Returns the pixel value at a given x,y
-    pub fn pixel_at(
-        &self,
-        width: u32,
-        height: u32,
-    ) -> Option<ColorU8> {
-        unsafe {
-            let mut color = ColorU8 { combined: 0 };
+impl From<std::io::Error> for ImageLoadError {
+    fn from(error: std::io::Error) -> Self {
+        ImageLoadError::Io(error)
+    }
+}
 
-            if sys::image_get_pixel_at_checked(self.0, width, height, &mut color as *mut _) {
-                Some(color)
+// The methods shared by CompressorImageRef and CompressorImage. Both store the image pointer in
+// field 0.
+macro_rules! compressor_image_methods {
+    () => {
+        /// Sets the image to be completely empty (i.e. 0 width, 0 height). (This was called `clear`
+        /// in the upstream API.)
+        pub fn invalidate(&mut self) {
+            unsafe {
+                sys::image_clear(self.0);
+            }
+        }
+
+        /// Resizes the image to the given width/height
+        ///
+        /// By default the pitch will be equal to the width. To customize this, use
+        /// `resize_with_pitch`
+        pub fn resize(
+            &mut self,
+            width: u32,
+            height: u32,
+        ) {
+            unsafe {
+                sys::image_resize(self.0, width, height);
+            }
+        }
+
+        /// Resize the image to the given width/height with a custom "pitch". The pitch is the
+        /// offset between rows and is not needed for all formats. By default, the pitch will be
+        /// equal to the width
+        pub fn resize_with_pitch(
+            &mut self,
+            width: u32,
+            height: u32,
+            pitch: u32,
+        ) {
+            unsafe {
+                sys::image_resize_with_pitch(self.0, width, height, pitch);
+            }
+        }
+
+        /// Resize the image and populate it with the given data.
+        ///
+        /// channel_count should be the number of channels in the image (so >=1 and <= 4)
+        ///
+        /// # Panics
+        ///
+        /// Panics if channel_count is not 1-4, or if `data` is too short to hold `width` by
+        /// `height` pixels with `channel_count` bytes each
+        pub fn init(
+            &mut self,
+            data: &[u8],
+            width: u32,
+            height: u32,
+            channel_count: u8,
+        ) {
+            assert!(
+                (1..=4).contains(&channel_count),
+                "channel count {} is not 1-4",
+                channel_count
+            );
+            let required_length = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|length| length.checked_mul(channel_count as usize));
+            assert!(
+                required_length.map_or(false, |length| data.len() >= length),
+                "buffer of {} bytes is too short for {}x{} pixels with {} channels",
+                data.len(),
+                width,
+                height,
+                channel_count
+            );
+
+            unsafe {
+                sys::image_init(self.0, data.as_ptr(), width, height, channel_count as _);
+            }
+        }
+
+        /// Resize the image and populate it with the given data, which can be created from an
+        /// `image::DynamicImage` when the `image` feature is enabled
+        pub fn init_from<'a, T: Into<SourceImageData<'a>>>(
+            &mut self,
+            source: T,
+        ) {
+            let source = source.into();
+            self.init(
+                &source.data,
+                source.width,
+                source.height,
+                source.channel_count,
+            );
+        }
+
+        /// Decodes a PNG file into the image, using the PNG reader bundled with the encoder. The
+        /// image is left empty if decoding fails.
+        pub fn load_png_bytes(
+            &mut self,
+            data: &[u8],
+        ) -> Result<(), ImageLoadError> {
+            self.load_bytes(data, SourceFileFormat::Png)
+        }
+
+        /// Decodes a JPEG file into the image, using the JPEG reader bundled with the encoder. The
+        /// image is left empty if decoding fails.
+        pub fn load_jpeg_bytes(
+            &mut self,
+            data: &[u8],
+        ) -> Result<(), ImageLoadError> {
+            self.load_bytes(data, SourceFileFormat::Jpeg)
+        }
+
+        /// Reads a PNG or JPEG file into the image. The format is detected from the file's contents
+        /// rather than its extension. The image is left empty if decoding fails.
+        pub fn load_file<P: AsRef<Path>>(
+            &mut self,
+            path: P,
+        ) -> Result<(), ImageLoadError> {
+            let data = std::fs::read(path)?;
+            let format = SourceFileFormat::detect(&data).ok_or(ImageLoadError::UnknownFormat)?;
+            self.load_bytes(&data, format)
+        }
+
+        fn load_bytes(
+            &mut self,
+            data: &[u8],
+            format: SourceFileFormat,
+        ) -> Result<(), ImageLoadError> {
+            if SourceFileFormat::detect(data) != Some(format) {
+                return Err(ImageLoadError::InvalidSignature(format));
+            }
+
+            // The JPEG reader takes the size as an int
+            let loaded = data.len() <= i32::MAX as usize
+                && unsafe {
+                    match format {
+                        SourceFileFormat::Png => {
+                            sys::image_load_png(self.0, data.as_ptr(), data.len() as u32)
+                        }
+                        SourceFileFormat::Jpeg => {
+                            sys::image_load_jpeg(self.0, data.as_ptr(), data.len() as u32)
+                        }
+                    }
+                };
+
+            if loaded {
+                Ok(())
             } else {
-                None
+                self.invalidate();
+                Err(ImageLoadError::DecodeFailed(format))
             }
         }
-    }
 
-    /// Returns teh pixel value at a given x,y without doing bounds checking
-    ///
-    /// # Safety
-    ///
-    /// Accessing pixel out of bounds of the image will result in undefined behavior
-    pub unsafe fn pixel_at_unchecked(
-        &self,
-        width: u32,
-        height: u32,
-    ) -> ColorU8 {
-        sys::image_get_pixel_at_unchecked(self.0, width, height)
-    }
+        /// Returns the pixel value at a given x,y
+        pub fn pixel_at(
+            &self,
+            x: u32,
+            y: u32,
+        ) -> Option<ColorU8> {
+            unsafe {
+                let mut color = ColorU8 { combined: 0 };
 
-    /// Returns the width of the image in pixels
-    pub fn width(&self) -> u32 {
-        unsafe { sys::image_get_width(self.0) }
-    }
+                if sys::image_get_pixel_at_checked(self.0, x, y, &mut color as *mut _) {
+                    Some(color)
+                } else {
+                    None
+                }
+            }
+        }
 
-    /// Returns the height of the image in pixels
-    pub fn height(&self) -> u32 {
-        unsafe { sys::image_get_height(self.0) }
-    }
+        /// Returns the pixel value at a given x,y without doing bounds checking
+        ///
+        /// # Safety
+        ///
+        /// Accessing pixel out of bounds of the image will result in undefined behavior
+        pub unsafe fn pixel_at_unchecked(
+            &self,
+            x: u32,
+            y: u32,
+        ) -> ColorU8 {
+            sys::image_get_pixel_at_unchecked(self.0, x, y)
+        }
 
-    /// Returns the pitch of the image in pixels, which represents the offset between rows
-    pub fn pitch(&self) -> u32 {
-        unsafe { sys::image_get_pitch(self.0) }
-    }
+        /// Sets the pixel value at a given x,y
+        ///
+        /// # Panics
+        ///
+        /// Panics if x,y is outside of the image
+        pub fn set_pixel(
+            &mut self,
+            x: u32,
+            y: u32,
+            color: ColorU8,
+        ) {
+            let (width, height) = (self.width(), self.height());
+            assert!(
+                x < width && y < height,
+                "pixel {},{} is outside of the {}x{} image",
+                x,
+                y,
+                width,
+                height
+            );
+            let index = y as usize * self.pitch() as usize + x as usize;
+            self.pixel_data_mut()[index] = color;
+        }
 
-    /// Returns the total number of pixels in the image
-    pub fn total_pixels(&self) -> u32 {
-        unsafe { sys::image_get_total_pixels(self.0) }
-    }
+        /// Sets every pixel in the image to the given color
+        pub fn fill(
+            &mut self,
+            color: ColorU8,
+        ) {
+            for row in self.rows_mut() {
+                for pixel in row {
+                    *pixel = color;
+                }
+            }
+        }
 
-    /// Returns how many blocks wide the image is, given `w`, the width of a block in pixels
-    pub fn block_width(
-        &self,
-        w: u32,
-    ) -> u32 {
-        unsafe { sys::image_get_block_width(self.0, w) }
-    }
+        /// Returns an iterator over the rows of the image, from top to bottom. Each row is `width`
+        /// pixels long, so the padding between the end of a row and the start of the next (when the
+        /// pitch is larger than the width) is skipped.
+        pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [ColorU8]> + '_ {
+            let width = self.width() as usize;
+            let height = self.height() as usize;
+            // The pitch is 0 for an empty image, which chunks_mut() doesn't accept
+            let pitch = (self.pitch() as usize).max(1);
+            self.pixel_data_mut()
+                .chunks_mut(pitch)
+                .take(height)
+                .map(move |row| &mut row[..width])
+        }
 
-    /// Returns how many blocks high the image is, given `h`, the height of a block in pixels
-    pub fn block_height(
-        &self,
-        h: u32,
-    ) -> u32 {
-        unsafe { sys::image_get_block_height(self.0, h) }
-    }
+        /// Copies 8-bit RGBA pixel data into the image, which must already have the desired size.
+        /// `stride` is the offset in bytes between the starts of two rows in `data`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `data` is too short to hold `width` by `height` pixels with the given stride
+        pub fn copy_from_rgba(
+            &mut self,
+            data: &[u8],
+            stride: usize,
+        ) {
+            let (width, height) = (self.width(), self.height());
+            self.copy_rect_from_rgba(0, 0, width, height, data, stride);
+        }
 
-    /// Returns the number of blocks required to store the image, given `w` and `h`, the width and
-    /// height of a block in pixels
-    pub fn total_blocks(
-        &self,
-        w: u32,
-        h: u32,
-    ) -> u32 {
-        unsafe { sys::image_get_total_blocks(self.0, w, h) }
-    }
+        /// Copies 8-bit RGBA pixel data into the `width` by `height` rectangle of the image that
+        /// starts at x,y. `stride` is the offset in bytes between the starts of two rows in `data`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the rectangle is not inside the image, or if `data` is too short to hold
+        /// `width` by `height` pixels with the given stride
+        pub fn copy_rect_from_rgba(
+            &mut self,
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+            data: &[u8],
+            stride: usize,
+        ) {
+            let rows = self.rect_rows(x, y, width, height, data.len(), stride);
+            let pixel_data = self.pixel_data_u8_mut();
+            for (dst_start, src_start, row_length) in rows {
+                pixel_data[dst_start..dst_start + row_length]
+                    .copy_from_slice(&data[src_start..src_start + row_length]);
+            }
+        }
 
-    /// Returns a mutable reference to the pixel data as a slice of [ColorU8]
-    pub fn pixel_data_mut(&mut self) -> &mut [ColorU8] {
-        unsafe {
-            let data = sys::image_get_pixel_data(self.0);
-            std::slice::from_raw_parts_mut(data.pData, data.length as usize)
+        /// Copies the `width` by `height` rectangle of the image that starts at x,y out as 8-bit
+        /// RGBA pixel data. `stride` is the offset in bytes between the starts of two rows in
+        /// `data`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the rectangle is not inside the image, or if `data` is too short to hold
+        /// `width` by `height` pixels with the given stride
+        pub fn copy_rect_to_rgba(
+            &self,
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+            data: &mut [u8],
+            stride: usize,
+        ) {
+            let rows = self.rect_rows(x, y, width, height, data.len(), stride);
+            let pixel_data = self.pixel_data_u8();
+            for (src_start, dst_start, row_length) in rows {
+                data[dst_start..dst_start + row_length]
+                    .copy_from_slice(&pixel_data[src_start..src_start + row_length]);
+            }
         }
-    }
 
-    /// Returns a mutable reference to the pixel data as a slice of u8
-    pub fn pixel_data_u8_mut(&mut self) -> &mut [u8] {
-        unsafe {
-            let data = sys::image_get_pixel_data(self.0);
-            std::slice::from_raw_parts_mut(
-                data.pData as *mut u8,
-                data.length as usize * std::mem::size_of::<ColorU8>(),
-            )
+        // Checks the bounds for a rectangle copy and returns, for each row, the byte offset into
+        // the image, the byte offset into the RGBA buffer and the length in bytes
+        fn rect_rows(
+            &self,
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+            buffer_length: usize,
+            stride: usize,
+        ) -> impl Iterator<Item = (usize, usize, usize)> {
+            let (image_width, image_height) = (self.width(), self.height());
+            assert!(
+                x as u64 + width as u64 <= image_width as u64
+                    && y as u64 + height as u64 <= image_height as u64,
+                "rectangle {}x{} at {},{} is outside of the {}x{} image",
+                width,
+                height,
+                x,
+                y,
+                image_width,
+                image_height
+            );
+
+            let pixel_size = std::mem::size_of::<ColorU8>();
+            let row_length = width as usize * pixel_size;
+            // An empty rectangle copies nothing, whatever the stride and buffer
+            let row_count = if width == 0 || height == 0 {
+                0
+            } else {
+                assert!(
+                    stride >= row_length,
+                    "stride {} is less than the row length {}",
+                    stride,
+                    row_length
+                );
+                let required_length = stride
+                    .checked_mul(height as usize - 1)
+                    .and_then(|length| length.checked_add(row_length));
+                assert!(
+                    required_length.map_or(false, |length| buffer_length >= length),
+                    "buffer of {} bytes is too short for {}x{} pixels with a stride of {}",
+                    buffer_length,
+                    width,
+                    height,
+                    stride
+                );
+                height as usize
+            };
+
+            let pitch = self.pitch() as usize;
+            (0..row_count).map(move |row| {
+                let image_start = ((y as usize + row) * pitch + x as usize) * pixel_size;
+                (image_start, row * stride, row_length)
+            })
+        }
+
+        /// Returns the width of the image in pixels
+        pub fn width(&self) -> u32 {
+            unsafe { sys::image_get_width(self.0) }
+        }
+
+        /// Returns the height of the image in pixels
+        pub fn height(&self) -> u32 {
+            unsafe { sys::image_get_height(self.0) }
+        }
+
+        /// Returns the pitch of the image in pixels, which represents the offset between rows
+        pub fn pitch(&self) -> u32 {
+            unsafe { sys::image_get_pitch(self.0) }
+        }
+
+        /// Returns the total number of pixels in the image
+        pub fn total_pixels(&self) -> u32 {
+            unsafe { sys::image_get_total_pixels(self.0) }
+        }
+
+        /// Returns how many blocks wide the image is, given `w`, the width of a block in pixels
+        pub fn block_width(
+            &self,
+            w: u32,
+        ) -> u32 {
+            unsafe { sys::image_get_block_width(self.0, w) }
+        }
+
+        /// Returns how many blocks high the image is, given `h`, the height of a block in pixels
+        pub fn block_height(
+            &self,
+            h: u32,
+        ) -> u32 {
+            unsafe { sys::image_get_block_height(self.0, h) }
+        }
+
+        /// Returns the number of blocks required to store the image, given `w` and `h`, the width
+        /// and height of a block in pixels
+        pub fn total_blocks(
+            &self,
+            w: u32,
+            h: u32,
+        ) -> u32 {
+            unsafe { sys::image_get_total_blocks(self.0, w, h) }
         }
+
+        /// Returns a mutable reference to the pixel data as a slice of [ColorU8]. The slice holds
+        /// `pitch` * `height` pixels; use [rows_mut](Self::rows_mut) to skip the padding at the end
+        /// of each row.
+        pub fn pixel_data_mut(&mut self) -> &mut [ColorU8] {
+            let (data, length) = self.raw_pixel_data();
+            unsafe { std::slice::from_raw_parts_mut(data, length) }
+        }
+
+        /// Returns a mutable reference to the pixel data as a slice of u8
+        pub fn pixel_data_u8_mut(&mut self) -> &mut [u8] {
+            let (data, length) = self.raw_pixel_data();
+            unsafe {
+                std::slice::from_raw_parts_mut(
+                    data as *mut u8,
+                    length * std::mem::size_of::<ColorU8>(),
+                )
+            }
+        }
+
+        /// Returns a mutable reference to the pixel data as a slice of u32
+        pub fn pixel_data_u32_mut(&mut self) -> &mut [u32] {
+            debug_assert_eq!(std::mem::size_of::<u32>(), std::mem::size_of::<ColorU8>());
+            let (data, length) = self.raw_pixel_data();
+            unsafe { std::slice::from_raw_parts_mut(data as *mut u32, length) }
+        }
+
+        fn pixel_data_u8(&self) -> &[u8] {
+            let (data, length) = self.raw_pixel_data();
+            unsafe {
+                std::slice::from_raw_parts(
+                    data as *const u8,
+                    length * std::mem::size_of::<ColorU8>(),
+                )
+            }
+        }
+
+        // The pixel pointer is null when the image is empty, which slices don't allow
+        fn raw_pixel_data(&self) -> (*mut ColorU8, usize) {
+            let data = unsafe { sys::image_get_pixel_data(self.0) };
+            if data.pData.is_null() {
+                (std::ptr::NonNull::dangling().as_ptr(), 0)
+            } else {
+                (data.pData, data.length as usize)
+            }
+        }
+    };
+}
+
+/// A reference to an image being stored by [CompressorParams](super::CompressorParams). Generally
+/// used to insert the source data that is to be encoded by a [Compressor](super::Compressor).
+///
+/// The reference borrows the params mutably, so it can't outlive them or be used after the image
+/// list is resized or reset:
+///
+/// ```compile_fail
+/// let mut compressor_params = basis_universal::CompressorParams::new();
+/// let compressor_image = compressor_params.source_image_mut(0);
+/// compressor_params.reset();
+/// compressor_image.width();
+/// ```
+pub struct CompressorImageRef<'a>(
+    pub *mut sys::basisu_image,
+    pub(crate) PhantomData<&'a mut CompressorParams>,
+);
+
+impl CompressorImageRef<'_> {
+    compressor_image_methods!();
+}
+
+/// An image that is not stored in [CompressorParams]. It can be prepared on any thread and then
+/// moved into the params with [CompressorParams::set_source_image] or
+/// [CompressorParams::set_source_mipmap_image]. It has the same methods as [CompressorImageRef].
+pub struct CompressorImage(*mut sys::basisu_image);
+
+// The image is plain pixel data that isn't shared with anything else
+unsafe impl Send for CompressorImage {}
+unsafe impl Sync for CompressorImage {}
+
+impl Default for CompressorImage {
+    fn default() -> Self {
+        Self::new()
     }
+}
+
+impl CompressorImage {
+    /// Create an empty image (0 width, 0 height)
+    pub fn new() -> Self {
+        unsafe { CompressorImage(sys::image_new()) }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut sys::basisu_image {
+        self.0
+    }
+
+    compressor_image_methods!();
+}
 
-    /// Returns a mutable reference to the pixel data as a slice of u32
-    pub fn pixel_data_u32_mut(&mut self) -> &mut [u32] {
-        debug_assert_eq!(std::mem::size_of::<u32>(), std::mem::size_of::<ColorU8>());
+impl Drop for CompressorImage {
+    fn drop(&mut self) {
         unsafe {
-            let data = sys::image_get_pixel_data(self.0);
-            std::slice::from_raw_parts_mut(data.pData as *mut u32, data.length as usize)
+            sys::image_delete(self.0);
         }
     }
 }
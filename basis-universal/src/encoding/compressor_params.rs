@@ -2,9 +2,10 @@ use super::*;
 use crate::{BasisTextureFormat, UserData};
 use basis_universal_sys as sys;
 pub use basis_universal_sys::ColorU8;
+use std::marker::PhantomData;
 
 /// The color space the image to be compressed is encoded in. Using the correct color space will
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ColorSpace {
     /// Used for normal maps or other "data" images
     Linear,
@@ -62,12 +63,24 @@ impl CompressorParams {
     pub fn source_image_mut(
         &mut self,
         image_index: u32,
-    ) -> CompressorImageRef {
+    ) -> CompressorImageRef<'_> {
         unsafe {
-            CompressorImageRef(sys::compressor_params_get_or_create_source_image(
-                self.0,
-                image_index,
-            ))
+            CompressorImageRef(
+                sys::compressor_params_get_or_create_source_image(self.0, image_index),
+                PhantomData,
+            )
+        }
+    }
+
+    /// Moves an image that was prepared outside of the params into the source image list. The
+    /// list is resized as needed such that the image will exist
+    pub fn set_source_image(
+        &mut self,
+        image_index: u32,
+        image: CompressorImage,
+    ) {
+        unsafe {
+            sys::compressor_params_set_source_image(self.0, image_index, image.as_ptr());
         }
     }
 
@@ -95,13 +108,34 @@ impl CompressorParams {
         &mut self,
         image_index: u32,
         level: u32,
-    ) -> CompressorImageRef {
+    ) -> CompressorImageRef<'_> {
+        unsafe {
+            CompressorImageRef(
+                sys::compressor_params_get_or_create_source_mipmap_image(
+                    self.0,
+                    image_index,
+                    level,
+                ),
+                PhantomData,
+            )
+        }
+    }
+
+    /// Moves an image that was prepared outside of the params into the source mipmap image list.
+    /// The list is resized as needed such that the image will exist
+    pub fn set_source_mipmap_image(
+        &mut self,
+        image_index: u32,
+        level: u32,
+        image: CompressorImage,
+    ) {
         unsafe {
-            CompressorImageRef(sys::compressor_params_get_or_create_source_mipmap_image(
+            sys::compressor_params_set_source_mipmap_image(
                 self.0,
                 image_index,
                 level,
-            ))
+                image.as_ptr(),
+            );
         }
     }
 
@@ -173,6 +207,8 @@ impl CompressorParams {
 
         unsafe {
             let mut flags = sys::compressor_params_get_pack_uastc_flags(self.0);
+            // Replace the previous level rather than combining it with the new one
+            flags &= !sys::UastcPackFlags_PackUASTCLevelMask;
             flags |= quality_level as i32; // bindgen reflects constants as signed integers. So even if it doesn't make sense for the quality level to be signed, it has to be.
             sys::compressor_params_set_pack_uastc_flags(self.0, flags);
         }
@@ -309,6 +345,17 @@ impl CompressorParams {
         }
     }
 
+    /// Also write a .KTX2 file when compressing. The .basis file is always written. See
+    /// [Compressor::ktx2_file](crate::Compressor::ktx2_file)
+    pub fn set_create_ktx2_file(
+        &mut self,
+        create_ktx2_file: bool,
+    ) {
+        unsafe {
+            sys::compressor_params_set_create_ktx2_file(self.0, create_ktx2_file);
+        }
+    }
+
     /// The `basisu` command line compressor offers a -normal_map parameter that sets several
     /// values automatically. This convenience function mimics that parameter.
     ///
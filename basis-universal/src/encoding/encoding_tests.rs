@@ -1,6 +1,16 @@
 use super::*;
 use crate::BasisTextureFormat;
-use image::GenericImageView;
+use std::convert::TryFrom;
+
+// Compresses with a fresh single-threaded compressor, which gives deterministic output
+fn compress_to_vec(compressor_params: &CompressorParams) -> Vec<u8> {
+    let mut compressor = Compressor::new(1);
+    unsafe {
+        assert!(compressor.init(compressor_params));
+        compressor.process().unwrap();
+    }
+    compressor.basis_file().to_vec()
+}
 
 #[test]
 fn test_new_compressor_params() {
@@ -14,6 +24,28 @@ fn test_new_compressor() {
     std::mem::drop(compressor);
 }
 
+#[test]
+fn test_compressor_error_code_try_from() {
+    assert_eq!(
+        CompressorErrorCode::try_from(
+            sys::basisu_basis_compressor_error_code_cECFailedCreateKTX2File
+        ),
+        Ok(CompressorErrorCode::cECFailedCreateKTX2File)
+    );
+
+    // Success is not an error
+    assert_eq!(
+        CompressorErrorCode::try_from(sys::basisu_basis_compressor_error_code_cECSuccess),
+        Err(sys::basisu_basis_compressor_error_code_cECSuccess)
+    );
+
+    // Values added by newer versions of the library are kept rather than rejected
+    let error_code = CompressorErrorCode::try_from(1000).unwrap();
+    assert_eq!(error_code, CompressorErrorCode::Unknown(1000));
+    let value: sys::basisu_basis_compressor_error_code = error_code.into();
+    assert_eq!(value, 1000);
+}
+
 #[test]
 fn test_compressor_init() {
     let compressor_params = CompressorParams::new();
@@ -62,23 +94,9 @@ fn test_encode_image() {
     // Read the PNG file from disk
     //
     let png_file = include_bytes!("../../test_assets/rust-logo.png");
-    let image_data =
-        image::load_from_memory_with_format(png_file, image::ImageFormat::Png).unwrap();
-
-    use image::ColorType;
-    let channel_count = match &image_data.color() {
-        ColorType::L8 => 1,
-        ColorType::La8 => 2,
-        ColorType::Rgb8 => 3,
-        ColorType::Rgba8 => 4,
-        ColorType::L16 => 1,
-        ColorType::La16 => 2,
-        ColorType::Rgb16 => 3,
-        ColorType::Rgba16 => 4,
-        ColorType::Bgr8 => 3,
-        ColorType::Bgra8 => 4,
-        _ => unimplemented!(),
-    };
+    let image_data = image::load_from_memory_with_format(png_file, image::ImageFormat::Png)
+        .unwrap()
+        .to_rgba8();
 
     let mut compressor_params = CompressorParams::new();
     compressor_params.set_generate_mipmaps(true);
@@ -87,12 +105,7 @@ fn test_encode_image() {
     // Set up the source image in the params
     //
     let mut compressor_image = compressor_params.source_image_mut(0);
-    compressor_image.init(
-        image_data.as_bytes(),
-        image_data.width(),
-        image_data.height(),
-        channel_count,
-    );
+    compressor_image.init(&image_data, image_data.width(), image_data.height(), 4);
 
     //
     // Create the compressor
@@ -118,3 +131,724 @@ fn test_encode_image() {
 
     std::mem::drop(compressor);
 }
+
+#[cfg(feature = "image")]
+#[test]
+fn test_init_from_dynamic_image() {
+    use image::{DynamicImage, ImageBuffer, Rgba};
+    use std::borrow::Cow;
+
+    // 3x2 pixels, with values that differ in every channel
+    let rgba = ImageBuffer::from_fn(3, 2, |x, y| {
+        Rgba([x as u8 * 80, y as u8 * 200, 30 + x as u8, 100 + y as u8])
+    });
+    let rgba_image = DynamicImage::ImageRgba8(rgba.clone());
+
+    for (image, channel_count, borrowed) in [
+        (DynamicImage::ImageRgba8(rgba.clone()), 4, true),
+        (DynamicImage::ImageRgb8(rgba_image.to_rgb8()), 3, true),
+        (DynamicImage::ImageLuma8(rgba_image.to_luma8()), 1, true),
+        (
+            DynamicImage::ImageLumaA8(rgba_image.to_luma_alpha8()),
+            2,
+            true,
+        ),
+        (DynamicImage::ImageRgba16(rgba_image.to_rgba16()), 4, false),
+        (DynamicImage::ImageBgra8(rgba_image.to_bgra8()), 4, false),
+        (DynamicImage::ImageBgr8(rgba_image.to_bgr8()), 4, false),
+        (DynamicImage::ImageRgb16(rgba_image.to_rgb16()), 4, false),
+        (DynamicImage::ImageLuma16(rgba_image.to_luma16()), 4, false),
+        (
+            DynamicImage::ImageLumaA16(rgba_image.to_luma_alpha16()),
+            4,
+            false,
+        ),
+    ] {
+        let source = SourceImageData::from(&image);
+        assert_eq!(source.width, 3);
+        assert_eq!(source.height, 2);
+        assert_eq!(source.channel_count, channel_count);
+        assert_eq!(matches!(source.data, Cow::Borrowed(_)), borrowed);
+
+        // The encoder expands every image to RGBA the same way the image crate does, and wide or
+        // BGR data must not be passed through as raw bytes
+        let expected = image.to_rgba8();
+
+        let mut compressor_params = CompressorParams::new();
+        let mut compressor_image = compressor_params.source_image_mut(0);
+        compressor_image.init_from(&image);
+        assert_eq!(compressor_image.width(), 3);
+        assert_eq!(compressor_image.height(), 2);
+        for (x, y, pixel) in expected.enumerate_pixels() {
+            let color = compressor_image.pixel_at(x, y).unwrap();
+            assert_eq!(unsafe { color.components }, pixel.0);
+        }
+    }
+}
+
+#[test]
+fn test_load_png_and_jpeg() {
+    let png_file = include_bytes!("../../test_assets/rust-logo.png");
+    let expected = image::load_from_memory_with_format(png_file, image::ImageFormat::Png)
+        .unwrap()
+        .to_rgba8();
+
+    let mut compressor_params = CompressorParams::new();
+    let mut compressor_image = compressor_params.source_image_mut(0);
+    compressor_image.load_png_bytes(png_file).unwrap();
+    assert_eq!(compressor_image.width(), expected.width());
+    assert_eq!(compressor_image.height(), expected.height());
+    for (x, y, pixel) in expected.enumerate_pixels() {
+        let color = compressor_image.pixel_at(x, y).unwrap();
+        assert_eq!(unsafe { color.components }, pixel.0);
+    }
+
+    compressor_image.invalidate();
+    compressor_image
+        .load_file("test_assets/rust-logo.png")
+        .unwrap();
+    assert_eq!(compressor_image.width(), expected.width());
+
+    // Encode a JPEG with the image crate. The decoders differ slightly, so only compare loosely.
+    let mut jpeg_file = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_file, 100)
+        .encode_image(&image::DynamicImage::ImageRgba8(expected.clone()).to_rgb8())
+        .unwrap();
+    assert_eq!(
+        SourceFileFormat::detect(&jpeg_file),
+        Some(SourceFileFormat::Jpeg)
+    );
+    compressor_image.load_jpeg_bytes(&jpeg_file).unwrap();
+    assert_eq!(compressor_image.width(), expected.width());
+    assert_eq!(compressor_image.height(), expected.height());
+    let jpeg_pixel = compressor_image.pixel_at(0, 0).unwrap();
+    assert_eq!(unsafe { jpeg_pixel.channels.a }, 255);
+
+    //
+    // Errors leave the image empty
+    //
+    assert!(matches!(
+        compressor_image.load_jpeg_bytes(png_file),
+        Err(ImageLoadError::InvalidSignature(SourceFileFormat::Jpeg))
+    ));
+    assert!(matches!(
+        compressor_image.load_png_bytes(&png_file[..png_file.len() / 2]),
+        Err(ImageLoadError::DecodeFailed(SourceFileFormat::Png))
+    ));
+    assert_eq!(compressor_image.width(), 0);
+    assert!(matches!(
+        compressor_image.load_jpeg_bytes(&jpeg_file[..16]),
+        Err(ImageLoadError::DecodeFailed(SourceFileFormat::Jpeg))
+    ));
+    assert!(matches!(
+        compressor_image.load_png_bytes(&[]),
+        Err(ImageLoadError::InvalidSignature(SourceFileFormat::Png))
+    ));
+    assert!(matches!(
+        compressor_image.load_file("test_assets/rust-logo-etc.basis"),
+        Err(ImageLoadError::UnknownFormat)
+    ));
+    assert!(matches!(
+        compressor_image.load_file("test_assets/does-not-exist.png"),
+        Err(ImageLoadError::Io(_))
+    ));
+}
+
+#[test]
+fn test_set_uastc_quality_level_replaces_level() {
+    let mut compressor_params = CompressorParams::new();
+    let pack_uastc_flags = |compressor_params: &CompressorParams| unsafe {
+        sys::compressor_params_get_pack_uastc_flags(compressor_params.0)
+    };
+    unsafe {
+        sys::compressor_params_set_pack_uastc_flags(
+            compressor_params.0,
+            sys::UastcPackFlags_PackUASTCFavorBC7Error,
+        );
+    }
+
+    // Setting a lower level after a higher one must not leave any of the higher level's bits set,
+    // and must keep the other flags
+    compressor_params.set_uastc_quality_level(crate::UASTC_QUALITY_MAX);
+    assert_eq!(
+        pack_uastc_flags(&compressor_params),
+        crate::UASTC_QUALITY_MAX as i32 | sys::UastcPackFlags_PackUASTCFavorBC7Error
+    );
+    compressor_params.set_uastc_quality_level(crate::UASTC_QUALITY_MIN + 1);
+    assert_eq!(
+        pack_uastc_flags(&compressor_params),
+        (crate::UASTC_QUALITY_MIN + 1) as i32 | sys::UastcPackFlags_PackUASTCFavorBC7Error
+    );
+}
+
+// A directory under the system temp directory that is removed when dropped
+struct TestDir(std::path::PathBuf);
+
+impl TestDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("basis-universal-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_batch_compressor() {
+    let test_dir = TestDir::new("batch");
+    let input_dir = test_dir.0.join("input");
+    let output_dir = test_dir.0.join("output");
+    std::fs::create_dir_all(&input_dir).unwrap();
+    std::fs::copy("test_assets/rust-logo.png", input_dir.join("a.png")).unwrap();
+    std::fs::copy("test_assets/rust-logo-256x256.png", input_dir.join("b.PNG")).unwrap();
+    std::fs::write(input_dir.join("c.png"), b"not a png").unwrap();
+    std::fs::write(input_dir.join("notes.txt"), b"ignored").unwrap();
+
+    let settings = CompressionSettings {
+        generate_mipmaps: true,
+        ..Default::default()
+    };
+    let jobs = CompressionJob::from_directory(&input_dir, &output_dir, &settings).unwrap();
+    let inputs: Vec<_> = jobs
+        .iter()
+        .map(|job| job.input.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(inputs, ["a.png", "b.PNG", "c.png"]);
+    assert_eq!(jobs[1].output, output_dir.join("b.basis"));
+
+    let batch_compressor = BatchCompressor {
+        worker_count: 2,
+        threads_per_compressor: 1,
+        job_pool: None,
+        cache_dir: Some(test_dir.0.join("cache")),
+    };
+
+    //
+    // First run compresses everything
+    //
+    let results = batch_compressor.run(&jobs);
+    assert_eq!(results.len(), 3);
+    for result in &results[0..2] {
+        let report = result.as_ref().unwrap();
+        assert!(!report.cache_hit);
+        assert!(report.input_size > 0);
+        assert!(report.output_size > 0);
+    }
+    assert!(matches!(
+        results[2],
+        Err(BatchJobError::ImageLoad(ImageLoadError::InvalidSignature(
+            SourceFileFormat::Png
+        )))
+    ));
+
+    let transcoder = crate::Transcoder::new();
+    let first_outputs: Vec<_> = jobs[0..2]
+        .iter()
+        .map(|job| std::fs::read(&job.output).unwrap())
+        .collect();
+    for (output, report) in first_outputs.iter().zip(&results) {
+        assert_eq!(output.len(), report.as_ref().unwrap().output_size);
+        transcoder.validate_file_checksums(output, true).unwrap();
+    }
+    assert_eq!(transcoder.image_level_count(&first_outputs[1], 0), Ok(9));
+
+    //
+    // Second run copies the same outputs from the cache
+    //
+    std::fs::remove_dir_all(&output_dir).unwrap();
+    let results = batch_compressor.run(&jobs[0..2]);
+    for result in &results {
+        assert!(result.as_ref().unwrap().cache_hit);
+    }
+    for (job, first_output) in jobs.iter().zip(&first_outputs) {
+        assert_eq!(&std::fs::read(&job.output).unwrap(), first_output);
+    }
+
+    //
+    // Changing the input or the settings misses the cache
+    //
+    std::fs::copy("test_assets/rust-logo-256x256.png", input_dir.join("a.png")).unwrap();
+    let mut uastc_job = jobs[1].clone();
+    uastc_job.settings.basis_format = BasisTextureFormat::UASTC4x4;
+    let mut ktx2_job = jobs[1].clone();
+    ktx2_job.settings.create_ktx2_file = true;
+    ktx2_job.output = output_dir.join("b.ktx2");
+
+    let results = batch_compressor.run(&[jobs[0].clone(), uastc_job.clone(), ktx2_job.clone()]);
+    for result in &results {
+        assert!(!result.as_ref().unwrap().cache_hit);
+    }
+    let uastc_output = std::fs::read(&uastc_job.output).unwrap();
+    assert_eq!(
+        transcoder.basis_texture_format(&uastc_output),
+        Ok(BasisTextureFormat::UASTC4x4)
+    );
+    let ktx2_output = std::fs::read(&ktx2_job.output).unwrap();
+    assert_eq!(&ktx2_output[0..12], b"\xABKTX 20\xBB\r\n\x1A\n");
+
+    // No temporary files are left in the cache
+    for entry in std::fs::read_dir(test_dir.0.join("cache")).unwrap() {
+        let path = entry.unwrap().path();
+        let extension = path.extension().unwrap();
+        assert!(extension == "basis" || extension == "ktx2", "{:?}", path);
+    }
+
+    // Zero workers or threads are treated as one rather than failing
+    let batch_compressor = BatchCompressor {
+        worker_count: 0,
+        threads_per_compressor: 0,
+        job_pool: None,
+        cache_dir: None,
+    };
+    let results = batch_compressor.run(&jobs[0..1]);
+    assert!(!results[0].as_ref().unwrap().cache_hit);
+}
+
+#[test]
+fn test_batch_compressor_invalid_settings() {
+    let test_dir = TestDir::new("batch-invalid-settings");
+    let good_job = CompressionJob {
+        input: "test_assets/rust-logo.png".into(),
+        output: test_dir.0.join("good.basis"),
+        settings: Default::default(),
+    };
+    let mut etc1s_job = good_job.clone();
+    etc1s_job.output = test_dir.0.join("etc1s.basis");
+    etc1s_job.settings.etc1s_quality_level = crate::ETC1S_QUALITY_MAX + 1;
+    let mut uastc_job = good_job.clone();
+    uastc_job.output = test_dir.0.join("uastc.basis");
+    uastc_job.settings.uastc_quality_level = crate::UASTC_QUALITY_MAX + 1;
+
+    let batch_compressor = BatchCompressor {
+        worker_count: 2,
+        threads_per_compressor: 1,
+        job_pool: None,
+        cache_dir: None,
+    };
+
+    // The bad jobs fail without taking down the worker that runs the good one
+    let results = batch_compressor.run(&[etc1s_job.clone(), good_job.clone(), uastc_job.clone()]);
+    assert!(matches!(results[0], Err(BatchJobError::InvalidSettings(_))));
+    assert!(!results[1].as_ref().unwrap().cache_hit);
+    assert!(matches!(results[2], Err(BatchJobError::InvalidSettings(_))));
+    assert!(good_job.output.exists());
+    assert!(!etc1s_job.output.exists());
+    assert!(!uastc_job.output.exists());
+}
+
+#[test]
+fn test_shared_job_pool() {
+    let png_file = include_bytes!("../../test_assets/rust-logo.png");
+    let job_pool = JobPool::new(3);
+    assert_eq!(job_pool.total_thread_count(), 3);
+
+    let mut compressors: Vec<_> = (0..4)
+        .map(|_| Compressor::with_job_pool(&job_pool))
+        .collect();
+    assert!(compressors[0].job_pool().is_some());
+    assert!(Compressor::new(1).job_pool().is_none());
+
+    // The compressors keep the pool alive
+    std::mem::drop(job_pool);
+
+    std::thread::scope(|scope| {
+        for (index, compressor) in compressors.iter_mut().enumerate() {
+            scope.spawn(move || {
+                let mut compressor_params = CompressorParams::new();
+                compressor_params.set_generate_mipmaps(true);
+                if index % 2 == 1 {
+                    compressor_params.set_basis_format(BasisTextureFormat::UASTC4x4);
+                }
+                compressor_params
+                    .source_image_mut(0)
+                    .load_png_bytes(png_file)
+                    .unwrap();
+
+                unsafe {
+                    assert!(compressor.init(&compressor_params));
+                    compressor.process().unwrap();
+                }
+            });
+        }
+    });
+
+    let transcoder = crate::Transcoder::new();
+    for (index, compressor) in compressors.iter().enumerate() {
+        let basis_file = compressor.basis_file();
+        transcoder
+            .validate_file_checksums(basis_file, true)
+            .unwrap();
+        let expected_format = if index % 2 == 1 {
+            BasisTextureFormat::UASTC4x4
+        } else {
+            BasisTextureFormat::ETC1S
+        };
+        assert_eq!(
+            transcoder.basis_texture_format(basis_file),
+            Ok(expected_format)
+        );
+        assert_eq!(transcoder.image_level_count(basis_file, 0), Ok(7));
+    }
+}
+
+#[test]
+fn test_job_pool_serializes_process() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let png_file = include_bytes!("../../test_assets/rust-logo.png");
+    let job_pool = JobPool::new(2);
+    let mut compressor = Compressor::with_job_pool(&job_pool);
+    let mut compressor_params = CompressorParams::new();
+    compressor_params
+        .source_image_mut(0)
+        .load_png_bytes(png_file)
+        .unwrap();
+    unsafe {
+        assert!(compressor.init(&compressor_params));
+    }
+
+    // Stands in for another compressor that is in process() on the same pool
+    let process_lock = job_pool.lock_for_process();
+    let processed = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let processed = &processed;
+        let compressor = &mut compressor;
+        let handle = scope.spawn(move || {
+            unsafe {
+                compressor.process().unwrap();
+            }
+            processed.store(true, Ordering::SeqCst);
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!processed.load(Ordering::SeqCst));
+        std::mem::drop(process_lock);
+        handle.join().unwrap();
+    });
+    assert!(processed.load(Ordering::SeqCst));
+    assert!(!compressor.basis_file().is_empty());
+}
+
+#[test]
+fn test_batch_compressor_with_job_pool() {
+    let test_dir = TestDir::new("batch-job-pool");
+    let jobs: Vec<_> = (0..4)
+        .map(|index| CompressionJob {
+            input: "test_assets/rust-logo.png".into(),
+            output: test_dir.0.join(format!("{}.basis", index)),
+            settings: CompressionSettings {
+                etc1s_quality_level: 64 + index,
+                ..Default::default()
+            },
+        })
+        .collect();
+
+    let batch_compressor = BatchCompressor {
+        worker_count: 4,
+        job_pool: Some(JobPool::new(2)),
+        ..Default::default()
+    };
+    let transcoder = crate::Transcoder::new();
+    for (job, result) in jobs.iter().zip(batch_compressor.run(&jobs)) {
+        assert!(!result.unwrap().cache_hit);
+        let output = std::fs::read(&job.output).unwrap();
+        transcoder.validate_file_checksums(&output, true).unwrap();
+    }
+}
+
+#[test]
+fn test_compressor_reuse() {
+    let small_png = include_bytes!("../../test_assets/rust-logo.png");
+    let large_png = include_bytes!("../../test_assets/rust-logo-256x256.png");
+
+    let make_params = |png_file: &[u8], create_ktx2_file: bool| {
+        let mut compressor_params = CompressorParams::new();
+        compressor_params.set_generate_mipmaps(true);
+        compressor_params.set_create_ktx2_file(create_ktx2_file);
+        compressor_params
+            .source_image_mut(0)
+            .load_png_bytes(png_file)
+            .unwrap();
+        compressor_params
+    };
+
+    let mut compressor = Compressor::new(1);
+    assert!(compressor.basis_file().is_empty());
+    assert!(compressor.ktx2_file().is_empty());
+
+    // Alternate between images, with and without a .KTX2 file, and check that every cycle produces
+    // exactly what a fresh compressor would
+    for (png_file, create_ktx2_file) in [
+        (&small_png[..], true),
+        (&large_png[..], false),
+        (&small_png[..], false),
+        (&large_png[..], true),
+    ] {
+        let compressor_params = make_params(png_file, create_ktx2_file);
+        unsafe {
+            assert!(compressor.init(&compressor_params));
+            // Nothing from the previous cycle is visible after init()
+            assert!(compressor.basis_file().is_empty());
+            assert!(compressor.ktx2_file().is_empty());
+            compressor.process().unwrap();
+        }
+
+        assert_eq!(
+            compressor.basis_file(),
+            &compress_to_vec(&compressor_params)[..]
+        );
+        assert_eq!(
+            compressor.basis_file_size() as usize,
+            compressor.basis_file().len()
+        );
+        assert_eq!(compressor.ktx2_file().is_empty(), !create_ktx2_file);
+    }
+
+    compressor.reset();
+    assert!(compressor.basis_file().is_empty());
+    assert!(compressor.ktx2_file().is_empty());
+
+    // Reset without init, and init without process, are both fine
+    compressor.reset();
+    let compressor_params = make_params(small_png, false);
+    unsafe {
+        assert!(compressor.init(&compressor_params));
+        assert!(compressor.init(&compressor_params));
+        compressor.process().unwrap();
+    }
+    assert_eq!(
+        compressor.basis_file(),
+        &compress_to_vec(&compressor_params)[..]
+    );
+
+    // The same applies to compressors using a shared job pool
+    let mut compressor = Compressor::with_job_pool(&JobPool::new(2));
+    for _ in 0..2 {
+        unsafe {
+            assert!(compressor.init(&compressor_params));
+            compressor.process().unwrap();
+        }
+        crate::Transcoder::new()
+            .validate_file_checksums(compressor.basis_file(), true)
+            .unwrap();
+        compressor.reset();
+    }
+}
+
+#[test]
+fn test_owned_compressor_image() {
+    let small_png = include_bytes!("../../test_assets/rust-logo.png");
+    let large_png = include_bytes!("../../test_assets/rust-logo-256x256.png");
+
+    // Prepare the images on other threads
+    let handles: Vec<_> = [&small_png[..], &large_png[..]]
+        .iter()
+        .map(|png_file| {
+            let png_file = png_file.to_vec();
+            std::thread::spawn(move || {
+                let mut image = CompressorImage::new();
+                assert_eq!(image.width(), 0);
+                image.load_png_bytes(&png_file).unwrap();
+                image
+            })
+        })
+        .collect();
+    let images: Vec<CompressorImage> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!((images[0].width(), images[0].height()), (64, 64));
+    assert_eq!((images[1].width(), images[1].height()), (256, 256));
+    let small_pixel = unsafe { images[0].pixel_at(32, 32).unwrap().combined };
+
+    let mut compressor_params = CompressorParams::new();
+    for (image_index, image) in images.into_iter().enumerate() {
+        compressor_params.set_source_image(image_index as u32, image);
+    }
+    assert_eq!(compressor_params.source_image_mut(0).width(), 64);
+    let pixel = compressor_params
+        .source_image_mut(0)
+        .pixel_at(32, 32)
+        .unwrap();
+    assert_eq!(unsafe { pixel.combined }, small_pixel);
+
+    let mut expected_params = CompressorParams::new();
+    expected_params
+        .source_image_mut(0)
+        .load_png_bytes(small_png)
+        .unwrap();
+    expected_params
+        .source_image_mut(1)
+        .load_png_bytes(large_png)
+        .unwrap();
+
+    assert_eq!(
+        compress_to_vec(&compressor_params),
+        compress_to_vec(&expected_params)
+    );
+
+    // Replacing an image drops the one that was there before
+    let mut image = CompressorImage::new();
+    image.resize(8, 8);
+    compressor_params.set_source_image(0, image);
+    assert_eq!(compressor_params.source_image_mut(0).width(), 8);
+}
+
+#[test]
+fn test_pixel_access_with_pitch() {
+    let rgba = |r: u8, g: u8, b: u8, a: u8| ColorU8 {
+        combined: u32::from_le_bytes([r, g, b, a]),
+    };
+    let combined = |color: ColorU8| unsafe { color.combined };
+    let padding = rgba(1, 2, 3, 4);
+
+    let mut image = CompressorImage::new();
+    assert_eq!(image.rows_mut().count(), 0);
+    assert!(image.pixel_data_mut().is_empty());
+
+    // 5x3 pixels, with 3 pixels of padding at the end of each row
+    image.resize_with_pitch(5, 3, 8);
+    assert_eq!(image.pitch(), 8);
+    assert_eq!(image.pixel_data_mut().len(), 24);
+    for pixel in image.pixel_data_mut() {
+        *pixel = padding;
+    }
+
+    image.fill(rgba(10, 20, 30, 255));
+    assert_eq!(image.rows_mut().count(), 3);
+    assert!(image.rows_mut().all(|row| row.len() == 5));
+    image.set_pixel(4, 2, rgba(50, 60, 70, 80));
+    assert_eq!(
+        combined(image.pixel_at(4, 2).unwrap()),
+        combined(rgba(50, 60, 70, 80))
+    );
+    // x and y are not swapped
+    assert!(image.pixel_at(4, 1).is_some());
+    assert!(image.pixel_at(1, 4).is_none());
+
+    for (y, row) in image.rows_mut().enumerate() {
+        row[0] = rgba(y as u8, 0, 0, 255);
+    }
+    assert_eq!(
+        combined(image.pixel_at(0, 2).unwrap()),
+        combined(rgba(2, 0, 0, 255))
+    );
+
+    // Copy from a buffer whose rows are padded too, with a stride of 7 pixels
+    let stride = 7 * 4;
+    let mut source = vec![0u8; stride * 2 + 5 * 4];
+    for y in 0..3 {
+        for x in 0..5 {
+            let offset = y * stride + x * 4;
+            source[offset..offset + 4].copy_from_slice(&[x as u8, y as u8, 100, 200]);
+        }
+    }
+    image.copy_from_rgba(&source, stride);
+    for y in 0..3 {
+        for x in 0..5 {
+            assert_eq!(
+                combined(image.pixel_at(x, y).unwrap()),
+                combined(rgba(x as u8, y as u8, 100, 200))
+            );
+        }
+    }
+
+    // Blit a 2x2 rectangle into the bottom right corner and read it back out
+    let square = [255u8; 16];
+    image.copy_rect_from_rgba(3, 1, 2, 2, &square, 8);
+    assert_eq!(
+        combined(image.pixel_at(2, 1).unwrap()),
+        combined(rgba(2, 1, 100, 200))
+    );
+    assert_eq!(
+        combined(image.pixel_at(3, 1).unwrap()),
+        combined(rgba(255, 255, 255, 255))
+    );
+    assert_eq!(
+        combined(image.pixel_at(4, 2).unwrap()),
+        combined(rgba(255, 255, 255, 255))
+    );
+
+    // Empty rectangles copy nothing, even with an empty buffer
+    image.copy_rect_from_rgba(1, 1, 0, 2, &[], 0);
+    image.copy_rect_to_rgba(1, 1, 2, 0, &mut [], 0);
+
+    let mut output = [0u8; 3 * 4 * 2];
+    image.copy_rect_to_rgba(2, 1, 3, 2, &mut output, 12);
+    assert_eq!(&output[0..4], &[2, 1, 100, 200]);
+    assert_eq!(&output[4..12], &[255; 8]);
+    assert_eq!(&output[12..16], &[2, 2, 100, 200]);
+
+    // None of the above touched the padding
+    let data = image.pixel_data_mut();
+    for y in 0..3 {
+        for x in 5..8 {
+            assert_eq!(combined(data[y * 8 + x]), combined(padding));
+        }
+    }
+
+    // The encoder reads the image using its pitch too, so it gives the same result as a tightly
+    // packed copy
+    let mut packed = vec![0u8; 5 * 3 * 4];
+    image.copy_rect_to_rgba(0, 0, 5, 3, &mut packed, 5 * 4);
+    let mut packed_image = CompressorImage::new();
+    packed_image.init(&packed, 5, 3, 4);
+    assert_eq!(packed_image.pitch(), 5);
+
+    let compress = |image: CompressorImage| {
+        let mut compressor_params = CompressorParams::new();
+        compressor_params.set_source_image(0, image);
+        compress_to_vec(&compressor_params)
+    };
+    assert_eq!(compress(image), compress(packed_image));
+}
+
+#[test]
+#[should_panic]
+fn test_copy_rect_outside_image() {
+    let mut image = CompressorImage::new();
+    image.resize_with_pitch(4, 4, 6);
+    image.copy_rect_from_rgba(3, 0, 2, 1, &[0; 8], 8);
+}
+
+#[test]
+#[should_panic]
+fn test_copy_from_rgba_short_buffer() {
+    let mut image = CompressorImage::new();
+    image.resize(4, 4);
+    image.copy_from_rgba(&[0; 4 * 4 * 4 - 1], 16);
+}
+
+#[test]
+#[should_panic]
+fn test_init_short_buffer() {
+    let mut image = CompressorImage::new();
+    image.init(&[0; 4 * 4 * 3 - 1], 4, 4, 3);
+}
+
+#[test]
+#[should_panic]
+fn test_init_from_short_source_image_data() {
+    let mut image = CompressorImage::new();
+    image.init_from(SourceImageData {
+        data: vec![0; 4 * 4 * 4 - 1].into(),
+        width: 4,
+        height: 4,
+        channel_count: 4,
+    });
+}
+
+#[test]
+#[should_panic]
+fn test_init_invalid_channel_count() {
+    let mut image = CompressorImage::new();
+    image.init(&[0; 4 * 4 * 5], 4, 4, 5);
+}
@@ -0,0 +1,84 @@
+use super::*;
+use basis_universal_sys as sys;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A pool of encoder threads that can be shared by several [Compressor]s, created with
+/// [Compressor::with_job_pool]. This bounds the total number of encoder threads no matter how many
+/// compressors exist.
+///
+/// Cloning is cheap and refers to the same pool. The threads are stopped once the pool and every
+/// compressor using it have been dropped.
+///
+/// Compressors that share a pool take turns in [Compressor::process], so they encode one image
+/// at a time and together are no faster than a single compressor using every thread of the pool.
+/// Loading images and [Compressor::init] can still happen on many threads at the same time. Use a
+/// shared pool to bound the number of encoder threads, not to encode more images at once.
+///
+/// The turns are needed because the upstream `job_pool::wait_for_all` is not safe with several
+/// callers. It runs queued jobs on the calling thread without counting them as active, so while
+/// one caller runs a job queued by another compressor, that compressor's `wait_for_all` sees an
+/// empty queue and no active jobs and returns before its job has finished. The encoder queues its
+/// jobs on the pool directly, so waiting for only a compressor's own jobs would need changes to
+/// the upstream `job_pool`.
+#[derive(Clone)]
+pub struct JobPool(Arc<JobPoolInner>);
+
+struct JobPoolInner {
+    job_pool: *mut sys::JobPool,
+    process_lock: Mutex<()>,
+}
+
+// The C++ job pool synchronizes access to its queue internally
+unsafe impl Send for JobPoolInner {}
+unsafe impl Sync for JobPoolInner {}
+
+impl JobPool {
+    /// total_thread_count is the TOTAL number of job pool threads, including the thread that calls
+    /// [Compressor::process]. So 2=1 new thread, 3=2 new threads, etc.
+    pub fn new(total_thread_count: u32) -> Self {
+        encoder_init();
+        assert!(total_thread_count > 0);
+        let job_pool = unsafe { sys::job_pool_new(total_thread_count as _) };
+        JobPool(Arc::new(JobPoolInner {
+            job_pool,
+            process_lock: Mutex::default(),
+        }))
+    }
+
+    /// The total number of threads, including the thread that calls [Compressor::process]
+    pub fn total_thread_count(&self) -> u32 {
+        unsafe { sys::job_pool_get_total_threads(self.0.job_pool) }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut sys::JobPool {
+        self.0.job_pool
+    }
+
+    // Held by a compressor for the duration of process(). See the type docs for why.
+    pub(crate) fn lock_for_process(&self) -> MutexGuard<'_, ()> {
+        // A panic while holding the lock doesn't leave the pool in a bad state
+        self.0
+            .process_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for JobPool {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("JobPool")
+            .field("total_thread_count", &self.total_thread_count())
+            .finish()
+    }
+}
+
+impl Drop for JobPoolInner {
+    fn drop(&mut self) {
+        unsafe {
+            sys::job_pool_delete(self.job_pool);
+        }
+    }
+}
@@ -12,6 +12,12 @@ pub use compressor_params::*;
 mod compressor;
 pub use compressor::*;
 
+mod job_pool;
+pub use job_pool::*;
+
+mod batch;
+pub use batch::*;
+
 /// A single uncompressed pixel value
 pub use basis_universal_sys::ColorU8;
 
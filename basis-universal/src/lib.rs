@@ -27,7 +27,7 @@ pub use encoding::*;
 pub use basis_universal_sys as sys;
 
 /// Arbitrary data that can be attached to a basis-universal file/binary blob
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub struct UserData {
     pub userdata0: u32,
     pub userdata1: u32,
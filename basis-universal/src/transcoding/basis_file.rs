@@ -0,0 +1,328 @@
+use super::*;
+use crate::UserData;
+use std::borrow::Cow;
+
+/// An image within a [BasisFile], along with all of its mip levels
+#[derive(Debug, Copy, Clone)]
+pub struct BasisFileImage<'b> {
+    info: &'b ImageInfo,
+    levels: &'b [ImageLevelInfo],
+}
+
+impl<'b> BasisFileImage<'b> {
+    /// Index of the image within the basis data
+    pub fn image_index(&self) -> u32 {
+        self.info.m_image_index
+    }
+
+    /// Information about the image
+    pub fn info(&self) -> &'b ImageInfo {
+        self.info
+    }
+
+    /// Number of mip levels in the image
+    pub fn level_count(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// Information about the given mip level of the image
+    pub fn level(
+        &self,
+        level_index: u32,
+    ) -> Option<&'b ImageLevelInfo> {
+        self.levels.get(level_index as usize)
+    }
+
+    /// Iterates over all mip levels of the image
+    pub fn levels(&self) -> std::slice::Iter<'b, ImageLevelInfo> {
+        self.levels.iter()
+    }
+}
+
+/// Basis data that has been validated and prepared for transcoding.
+///
+/// Unlike [Transcoder], the data is only passed in once. The header is parsed when the file is
+/// created, and the file/image/level descriptions are cached so that queries and transcoding don't
+/// need to parse it again. For ETC1S data, the codebooks are decoded once and reused by every
+/// call to `transcode()`.
+pub struct BasisFile<'a> {
+    data: Cow<'a, [u8]>,
+    transcoder: Transcoder,
+    file_info: FileInfo,
+    basis_type: BasisTextureType,
+    basis_format: BasisTextureFormat,
+    images: Vec<(ImageInfo, Vec<ImageLevelInfo>)>,
+}
+
+/// A [BasisFile] that owns its data
+pub type BasisFileBuf = BasisFile<'static>;
+
+impl<'a> BasisFile<'a> {
+    /// Validates the header of the given basis data and prepares it for transcoding
+    pub fn new(data: &'a [u8]) -> Result<Self, TranscodeError> {
+        Self::from_cow(Cow::Borrowed(data))
+    }
+
+    fn from_cow(data: Cow<'a, [u8]>) -> Result<Self, TranscodeError> {
+        let mut transcoder = Transcoder::new();
+        transcoder.validate_header(&data)?;
+        let file_info = transcoder.file_info(&data)?;
+        let basis_type = transcoder.basis_texture_type(&data)?;
+        let basis_format = transcoder.basis_texture_format(&data)?;
+
+        let mut images = Vec::with_capacity(file_info.summary.m_total_images as usize);
+        for image_index in 0..file_info.summary.m_total_images {
+            let image_info = transcoder.image_info(&data, image_index)?;
+            let levels = (0..image_info.m_total_levels)
+                .map(|level_index| transcoder.image_level_info(&data, image_index, level_index))
+                .collect::<Result<Vec<_>, _>>()?;
+            images.push((image_info, levels));
+        }
+
+        transcoder.prepare_transcoding(&data)?;
+
+        Ok(BasisFile {
+            data,
+            transcoder,
+            file_info,
+            basis_type,
+            basis_format,
+            images,
+        })
+    }
+
+    /// Converts a borrowed file into one that owns its data. The prepared state is kept, so the
+    /// file doesn't need to be prepared again.
+    pub fn into_owned(self) -> BasisFileBuf {
+        BasisFile {
+            data: Cow::Owned(self.data.into_owned()),
+            transcoder: self.transcoder,
+            file_info: self.file_info,
+            basis_type: self.basis_type,
+            basis_format: self.basis_format,
+            images: self.images,
+        }
+    }
+
+    /// The basis data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The prepared transcoder, for functionality not exposed by [BasisFile]. The transcoder can
+    /// only be used with the data returned by `data()`.
+    pub fn transcoder(&self) -> &Transcoder {
+        &self.transcoder
+    }
+
+    /// Description of the basis file
+    pub fn file_info(&self) -> &FileInfo {
+        &self.file_info
+    }
+
+    /// The type of texture represented by the basis data
+    pub fn basis_texture_type(&self) -> BasisTextureType {
+        self.basis_type
+    }
+
+    /// The basis texture format of the basis data
+    pub fn basis_texture_format(&self) -> BasisTextureFormat {
+        self.basis_format
+    }
+
+    /// Arbitrary data that was attached to the basis data when it was encoded
+    pub fn user_data(&self) -> UserData {
+        UserData {
+            userdata0: self.file_info.summary.m_userdata0,
+            userdata1: self.file_info.summary.m_userdata1,
+        }
+    }
+
+    /// Number of images in the basis data
+    pub fn image_count(&self) -> u32 {
+        self.images.len() as u32
+    }
+
+    /// Returns the given image, or None if it does not exist
+    pub fn image(
+        &self,
+        image_index: u32,
+    ) -> Option<BasisFileImage<'_>> {
+        self.images
+            .get(image_index as usize)
+            .map(|(info, levels)| BasisFileImage { info, levels })
+    }
+
+    /// Iterates over all images in the basis data
+    pub fn images(&self) -> impl ExactSizeIterator<Item = BasisFileImage<'_>> + '_ {
+        self.images
+            .iter()
+            .map(|(info, levels)| BasisFileImage { info, levels })
+    }
+
+    /// Iterates over the mip levels of the given image. This is empty if the image does not exist.
+    pub fn levels(
+        &self,
+        image_index: u32,
+    ) -> std::slice::Iter<'_, ImageLevelInfo> {
+        self.images
+            .get(image_index as usize)
+            .map(|(_, levels)| &levels[..])
+            .unwrap_or_default()
+            .iter()
+    }
+
+    /// Returns information about the specified image's mipmap level.
+    pub fn image_level_info(
+        &self,
+        image_index: u32,
+        level_index: u32,
+    ) -> Result<&ImageLevelInfo, TranscodeError> {
+        let (_, levels) =
+            self.images
+                .get(image_index as usize)
+                .ok_or(TranscodeError::ImageNotFound {
+                    image_index,
+                    image_count: self.image_count(),
+                })?;
+        levels
+            .get(level_index as usize)
+            .ok_or(TranscodeError::ImageLevelNotFound {
+                image_index,
+                level_index,
+                level_count: levels.len() as u32,
+            })
+    }
+
+    /// Returns basic information about an image. Note that orig_width/orig_height may not be a multiple of 4.
+    pub fn image_level_description(
+        &self,
+        image_index: u32,
+        level_index: u32,
+    ) -> Result<ImageLevelDescription, TranscodeError> {
+        let level_info = self.image_level_info(image_index, level_index)?;
+        Ok(ImageLevelDescription {
+            original_width: level_info.m_orig_width,
+            original_height: level_info.m_orig_height,
+            block_count: level_info.m_total_blocks,
+        })
+    }
+
+    /// Returns the number of bytes needed to hold the given image level when transcoded to
+    /// `transcode_format`
+    pub fn required_output_size(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: &TranscodeParameters,
+    ) -> Result<usize, TranscodeError> {
+        let description = self.image_level_description(
+            transcode_parameters.image_index,
+            transcode_parameters.level_index,
+        )?;
+        Transcoder::required_output_bytes(&description, transcode_format, transcode_parameters)
+    }
+
+    /// Transcodes a single mipmap level. See [Transcoder::transcode_image_level]
+    pub fn transcode(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+    ) -> Result<Vec<u8>, TranscodeError> {
+        let mut output =
+            vec![0_u8; self.required_output_size(transcode_format, &transcode_parameters)?];
+        self.do_transcode(transcode_format, transcode_parameters, None, &mut output)?;
+        Ok(output)
+    }
+
+    /// Transcodes a single mipmap level into a caller-provided buffer. See
+    /// [Transcoder::transcode_image_level_into]
+    pub fn transcode_into(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        self.do_transcode(transcode_format, transcode_parameters, None, output)
+    }
+
+    /// Transcodes a single mipmap level using the given state. See
+    /// [Transcoder::transcode_image_level_with_state]
+    pub fn transcode_with_state(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        transcoder_state: &mut TranscoderState,
+    ) -> Result<Vec<u8>, TranscodeError> {
+        let mut output =
+            vec![0_u8; self.required_output_size(transcode_format, &transcode_parameters)?];
+        self.do_transcode(
+            transcode_format,
+            transcode_parameters,
+            Some(transcoder_state),
+            &mut output,
+        )?;
+        Ok(output)
+    }
+
+    /// Transcodes a single mipmap level into a caller-provided buffer using the given state. See
+    /// [Transcoder::transcode_image_level_into_with_state]
+    pub fn transcode_into_with_state(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        transcoder_state: &mut TranscoderState,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        self.do_transcode(
+            transcode_format,
+            transcode_parameters,
+            Some(transcoder_state),
+            output,
+        )
+    }
+
+    /// Transcodes every level of every image in parallel. See [Transcoder::transcode_all]
+    #[cfg(feature = "rayon")]
+    pub fn transcode_all(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+    ) -> Result<TranscodedTexture, TranscodeError> {
+        self.transcoder.transcode_all(&self.data, transcode_format)
+    }
+
+    fn do_transcode(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        transcoder_state: Option<&mut TranscoderState>,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        let image_index = transcode_parameters.image_index;
+        let level_index = transcode_parameters.level_index;
+        let description = self.image_level_description(image_index, level_index)?;
+        Transcoder::check_transcode_format(
+            self.basis_format,
+            transcode_format,
+            image_index,
+            level_index,
+            &description,
+        )?;
+
+        self.transcoder.do_transcode_image_level(
+            &self.data,
+            transcode_format,
+            transcode_parameters,
+            &description,
+            transcoder_state,
+            output,
+        )
+    }
+}
+
+impl BasisFileBuf {
+    /// Validates the header of the given basis data and prepares it for transcoding. The file takes
+    /// ownership of the data.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, TranscodeError> {
+        Self::from_cow(Cow::Owned(data))
+    }
+}
@@ -0,0 +1,568 @@
+//! Reading and writing transcoded textures as .dds files
+//!
+//! Formats that have a `DXGI_FORMAT` (see [TranscoderTextureFormat::dxgi_format]) can be written.
+//! BC1, BC3, BC4, BC5 and RGBA32 textures use the legacy header when possible. BC7, sRGB formats,
+//! the 16-bit formats, arrays and cubemap arrays use the DX10 extended header.
+
+use super::{
+    BasisTextureType, TranscodeError, TranscodeParameters, Transcoder, TranscoderTextureFormat,
+};
+use std::convert::TryFrom;
+use std::io::Write;
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: usize = 124;
+const PIXEL_FORMAT_SIZE: u32 = 32;
+const DX10_HEADER_SIZE: usize = 20;
+
+// DDS_HEADER::dwFlags
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+// DDS_PIXELFORMAT::dwFlags
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+// DDS_HEADER::dwCaps and dwCaps2
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+
+// DDS_HEADER_DXT10
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+const D3D10_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+// Formats that can be written with the legacy header, and the formats that are recognized when
+// reading legacy headers
+const LEGACY_FOURCCS: &[(TranscoderTextureFormat, &[u8; 4])] = &[
+    (TranscoderTextureFormat::BC1_RGB, b"DXT1"),
+    (TranscoderTextureFormat::BC3_RGBA, b"DXT5"),
+    (TranscoderTextureFormat::BC4_R, b"ATI1"),
+    (TranscoderTextureFormat::BC5_RG, b"ATI2"),
+    (TranscoderTextureFormat::BC4_R, b"BC4U"),
+    (TranscoderTextureFormat::BC5_RG, b"BC5U"),
+];
+
+// Formats that can be stored in a DDS file
+const DDS_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::BC4_R,
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::RGBA32,
+    TranscoderTextureFormat::RGB565,
+    TranscoderTextureFormat::RGBA4444,
+];
+
+/// Error result from reading or writing a DDS file
+#[derive(Debug)]
+pub enum DdsError {
+    /// The basis data could not be transcoded
+    Transcode(TranscodeError),
+    /// Writing the file failed
+    Io(std::io::Error),
+    /// The format can't be stored in a DDS file
+    FormatNotSupported(TranscoderTextureFormat),
+    /// Volume textures can't be written because their mip levels don't shrink in depth
+    TextureTypeNotSupported(BasisTextureType),
+    /// Images of an array or cubemap must all have the same size and number of mip levels
+    ImagesDiffer { image_index: u32 },
+    /// Cubemaps need a multiple of 6 images
+    InvalidCubemap { image_count: u32 },
+    /// The texture data is not the size described by the layout
+    DataSizeMismatch {
+        expected_bytes: usize,
+        provided_bytes: usize,
+    },
+    /// The file is not a DDS file that can be read
+    InvalidFile(&'static str),
+}
+
+impl std::fmt::Display for DdsError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            DdsError::Transcode(error) => write!(f, "failed to transcode: {}", error),
+            DdsError::Io(error) => write!(f, "failed to write DDS file: {}", error),
+            DdsError::FormatNotSupported(format) => {
+                write!(f, "{:?} can't be stored in a DDS file", format)
+            }
+            DdsError::TextureTypeNotSupported(texture_type) => {
+                write!(f, "{:?} can't be stored in a DDS file", texture_type)
+            }
+            DdsError::ImagesDiffer { image_index } => write!(
+                f,
+                "image {} has a different size or level count than image 0",
+                image_index
+            ),
+            DdsError::InvalidCubemap { image_count } => write!(
+                f,
+                "cubemaps need a multiple of 6 images, but there are {}",
+                image_count
+            ),
+            DdsError::DataSizeMismatch {
+                expected_bytes,
+                provided_bytes,
+            } => write!(
+                f,
+                "texture data is {} bytes, but the layout needs {} bytes",
+                provided_bytes, expected_bytes
+            ),
+            DdsError::InvalidFile(reason) => write!(f, "invalid DDS file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DdsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DdsError::Transcode(error) => Some(error),
+            DdsError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TranscodeError> for DdsError {
+    fn from(error: TranscodeError) -> Self {
+        DdsError::Transcode(error)
+    }
+}
+
+impl From<std::io::Error> for DdsError {
+    fn from(error: std::io::Error) -> Self {
+        DdsError::Io(error)
+    }
+}
+
+/// Describes the surfaces stored in a DDS file. The data is stored layer by layer (cubemap faces
+/// in X+, X-, Y+, Y-, Z+, Z- order), with all mip levels of a layer stored together.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DdsLayout {
+    pub format: TranscoderTextureFormat,
+    /// Ignored for formats that don't have an sRGB variant, such as BC4 and BC5
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    pub level_count: u32,
+    /// Number of array elements. For cubemaps this is the number of cubes, not faces.
+    pub array_size: u32,
+    pub is_cubemap: bool,
+}
+
+impl DdsLayout {
+    /// Number of 2D surfaces of each mip level (faces for cubemaps)
+    pub fn layer_count(&self) -> u32 {
+        if self.is_cubemap {
+            self.array_size * 6
+        } else {
+            self.array_size
+        }
+    }
+
+    /// Width and height of the given mip level in pixels
+    pub fn level_dimensions(
+        &self,
+        level_index: u32,
+    ) -> (u32, u32) {
+        (
+            (self.width >> level_index).max(1),
+            (self.height >> level_index).max(1),
+        )
+    }
+
+    /// Size in bytes of a single surface of the given mip level
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size does not fit in a usize. Layouts returned by [read_dds] always fit.
+    pub fn level_size(
+        &self,
+        level_index: u32,
+    ) -> usize {
+        self.checked_level_size(level_index)
+            .expect("DDS level size does not fit in a usize")
+    }
+
+    /// Size in bytes of all surfaces
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size does not fit in a usize. Layouts returned by [read_dds] always fit.
+    pub fn data_size(&self) -> usize {
+        self.checked_data_size()
+            .expect("DDS data size does not fit in a usize")
+    }
+
+    fn checked_level_size(
+        &self,
+        level_index: u32,
+    ) -> Option<usize> {
+        let (width, height) = self.level_dimensions(level_index);
+        let (width, height) = if self.format.is_compressed() {
+            // Round up to whole blocks. Level dimensions are never 0, and this can't overflow
+            let blocks = |size: u32, block_size: u32| ((size - 1) / block_size + 1) as u64;
+            (
+                blocks(width, self.format.block_width()),
+                blocks(height, self.format.block_height()),
+            )
+        } else {
+            (width as u64, height as u64)
+        };
+        let size = width
+            .checked_mul(height)?
+            .checked_mul(self.format.bytes_per_block_or_pixel() as u64)?;
+        usize::try_from(size).ok()
+    }
+
+    fn checked_data_size(&self) -> Option<usize> {
+        let mut layer_size = 0_usize;
+        for level_index in 0..self.level_count {
+            layer_size = layer_size.checked_add(self.checked_level_size(level_index)?)?;
+        }
+        let layer_count = if self.is_cubemap {
+            self.array_size.checked_mul(6)?
+        } else {
+            self.array_size
+        };
+        layer_size.checked_mul(layer_count as usize)
+    }
+
+    /// Byte range of the given layer and mip level within the texture data
+    pub fn surface_range(
+        &self,
+        layer_index: u32,
+        level_index: u32,
+    ) -> Option<std::ops::Range<usize>> {
+        if layer_index >= self.layer_count() || level_index >= self.level_count {
+            return None;
+        }
+
+        let layer_size: usize = (0..self.level_count).map(|i| self.level_size(i)).sum();
+        let start = layer_size * layer_index as usize
+            + (0..level_index).map(|i| self.level_size(i)).sum::<usize>();
+        Some(start..start + self.level_size(level_index))
+    }
+
+    // srgb is ignored for formats that don't have an sRGB variant
+    fn is_srgb(&self) -> bool {
+        self.srgb && self.format.dxgi_format(true) != self.format.dxgi_format(false)
+    }
+
+    // The legacy header can't express sRGB, arrays or cubemap arrays
+    fn legacy_fourcc(&self) -> Option<&'static [u8; 4]> {
+        if self.is_srgb() || self.array_size > 1 {
+            return None;
+        }
+
+        LEGACY_FOURCCS
+            .iter()
+            .find(|(format, _)| *format == self.format)
+            .map(|(_, fourcc)| *fourcc)
+    }
+}
+
+/// Writes a DDS file containing `data`, which must hold every surface of the layout in the order
+/// described by [DdsLayout]
+pub fn write_dds<W: Write>(
+    writer: &mut W,
+    layout: &DdsLayout,
+    data: &[u8],
+) -> Result<(), DdsError> {
+    let dxgi_format = match layout.format.dxgi_format(layout.srgb) {
+        Some(dxgi_format) if DDS_FORMATS.contains(&layout.format) => dxgi_format,
+        _ => return Err(DdsError::FormatNotSupported(layout.format)),
+    };
+
+    if data.len() != layout.data_size() {
+        return Err(DdsError::DataSizeMismatch {
+            expected_bytes: layout.data_size(),
+            provided_bytes: data.len(),
+        });
+    }
+
+    let legacy_fourcc = layout.legacy_fourcc();
+    let legacy_rgba = layout.format == TranscoderTextureFormat::RGBA32
+        && !layout.is_srgb()
+        && layout.array_size == 1;
+    let use_dx10 = legacy_fourcc.is_none() && !legacy_rgba;
+
+    let mut header = [0_u32; HEADER_SIZE / 4];
+    header[0] = HEADER_SIZE as u32;
+    header[1] = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+    header[2] = layout.height;
+    header[3] = layout.width;
+    if layout.format.is_compressed() {
+        header[1] |= DDSD_LINEARSIZE;
+        header[4] = layout.level_size(0) as u32;
+    } else {
+        header[1] |= DDSD_PITCH;
+        header[4] = layout.width * layout.format.bytes_per_block_or_pixel();
+    }
+    header[6] = layout.level_count;
+    if layout.level_count > 1 {
+        header[1] |= DDSD_MIPMAPCOUNT;
+    }
+
+    // DDS_PIXELFORMAT starts at dword 18
+    header[18] = PIXEL_FORMAT_SIZE;
+    if let Some(fourcc) = legacy_fourcc {
+        header[19] = DDPF_FOURCC;
+        header[20] = u32::from_le_bytes(*fourcc);
+    } else if use_dx10 {
+        header[19] = DDPF_FOURCC;
+        header[20] = u32::from_le_bytes(*b"DX10");
+    } else {
+        header[19] = DDPF_RGB | DDPF_ALPHAPIXELS;
+        header[21] = 32;
+        header[22] = 0x0000_00FF;
+        header[23] = 0x0000_FF00;
+        header[24] = 0x00FF_0000;
+        header[25] = 0xFF00_0000;
+    }
+
+    header[26] = DDSCAPS_TEXTURE;
+    if layout.level_count > 1 {
+        header[26] |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+    if layout.is_cubemap {
+        header[26] |= DDSCAPS_COMPLEX;
+        header[27] = DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALLFACES;
+    }
+
+    writer.write_all(DDS_MAGIC)?;
+    for value in header.iter() {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+
+    if use_dx10 {
+        let misc_flag = if layout.is_cubemap {
+            D3D10_RESOURCE_MISC_TEXTURECUBE
+        } else {
+            0
+        };
+        for value in [
+            dxgi_format,
+            D3D10_RESOURCE_DIMENSION_TEXTURE2D,
+            misc_flag,
+            layout.array_size,
+            0,
+        ]
+        .iter()
+        {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    writer.write_all(data)?;
+    Ok(())
+}
+
+/// Reads the layout of a DDS file written by [write_dds] and returns it along with the texture data
+pub fn read_dds(dds: &[u8]) -> Result<(DdsLayout, &[u8]), DdsError> {
+    if dds.len() < DDS_MAGIC.len() + HEADER_SIZE || &dds[0..4] != DDS_MAGIC {
+        return Err(DdsError::InvalidFile("missing DDS header"));
+    }
+
+    let header: Vec<u32> = dds[4..4 + HEADER_SIZE]
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    if header[0] as usize != HEADER_SIZE || header[18] != PIXEL_FORMAT_SIZE {
+        return Err(DdsError::InvalidFile("invalid header size"));
+    }
+
+    let height = header[2];
+    let width = header[3];
+    let level_count = if header[1] & DDSD_MIPMAPCOUNT != 0 {
+        header[6].max(1)
+    } else {
+        1
+    };
+    let pixel_format_flags = header[19];
+    let fourcc = header[20].to_le_bytes();
+
+    let mut data_offset = DDS_MAGIC.len() + HEADER_SIZE;
+    let (format, srgb, array_size, is_cubemap) =
+        if pixel_format_flags & DDPF_FOURCC != 0 && &fourcc == b"DX10" {
+            if dds.len() < data_offset + DX10_HEADER_SIZE {
+                return Err(DdsError::InvalidFile("missing DX10 header"));
+            }
+
+            let dx10: Vec<u32> = dds[data_offset..data_offset + DX10_HEADER_SIZE]
+                .chunks_exact(4)
+                .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect();
+            data_offset += DX10_HEADER_SIZE;
+
+            if dx10[1] != D3D10_RESOURCE_DIMENSION_TEXTURE2D {
+                return Err(DdsError::InvalidFile("only 2D textures are supported"));
+            }
+
+            let (format, srgb) = DDS_FORMATS
+                .iter()
+                .find_map(|&format| {
+                    if format.dxgi_format(false) == Some(dx10[0]) {
+                        Some((format, false))
+                    } else if format.dxgi_format(true) == Some(dx10[0]) {
+                        Some((format, true))
+                    } else {
+                        None
+                    }
+                })
+                .ok_or(DdsError::InvalidFile("unsupported DXGI format"))?;
+            let is_cubemap = dx10[2] & D3D10_RESOURCE_MISC_TEXTURECUBE != 0;
+            (format, srgb, dx10[3].max(1), is_cubemap)
+        } else {
+            let format = if pixel_format_flags & DDPF_FOURCC != 0 {
+                LEGACY_FOURCCS
+                    .iter()
+                    .find(|(_, format_fourcc)| **format_fourcc == fourcc)
+                    .map(|(format, _)| *format)
+                    .ok_or(DdsError::InvalidFile("unsupported FourCC"))?
+            } else if pixel_format_flags & DDPF_RGB != 0
+                && header[21] == 32
+                && header[22..26] == [0x0000_00FF, 0x0000_FF00, 0x00FF_0000, 0xFF00_0000]
+            {
+                TranscoderTextureFormat::RGBA32
+            } else {
+                return Err(DdsError::InvalidFile("unsupported pixel format"));
+            };
+
+            let is_cubemap = header[27] & DDSCAPS2_CUBEMAP != 0;
+            (format, false, 1, is_cubemap)
+        };
+
+    if width == 0 || height == 0 || level_count > 32 || array_size as usize > dds.len() {
+        return Err(DdsError::InvalidFile("invalid dimensions"));
+    }
+
+    let layout = DdsLayout {
+        format,
+        srgb,
+        width,
+        height,
+        level_count,
+        array_size,
+        is_cubemap,
+    };
+
+    // The dimensions come straight from the file, so the size can overflow
+    let data_size = layout
+        .checked_data_size()
+        .ok_or(DdsError::InvalidFile("texture is too large"))?;
+    let data = &dds[data_offset..];
+    if data.len() < data_size {
+        return Err(DdsError::DataSizeMismatch {
+            expected_bytes: data_size,
+            provided_bytes: data.len(),
+        });
+    }
+
+    Ok((layout, &data[..data_size]))
+}
+
+impl Transcoder {
+    /// Transcodes every image and mip level of the basis data to `transcode_format` and writes
+    /// them as a DDS file. `prepare_transcoding()` must have been called first.
+    ///
+    /// Cubemap files are written as cubemaps, other files with more than one image are written as
+    /// arrays. Every image must have the same size and number of mip levels.
+    pub fn write_dds<W: Write>(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        srgb: bool,
+        writer: &mut W,
+    ) -> Result<(), DdsError> {
+        let layout = self.dds_layout(data, transcode_format, srgb)?;
+
+        let mut output = vec![0_u8; layout.data_size()];
+        for image_index in 0..layout.layer_count() {
+            for level_index in 0..layout.level_count {
+                let range = layout.surface_range(image_index, level_index).unwrap();
+                self.transcode_image_level_into(
+                    data,
+                    transcode_format,
+                    TranscodeParameters {
+                        image_index,
+                        level_index,
+                        ..Default::default()
+                    },
+                    &mut output[range],
+                )?;
+            }
+        }
+
+        write_dds(writer, &layout, &output)
+    }
+
+    // Works out how the basis data maps to DDS surfaces
+    fn dds_layout(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        srgb: bool,
+    ) -> Result<DdsLayout, DdsError> {
+        if !DDS_FORMATS.contains(&transcode_format) {
+            return Err(DdsError::FormatNotSupported(transcode_format));
+        }
+
+        let texture_type = self.basis_texture_type(data)?;
+        let image_count = self.image_count(data)?;
+        let is_cubemap = match texture_type {
+            BasisTextureType::TextureTypeCubemapArray => {
+                if image_count % 6 != 0 {
+                    return Err(DdsError::InvalidCubemap { image_count });
+                }
+                true
+            }
+            BasisTextureType::TextureTypeVolume => {
+                return Err(DdsError::TextureTypeNotSupported(texture_type))
+            }
+            _ => false,
+        };
+
+        let first_level = self.image_level_description(data, 0, 0)?;
+        let layout = DdsLayout {
+            format: transcode_format,
+            srgb: srgb && transcode_format.dxgi_format(true) != transcode_format.dxgi_format(false),
+            width: first_level.original_width,
+            height: first_level.original_height,
+            level_count: self.image_level_count(data, 0)?,
+            array_size: if is_cubemap {
+                image_count / 6
+            } else {
+                image_count
+            },
+            is_cubemap,
+        };
+
+        for image_index in 0..image_count {
+            if self.image_level_count(data, image_index)? != layout.level_count {
+                return Err(DdsError::ImagesDiffer { image_index });
+            }
+
+            for level_index in 0..layout.level_count {
+                let description = self.image_level_description(data, image_index, level_index)?;
+                let dimensions = (description.original_width, description.original_height);
+                if dimensions != layout.level_dimensions(level_index) {
+                    return Err(DdsError::ImagesDiffer { image_index });
+                }
+            }
+        }
+
+        Ok(layout)
+    }
+}
@@ -1,4 +1,5 @@
 use basis_universal_sys as sys;
+use std::convert::TryFrom;
 use std::ffi::CStr;
 
 /// The type of data stored
@@ -23,9 +24,39 @@ impl Into<sys::basist_basis_texture_type> for BasisTextureType {
     }
 }
 
-impl From<sys::basist_basis_texture_type> for BasisTextureType {
-    fn from(value: sys::basist_basis_texture_type) -> Self {
-        unsafe { std::mem::transmute(value as u32) }
+impl TryFrom<sys::basist_basis_texture_type> for BasisTextureType {
+    type Error = sys::basist_basis_texture_type;
+
+    fn try_from(value: sys::basist_basis_texture_type) -> Result<Self, Self::Error> {
+        match value {
+            sys::basist_basis_texture_type_cBASISTexType2D => Ok(BasisTextureType::TextureType2D),
+            sys::basist_basis_texture_type_cBASISTexType2DArray => {
+                Ok(BasisTextureType::TextureType2DArray)
+            }
+            sys::basist_basis_texture_type_cBASISTexTypeCubemapArray => {
+                Ok(BasisTextureType::TextureTypeCubemapArray)
+            }
+            sys::basist_basis_texture_type_cBASISTexTypeVideoFrames => {
+                Ok(BasisTextureType::TextureTypeVideoFrames)
+            }
+            sys::basist_basis_texture_type_cBASISTexTypeVolume => {
+                Ok(BasisTextureType::TextureTypeVolume)
+            }
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<super::file_format::TextureType> for BasisTextureType {
+    fn from(texture_type: super::file_format::TextureType) -> Self {
+        use super::file_format::TextureType;
+        match texture_type {
+            TextureType::TextureType2D => BasisTextureType::TextureType2D,
+            TextureType::TextureType2DArray => BasisTextureType::TextureType2DArray,
+            TextureType::TextureTypeCubemapArray => BasisTextureType::TextureTypeCubemapArray,
+            TextureType::TextureTypeVideoFrames => BasisTextureType::TextureTypeVideoFrames,
+            TextureType::TextureTypeVolume => BasisTextureType::TextureTypeVolume,
+        }
     }
 }
 
@@ -61,9 +92,25 @@ impl Into<sys::basist_basis_tex_format> for BasisTextureFormat {
     }
 }
 
-impl From<sys::basist_basis_tex_format> for BasisTextureFormat {
-    fn from(value: sys::basist_basis_tex_format) -> Self {
-        unsafe { std::mem::transmute(value as i32) }
+impl TryFrom<sys::basist_basis_tex_format> for BasisTextureFormat {
+    type Error = sys::basist_basis_tex_format;
+
+    fn try_from(value: sys::basist_basis_tex_format) -> Result<Self, Self::Error> {
+        match value {
+            sys::basist_basis_tex_format_cETC1S => Ok(BasisTextureFormat::ETC1S),
+            sys::basist_basis_tex_format_cUASTC4x4 => Ok(BasisTextureFormat::UASTC4x4),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<super::file_format::TextureFormat> for BasisTextureFormat {
+    fn from(texture_format: super::file_format::TextureFormat) -> Self {
+        use super::file_format::TextureFormat;
+        match texture_format {
+            TextureFormat::ETC1S => BasisTextureFormat::ETC1S,
+            TextureFormat::UASTC4x4 => BasisTextureFormat::UASTC4x4,
+        }
     }
 }
 
@@ -160,9 +207,66 @@ impl Into<sys::basist_transcoder_texture_format> for TranscoderTextureFormat {
     }
 }
 
-impl From<sys::basist_transcoder_texture_format> for TranscoderTextureFormat {
-    fn from(value: sys::basist_transcoder_texture_format) -> Self {
-        unsafe { std::mem::transmute(value as i32) }
+impl TryFrom<sys::basist_transcoder_texture_format> for TranscoderTextureFormat {
+    type Error = sys::basist_transcoder_texture_format;
+
+    fn try_from(value: sys::basist_transcoder_texture_format) -> Result<Self, Self::Error> {
+        match value {
+            sys::basist_transcoder_texture_format_cTFETC1_RGB => {
+                Ok(TranscoderTextureFormat::ETC1_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFETC2_RGBA => {
+                Ok(TranscoderTextureFormat::ETC2_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFBC1_RGB => {
+                Ok(TranscoderTextureFormat::BC1_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFBC3_RGBA => {
+                Ok(TranscoderTextureFormat::BC3_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFBC4_R => Ok(TranscoderTextureFormat::BC4_R),
+            sys::basist_transcoder_texture_format_cTFBC5_RG => Ok(TranscoderTextureFormat::BC5_RG),
+            sys::basist_transcoder_texture_format_cTFBC7_RGBA => {
+                Ok(TranscoderTextureFormat::BC7_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFPVRTC1_4_RGB => {
+                Ok(TranscoderTextureFormat::PVRTC1_4_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFPVRTC1_4_RGBA => {
+                Ok(TranscoderTextureFormat::PVRTC1_4_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFASTC_4x4_RGBA => {
+                Ok(TranscoderTextureFormat::ASTC_4x4_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFATC_RGB => {
+                Ok(TranscoderTextureFormat::ATC_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFATC_RGBA => {
+                Ok(TranscoderTextureFormat::ATC_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFFXT1_RGB => {
+                Ok(TranscoderTextureFormat::FXT1_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFPVRTC2_4_RGB => {
+                Ok(TranscoderTextureFormat::PVRTC2_4_RGB)
+            }
+            sys::basist_transcoder_texture_format_cTFPVRTC2_4_RGBA => {
+                Ok(TranscoderTextureFormat::PVRTC2_4_RGBA)
+            }
+            sys::basist_transcoder_texture_format_cTFETC2_EAC_R11 => {
+                Ok(TranscoderTextureFormat::ETC2_EAC_R11)
+            }
+            sys::basist_transcoder_texture_format_cTFETC2_EAC_RG11 => {
+                Ok(TranscoderTextureFormat::ETC2_EAC_RG11)
+            }
+            sys::basist_transcoder_texture_format_cTFRGBA32 => Ok(TranscoderTextureFormat::RGBA32),
+            sys::basist_transcoder_texture_format_cTFRGB565 => Ok(TranscoderTextureFormat::RGB565),
+            sys::basist_transcoder_texture_format_cTFBGR565 => Ok(TranscoderTextureFormat::BGR565),
+            sys::basist_transcoder_texture_format_cTFRGBA4444 => {
+                Ok(TranscoderTextureFormat::RGBA4444)
+            }
+            _ => Err(value),
+        }
     }
 }
 
@@ -215,7 +319,7 @@ impl TranscoderTextureFormat {
     }
 
     /// Calculate the minimum output buffer required to store transcoded data in blocks for
-    /// compressed formats and pixels for uncompressed formats
+    /// compressed formats and pixels for uncompressed formats. Saturates at `u32::MAX`.
     pub fn calculate_minimum_output_buffer_blocks_or_pixels(
         self,
         original_width: u32,
@@ -240,7 +344,7 @@ impl TranscoderTextureFormat {
                 output_rows_in_pixels = original_height;
             }
 
-            output_rows_in_pixels * output_row_pitch_in_blocks_or_pixels
+            output_rows_in_pixels.saturating_mul(output_row_pitch_in_blocks_or_pixels)
         } else if self == TranscoderTextureFormat::FXT1_RGB {
             let num_blocks_fxt1_x = (original_width + 7) / 8;
             let num_blocks_fxt1_y = (original_height + 3) / 4;
@@ -261,7 +365,8 @@ impl TranscoderTextureFormat {
         minimum_output_buffer_blocks_or_pixels
     }
 
-    /// Calculate the minimum output buffer required to store transcoded data in bytes
+    /// Calculate the minimum output buffer required to store transcoded data in bytes. Saturates
+    /// at `u32::MAX`.
     pub fn calculate_minimum_output_buffer_bytes(
         self,
         original_width: u32,
@@ -276,7 +381,8 @@ impl TranscoderTextureFormat {
             total_slice_blocks,
             output_row_pitch_in_blocks_or_pixels,
             output_rows_in_pixels,
-        ) * self.bytes_per_block_or_pixel()
+        )
+        .saturating_mul(self.bytes_per_block_or_pixel())
     }
 
     /// Verify that the buffer size is large enough for the transcoded data
@@ -394,9 +500,47 @@ impl Into<sys::basist_block_format> for TranscoderBlockFormat {
     }
 }
 
-impl From<sys::basist_block_format> for TranscoderBlockFormat {
-    fn from(value: sys::basist_block_format) -> Self {
-        unsafe { std::mem::transmute(value as i32) }
+impl TryFrom<sys::basist_block_format> for TranscoderBlockFormat {
+    type Error = sys::basist_block_format;
+
+    fn try_from(value: sys::basist_block_format) -> Result<Self, Self::Error> {
+        match value {
+            sys::basist_block_format_cETC1 => Ok(TranscoderBlockFormat::ETC1),
+            sys::basist_block_format_cETC2_RGBA => Ok(TranscoderBlockFormat::ETC2_RGBA),
+            sys::basist_block_format_cBC1 => Ok(TranscoderBlockFormat::BC1),
+            sys::basist_block_format_cBC3 => Ok(TranscoderBlockFormat::BC3),
+            sys::basist_block_format_cBC4 => Ok(TranscoderBlockFormat::BC4),
+            sys::basist_block_format_cBC5 => Ok(TranscoderBlockFormat::BC5),
+            sys::basist_block_format_cPVRTC1_4_RGB => Ok(TranscoderBlockFormat::PVRTC1_4_RGB),
+            sys::basist_block_format_cPVRTC1_4_RGBA => Ok(TranscoderBlockFormat::PVRTC1_4_RGBA),
+            sys::basist_block_format_cBC7 => Ok(TranscoderBlockFormat::BC7),
+            sys::basist_block_format_cBC7_M5_COLOR => Ok(TranscoderBlockFormat::BC7_M5_COLOR),
+            sys::basist_block_format_cBC7_M5_ALPHA => Ok(TranscoderBlockFormat::BC7_M5_ALPHA),
+            sys::basist_block_format_cETC2_EAC_A8 => Ok(TranscoderBlockFormat::ETC2_EAC_A8),
+            sys::basist_block_format_cASTC_4x4 => Ok(TranscoderBlockFormat::ASTC_4x4),
+            sys::basist_block_format_cATC_RGB => Ok(TranscoderBlockFormat::ATC_RGB),
+            sys::basist_block_format_cATC_RGBA_INTERPOLATED_ALPHA => {
+                Ok(TranscoderBlockFormat::ATC_RGBA_INTERPOLATED_ALPHA)
+            }
+            sys::basist_block_format_cFXT1_RGB => Ok(TranscoderBlockFormat::FXT1_RGB),
+            sys::basist_block_format_cPVRTC2_4_RGB => Ok(TranscoderBlockFormat::PVRTC2_4_RGB),
+            sys::basist_block_format_cPVRTC2_4_RGBA => Ok(TranscoderBlockFormat::PVRTC2_4_RGBA),
+            sys::basist_block_format_cETC2_EAC_R11 => Ok(TranscoderBlockFormat::ETC2_EAC_R11),
+            sys::basist_block_format_cETC2_EAC_RG11 => Ok(TranscoderBlockFormat::ETC2_EAC_RG11),
+            sys::basist_block_format_cIndices => Ok(TranscoderBlockFormat::Indices),
+            sys::basist_block_format_cRGB32 => Ok(TranscoderBlockFormat::RGB32),
+            sys::basist_block_format_cRGBA32 => Ok(TranscoderBlockFormat::RGBA32),
+            sys::basist_block_format_cA32 => Ok(TranscoderBlockFormat::A32),
+            sys::basist_block_format_cRGB565 => Ok(TranscoderBlockFormat::RGB565),
+            sys::basist_block_format_cBGR565 => Ok(TranscoderBlockFormat::BGR565),
+            sys::basist_block_format_cRGBA4444_COLOR => Ok(TranscoderBlockFormat::RGBA4444_COLOR),
+            sys::basist_block_format_cRGBA4444_ALPHA => Ok(TranscoderBlockFormat::RGBA4444_ALPHA),
+            sys::basist_block_format_cRGBA4444_COLOR_OPAQUE => {
+                Ok(TranscoderBlockFormat::RGBA4444_COLOR_OPAQUE)
+            }
+            sys::basist_block_format_cRGBA4444 => Ok(TranscoderBlockFormat::RGBA4444),
+            _ => Err(value),
+        }
     }
 }
 
@@ -464,7 +608,7 @@ impl TranscoderBlockFormat {
     }
 
     /// Calculate the minimum output buffer required to store transcoded data in blocks for
-    /// compressed formats and pixels for uncompressed formats
+    /// compressed formats and pixels for uncompressed formats. Saturates at `u32::MAX`.
     pub fn calculate_minimum_output_buffer_blocks_or_pixels(
         self,
         original_width: u32,
@@ -490,7 +634,7 @@ impl TranscoderBlockFormat {
                 output_rows_in_pixels = original_height;
             }
 
-            output_rows_in_pixels * output_row_pitch_in_blocks_or_pixels
+            output_rows_in_pixels.saturating_mul(output_row_pitch_in_blocks_or_pixels)
         } else if self == TranscoderBlockFormat::FXT1_RGB {
             let num_blocks_fxt1_x = (original_width + 7) / 8;
             let num_blocks_fxt1_y = (original_height + 3) / 4;
@@ -500,7 +644,8 @@ impl TranscoderBlockFormat {
         }
     }
 
-    /// Calculate the minimum output buffer required to store transcoded data in bytes
+    /// Calculate the minimum output buffer required to store transcoded data in bytes. Saturates
+    /// at `u32::MAX`.
     pub fn calculate_minimum_output_buffer_bytes(
         self,
         original_width: u32,
@@ -515,6 +660,7 @@ impl TranscoderBlockFormat {
             total_slice_blocks,
             output_row_pitch_in_blocks_or_pixels,
             output_rows_in_pixels,
-        ) * self.bytes_per_block_or_pixel()
+        )
+        .saturating_mul(self.bytes_per_block_or_pixel())
     }
 }
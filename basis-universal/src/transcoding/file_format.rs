@@ -0,0 +1,668 @@
+//! Pure-Rust reader for the structure of .basis files
+//!
+//! This reads the header and slice descriptions without going through the C++ transcoder, so it
+//! can be used to inspect files (image count, dimensions, format, user data) without initializing
+//! the transcoder. Every offset and size read from the file is bounds checked. It does not allocate
+//! and only uses `core`, apart from the `std::error::Error` impl that the `std` feature enables. The
+//! texture format and type have their own enums here, which convert into [BasisTextureFormat] and
+//! [BasisTextureType].
+//!
+//! Decoding the slices still requires the [Transcoder](super::Transcoder).
+//!
+//! [BasisTextureFormat]: super::BasisTextureFormat
+//! [BasisTextureType]: super::BasisTextureType
+
+use super::ImageLevelDescription;
+use crate::UserData;
+use core::fmt;
+
+/// Size of the .basis file header in bytes
+pub const HEADER_SIZE: usize = 77;
+
+/// Size of a single slice description in bytes
+pub const SLICE_DESCRIPTION_SIZE: usize = 23;
+
+/// Signature at the start of every .basis file ("sB")
+pub const SIGNATURE: u16 = 0x4273;
+
+/// The file version this parser understands
+pub const VERSION: u16 = 0x13;
+
+/// Format of the slices in the file, as stored in the header
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextureFormat {
+    ETC1S,
+    UASTC4x4,
+}
+
+/// Type of texture stored in the file, as stored in the header
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextureType {
+    TextureType2D,
+    TextureType2DArray,
+    TextureTypeCubemapArray,
+    TextureTypeVideoFrames,
+    TextureTypeVolume,
+}
+
+bitflags::bitflags! {
+    /// Flags stored in the file header
+    pub struct HeaderFlags: u16 {
+        /// The slices are ETC1S data
+        const ETC1S = 1;
+        /// The images were flipped vertically when they were encoded
+        const Y_FLIPPED = 2;
+        /// ETC1S files with alpha store a separate alpha slice for each color slice
+        const HAS_ALPHA_SLICES = 4;
+        /// The file refers to an external global codebook instead of storing its own
+        const USES_GLOBAL_CODEBOOK = 8;
+        /// The texture data is sRGB rather than linear
+        const SRGB = 16;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags stored in each slice description
+    pub struct SliceFlags: u8 {
+        /// The slice holds alpha data (or for UASTC, color data with alpha)
+        const HAS_ALPHA = 1;
+        /// The slice is an I-frame of a video texture
+        const FRAME_IS_IFRAME = 2;
+    }
+}
+
+/// Error result from parsing the structure of a .basis file
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParseError {
+    /// The data is too small to hold the header or the data described by the header
+    TooSmall {
+        required_bytes: usize,
+        provided_bytes: usize,
+    },
+    /// The data does not start with the .basis signature
+    InvalidSignature(u16),
+    /// The file was written by an unsupported version of the encoder
+    UnsupportedVersion(u16),
+    /// The header size stored in the file is not the size of the header
+    InvalidHeaderSize(u16),
+    /// The texture format stored in the header is unknown
+    InvalidTextureFormat(u8),
+    /// The texture type stored in the header is unknown
+    InvalidTextureType(u8),
+    /// The ETC1S flag does not match the texture format
+    FormatFlagMismatch,
+    /// The file has no slices or images, or more images than slices
+    InvalidImageCount { image_count: u32, slice_count: u32 },
+    /// The slice description table lies outside of the file
+    SliceTableOutOfBounds { offset: u32, slice_count: u32 },
+    /// The data of a slice lies outside of the file
+    SliceOutOfBounds {
+        slice_index: u32,
+        offset: u32,
+        size: u32,
+    },
+    /// A slice refers to an image that does not exist
+    SliceImageOutOfRange { slice_index: u32, image_index: u32 },
+    /// The crc16 of the header does not match the contents of the header
+    HeaderChecksumMismatch { expected: u16, actual: u16 },
+    /// The crc16 of the data does not match the contents of the file
+    DataChecksumMismatch { expected: u16, actual: u16 },
+    /// The requested slice does not exist
+    SliceNotFound { slice_index: u32, slice_count: u32 },
+    /// The requested image does not exist
+    ImageNotFound { image_index: u32, image_count: u32 },
+    /// The requested mip level does not exist on the image
+    ImageLevelNotFound {
+        image_index: u32,
+        level_index: u32,
+        level_count: u32,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match *self {
+            ParseError::TooSmall {
+                required_bytes,
+                provided_bytes,
+            } => write!(
+                f,
+                ".basis data is too small ({} bytes required, {} bytes provided)",
+                required_bytes, provided_bytes
+            ),
+            ParseError::InvalidSignature(signature) => {
+                write!(f, "invalid .basis signature {:#06x}", signature)
+            }
+            ParseError::UnsupportedVersion(version) => {
+                write!(f, "unsupported .basis version {:#x}", version)
+            }
+            ParseError::InvalidHeaderSize(header_size) => {
+                write!(f, "invalid .basis header size {}", header_size)
+            }
+            ParseError::InvalidTextureFormat(format) => {
+                write!(f, "invalid texture format {}", format)
+            }
+            ParseError::InvalidTextureType(texture_type) => {
+                write!(f, "invalid texture type {}", texture_type)
+            }
+            ParseError::FormatFlagMismatch => {
+                write!(f, "ETC1S flag does not match the texture format")
+            }
+            ParseError::InvalidImageCount {
+                image_count,
+                slice_count,
+            } => write!(
+                f,
+                "invalid image count {} for {} slices",
+                image_count, slice_count
+            ),
+            ParseError::SliceTableOutOfBounds {
+                offset,
+                slice_count,
+            } => write!(
+                f,
+                "slice table at offset {} with {} slices is out of bounds",
+                offset, slice_count
+            ),
+            ParseError::SliceOutOfBounds {
+                slice_index,
+                offset,
+                size,
+            } => write!(
+                f,
+                "slice {} at offset {} with size {} is out of bounds",
+                slice_index, offset, size
+            ),
+            ParseError::SliceImageOutOfRange {
+                slice_index,
+                image_index,
+            } => write!(
+                f,
+                "slice {} refers to image {}, which does not exist",
+                slice_index, image_index
+            ),
+            ParseError::HeaderChecksumMismatch { expected, actual } => write!(
+                f,
+                "header checksum mismatch (expected {:#06x}, got {:#06x})",
+                expected, actual
+            ),
+            ParseError::DataChecksumMismatch { expected, actual } => write!(
+                f,
+                "data checksum mismatch (expected {:#06x}, got {:#06x})",
+                expected, actual
+            ),
+            ParseError::SliceNotFound {
+                slice_index,
+                slice_count,
+            } => write!(
+                f,
+                "slice {} not found (file has {} slices)",
+                slice_index, slice_count
+            ),
+            ParseError::ImageNotFound {
+                image_index,
+                image_count,
+            } => write!(
+                f,
+                "image {} not found (file has {} images)",
+                image_index, image_count
+            ),
+            ParseError::ImageLevelNotFound {
+                image_index,
+                level_index,
+                level_count,
+            } => write!(
+                f,
+                "level {} not found on image {} (image has {} levels)",
+                level_index, image_index, level_count
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// The .basis file header
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BasisFileHeader {
+    pub version: u16,
+    pub header_size: u16,
+    pub header_crc16: u16,
+    /// Size of everything after the header
+    pub data_size: u32,
+    pub data_crc16: u16,
+    pub total_slices: u32,
+    pub total_images: u32,
+    pub texture_format: TextureFormat,
+    pub flags: HeaderFlags,
+    pub texture_type: TextureType,
+    /// Microseconds per frame for video textures
+    pub us_per_frame: u32,
+    pub userdata0: u32,
+    pub userdata1: u32,
+    pub total_endpoints: u32,
+    pub endpoint_codebook_offset: u32,
+    pub endpoint_codebook_size: u32,
+    pub total_selectors: u32,
+    pub selector_codebook_offset: u32,
+    pub selector_codebook_size: u32,
+    pub tables_offset: u32,
+    pub tables_size: u32,
+    pub slice_descriptions_offset: u32,
+    pub extended_offset: u32,
+    pub extended_size: u32,
+}
+
+/// Description of a single slice. Each image level is stored in one slice, or two for ETC1S files
+/// with alpha.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SliceDescription {
+    pub image_index: u32,
+    pub level_index: u32,
+    pub flags: SliceFlags,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub num_blocks_x: u32,
+    pub num_blocks_y: u32,
+    pub file_offset: u32,
+    pub file_size: u32,
+    /// crc16 of the slice data. For ETC1S this is the crc16 of the unpacked ETC1S blocks, so it
+    /// can't be checked without transcoding.
+    pub slice_data_crc16: u16,
+}
+
+impl SliceDescription {
+    /// Returns true if the slice holds alpha data
+    pub fn has_alpha(&self) -> bool {
+        self.flags.contains(SliceFlags::HAS_ALPHA)
+    }
+
+    /// Returns true if the slice is an I-frame of a video texture
+    pub fn is_iframe(&self) -> bool {
+        self.flags.contains(SliceFlags::FRAME_IS_IFRAME)
+    }
+
+    /// Total number of 4x4 blocks in the slice
+    pub fn block_count(&self) -> u32 {
+        self.num_blocks_x * self.num_blocks_y
+    }
+}
+
+/// The structure of a .basis file, read from a byte slice
+///
+/// `parse()` checks that the header is well formed and that the slice table and all slices lie
+/// within the data, so the accessors don't need to check again.
+#[derive(Debug, Copy, Clone)]
+pub struct BasisFileView<'a> {
+    data: &'a [u8],
+    header: BasisFileHeader,
+}
+
+impl<'a> BasisFileView<'a> {
+    /// Reads the header and checks that the slice descriptions and slices are within the data.
+    /// Checksums are not verified, see `validate_checksums()`.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let header = parse_header(data)?;
+        let view = BasisFileView { data, header };
+
+        for slice_index in 0..header.total_slices {
+            let slice = view.read_slice(slice_index);
+            if slice.image_index >= header.total_images {
+                return Err(ParseError::SliceImageOutOfRange {
+                    slice_index,
+                    image_index: slice.image_index,
+                });
+            }
+
+            if !range_in_bounds(data, slice.file_offset, slice.file_size) {
+                return Err(ParseError::SliceOutOfBounds {
+                    slice_index,
+                    offset: slice.file_offset,
+                    size: slice.file_size,
+                });
+            }
+        }
+
+        Ok(view)
+    }
+
+    /// The data that was parsed
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The file header
+    pub fn header(&self) -> &BasisFileHeader {
+        &self.header
+    }
+
+    /// The type of texture stored in the file
+    pub fn texture_type(&self) -> TextureType {
+        self.header.texture_type
+    }
+
+    /// The format of the slices stored in the file
+    pub fn texture_format(&self) -> TextureFormat {
+        self.header.texture_format
+    }
+
+    /// Arbitrary data that was attached to the file when it was encoded
+    pub fn user_data(&self) -> UserData {
+        UserData {
+            userdata0: self.header.userdata0,
+            userdata1: self.header.userdata1,
+        }
+    }
+
+    /// Number of images in the file
+    pub fn image_count(&self) -> u32 {
+        self.header.total_images
+    }
+
+    /// Number of slices in the file
+    pub fn slice_count(&self) -> u32 {
+        self.header.total_slices
+    }
+
+    /// Returns the description of the given slice
+    pub fn slice(
+        &self,
+        slice_index: u32,
+    ) -> Result<SliceDescription, ParseError> {
+        if slice_index >= self.header.total_slices {
+            return Err(ParseError::SliceNotFound {
+                slice_index,
+                slice_count: self.header.total_slices,
+            });
+        }
+
+        Ok(self.read_slice(slice_index))
+    }
+
+    /// Iterates over the descriptions of all slices, in file order
+    pub fn slices(&self) -> impl ExactSizeIterator<Item = SliceDescription> + '_ {
+        (0..self.header.total_slices).map(move |slice_index| self.read_slice(slice_index))
+    }
+
+    /// Returns the compressed data of the given slice
+    pub fn slice_data(
+        &self,
+        slice_index: u32,
+    ) -> Result<&'a [u8], ParseError> {
+        let slice = self.slice(slice_index)?;
+        let start = slice.file_offset as usize;
+        Ok(&self.data[start..start + slice.file_size as usize])
+    }
+
+    /// Finds the slice holding the color data (or alpha data if `alpha_data` is true) of the given
+    /// image level. UASTC files store color and alpha in the same slice.
+    pub fn find_slice(
+        &self,
+        image_index: u32,
+        level_index: u32,
+        alpha_data: bool,
+    ) -> Option<u32> {
+        let alpha_slices = self.header.texture_format == TextureFormat::ETC1S;
+        (0..self.header.total_slices).find(|&slice_index| {
+            let slice = self.read_slice(slice_index);
+            slice.image_index == image_index
+                && slice.level_index == level_index
+                && (!alpha_slices || slice.has_alpha() == alpha_data)
+        })
+    }
+
+    /// Number of mip levels of the given image
+    pub fn image_level_count(
+        &self,
+        image_index: u32,
+    ) -> Result<u32, ParseError> {
+        self.check_image(image_index)?;
+        Ok(self
+            .slices()
+            .filter(|slice| slice.image_index == image_index)
+            .map(|slice| slice.level_index + 1)
+            .max()
+            .unwrap_or(0))
+    }
+
+    /// Returns basic information about an image level. Note that original_width/original_height
+    /// may not be a multiple of 4.
+    pub fn image_level_description(
+        &self,
+        image_index: u32,
+        level_index: u32,
+    ) -> Result<ImageLevelDescription, ParseError> {
+        self.check_image(image_index)?;
+        let slice_index = self.find_slice(image_index, level_index, false).ok_or(
+            ParseError::ImageLevelNotFound {
+                image_index,
+                level_index,
+                level_count: self.image_level_count(image_index)?,
+            },
+        )?;
+        let slice = self.read_slice(slice_index);
+        Ok(ImageLevelDescription {
+            original_width: slice.original_width,
+            original_height: slice.original_height,
+            block_count: slice.block_count(),
+        })
+    }
+
+    /// Checks the header and data crc16 checksums
+    pub fn validate_checksums(&self) -> Result<(), ParseError> {
+        let actual = crc16(&self.data[8..HEADER_SIZE], 0);
+        if actual != self.header.header_crc16 {
+            return Err(ParseError::HeaderChecksumMismatch {
+                expected: self.header.header_crc16,
+                actual,
+            });
+        }
+
+        let data = &self.data[HEADER_SIZE..HEADER_SIZE + self.header.data_size as usize];
+        let actual = crc16(data, 0);
+        if actual != self.header.data_crc16 {
+            return Err(ParseError::DataChecksumMismatch {
+                expected: self.header.data_crc16,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_image(
+        &self,
+        image_index: u32,
+    ) -> Result<(), ParseError> {
+        if image_index >= self.header.total_images {
+            return Err(ParseError::ImageNotFound {
+                image_index,
+                image_count: self.header.total_images,
+            });
+        }
+
+        Ok(())
+    }
+
+    // The slice table was bounds checked by parse_header()
+    fn read_slice(
+        &self,
+        slice_index: u32,
+    ) -> SliceDescription {
+        let offset = self.header.slice_descriptions_offset as usize
+            + slice_index as usize * SLICE_DESCRIPTION_SIZE;
+        let desc = &self.data[offset..offset + SLICE_DESCRIPTION_SIZE];
+        SliceDescription {
+            image_index: read_u24(desc, 0),
+            level_index: desc[3] as u32,
+            flags: SliceFlags::from_bits_truncate(desc[4]),
+            original_width: read_u16(desc, 5) as u32,
+            original_height: read_u16(desc, 7) as u32,
+            num_blocks_x: read_u16(desc, 9) as u32,
+            num_blocks_y: read_u16(desc, 11) as u32,
+            file_offset: read_u32(desc, 13),
+            file_size: read_u32(desc, 17),
+            slice_data_crc16: read_u16(desc, 21),
+        }
+    }
+}
+
+/// Reads and checks the .basis file header. This does not verify the checksums.
+pub fn parse_header(data: &[u8]) -> Result<BasisFileHeader, ParseError> {
+    if data.len() < HEADER_SIZE {
+        return Err(ParseError::TooSmall {
+            required_bytes: HEADER_SIZE,
+            provided_bytes: data.len(),
+        });
+    }
+
+    let signature = read_u16(data, 0);
+    if signature != SIGNATURE {
+        return Err(ParseError::InvalidSignature(signature));
+    }
+
+    let version = read_u16(data, 2);
+    if version != VERSION {
+        return Err(ParseError::UnsupportedVersion(version));
+    }
+
+    let header_size = read_u16(data, 4);
+    if header_size as usize != HEADER_SIZE {
+        return Err(ParseError::InvalidHeaderSize(header_size));
+    }
+
+    let data_size = read_u32(data, 8);
+    let required_bytes = HEADER_SIZE as u64 + data_size as u64;
+    if (data.len() as u64) < required_bytes {
+        return Err(ParseError::TooSmall {
+            required_bytes: required_bytes as usize,
+            provided_bytes: data.len(),
+        });
+    }
+
+    let texture_format = match data[20] {
+        0 => TextureFormat::ETC1S,
+        1 => TextureFormat::UASTC4x4,
+        format => return Err(ParseError::InvalidTextureFormat(format)),
+    };
+
+    let flags = HeaderFlags::from_bits_truncate(read_u16(data, 21));
+    if flags.contains(HeaderFlags::ETC1S) != (texture_format == TextureFormat::ETC1S) {
+        return Err(ParseError::FormatFlagMismatch);
+    }
+
+    let texture_type = match data[23] {
+        0 => TextureType::TextureType2D,
+        1 => TextureType::TextureType2DArray,
+        2 => TextureType::TextureTypeCubemapArray,
+        3 => TextureType::TextureTypeVideoFrames,
+        4 => TextureType::TextureTypeVolume,
+        texture_type => return Err(ParseError::InvalidTextureType(texture_type)),
+    };
+
+    let total_slices = read_u24(data, 14);
+    let total_images = read_u24(data, 17);
+    if total_slices == 0 || total_images == 0 || total_images > total_slices {
+        return Err(ParseError::InvalidImageCount {
+            image_count: total_images,
+            slice_count: total_slices,
+        });
+    }
+
+    let slice_descriptions_offset = read_u32(data, 65);
+    if (slice_descriptions_offset as usize) < HEADER_SIZE
+        || !range_in_bounds(
+            data,
+            slice_descriptions_offset,
+            total_slices * SLICE_DESCRIPTION_SIZE as u32,
+        )
+    {
+        return Err(ParseError::SliceTableOutOfBounds {
+            offset: slice_descriptions_offset,
+            slice_count: total_slices,
+        });
+    }
+
+    Ok(BasisFileHeader {
+        version,
+        header_size,
+        header_crc16: read_u16(data, 6),
+        data_size,
+        data_crc16: read_u16(data, 12),
+        total_slices,
+        total_images,
+        texture_format,
+        flags,
+        texture_type,
+        us_per_frame: read_u24(data, 24),
+        userdata0: read_u32(data, 31),
+        userdata1: read_u32(data, 35),
+        total_endpoints: read_u16(data, 39) as u32,
+        endpoint_codebook_offset: read_u32(data, 41),
+        endpoint_codebook_size: read_u24(data, 45),
+        total_selectors: read_u16(data, 48) as u32,
+        selector_codebook_offset: read_u32(data, 50),
+        selector_codebook_size: read_u24(data, 54),
+        tables_offset: read_u32(data, 57),
+        tables_size: read_u32(data, 61),
+        slice_descriptions_offset,
+        extended_offset: read_u32(data, 69),
+        extended_size: read_u32(data, 73),
+    })
+}
+
+/// The crc16 used by .basis files
+pub fn crc16(
+    data: &[u8],
+    crc: u16,
+) -> u16 {
+    let mut crc = !crc;
+    for &byte in data {
+        let mut x = (crc >> 8) ^ byte as u16;
+        x ^= x >> 4;
+        crc = (crc << 8) ^ (x << 12) ^ (x << 5) ^ x;
+    }
+    !crc
+}
+
+fn range_in_bounds(
+    data: &[u8],
+    offset: u32,
+    size: u32,
+) -> bool {
+    offset as u64 + size as u64 <= data.len() as u64
+}
+
+//
+// Little-endian readers. Callers check that the bytes are in bounds.
+//
+fn read_u16(
+    data: &[u8],
+    offset: usize,
+) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u24(
+    data: &[u8],
+    offset: usize,
+) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], 0])
+}
+
+fn read_u32(
+    data: &[u8],
+    offset: usize,
+) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
@@ -0,0 +1,195 @@
+use super::*;
+
+//
+// Mappings from transcoder formats to the texture formats of graphics APIs. The values are the raw
+// enum values from each API's headers so that no API bindings are needed.
+//
+// Formats that have no sRGB variant (single/two channel formats and the packed 16-bit formats)
+// return the same value whether or not sRGB is requested.
+//
+impl TranscoderTextureFormat {
+    /// The `VkFormat` for this format, or None if Vulkan can't sample it
+    pub fn vk_format(
+        self,
+        srgb: bool,
+    ) -> Option<u32> {
+        use TranscoderTextureFormat::*;
+        let (unorm, srgb_format) = match self {
+            // ETC2 is a superset of ETC1
+            ETC1_RGB => (147, Some(148)),
+            ETC2_RGBA => (151, Some(152)),
+            BC1_RGB => (131, Some(132)),
+            BC3_RGBA => (137, Some(138)),
+            BC4_R => (139, None),
+            BC5_RG => (141, None),
+            BC7_RGBA => (145, Some(146)),
+            // VK_IMG_format_pvrtc
+            PVRTC1_4_RGB | PVRTC1_4_RGBA => (1000054001, Some(1000054005)),
+            PVRTC2_4_RGB | PVRTC2_4_RGBA => (1000054003, Some(1000054007)),
+            ASTC_4x4_RGBA => (157, Some(158)),
+            ETC2_EAC_R11 => (153, None),
+            ETC2_EAC_RG11 => (155, None),
+            RGBA32 => (37, Some(43)),
+            RGB565 => (4, None),
+            BGR565 => (5, None),
+            RGBA4444 => (2, None),
+            ATC_RGB | ATC_RGBA | FXT1_RGB => return None,
+        };
+
+        Some(select_srgb(srgb, unorm, srgb_format))
+    }
+
+    /// The `DXGI_FORMAT` for this format, or None if Direct3D can't sample it
+    pub fn dxgi_format(
+        self,
+        srgb: bool,
+    ) -> Option<u32> {
+        use TranscoderTextureFormat::*;
+        let (unorm, srgb_format) = match self {
+            BC1_RGB => (71, Some(72)),
+            BC3_RGBA => (77, Some(78)),
+            BC4_R => (80, None),
+            BC5_RG => (83, None),
+            BC7_RGBA => (98, Some(99)),
+            RGBA32 => (28, Some(29)),
+            // DXGI names packed formats from the least significant bit
+            RGB565 => (85, None),
+            RGBA4444 => (191, None),
+            ETC1_RGB | ETC2_RGBA | PVRTC1_4_RGB | PVRTC1_4_RGBA | ASTC_4x4_RGBA | ATC_RGB
+            | ATC_RGBA | FXT1_RGB | PVRTC2_4_RGB | PVRTC2_4_RGBA | ETC2_EAC_R11 | ETC2_EAC_RG11
+            | BGR565 => return None,
+        };
+
+        Some(select_srgb(srgb, unorm, srgb_format))
+    }
+
+    /// The OpenGL/OpenGL ES internal format for this format, or None if there is none. RGB565 and
+    /// RGBA4444 must be uploaded with the `GL_UNSIGNED_SHORT_5_6_5` and `GL_UNSIGNED_SHORT_4_4_4_4`
+    /// pixel types.
+    pub fn gl_internal_format(
+        self,
+        srgb: bool,
+    ) -> Option<u32> {
+        use TranscoderTextureFormat::*;
+        let (unorm, srgb_format) = match self {
+            // There is no sRGB ETC1 format, but ETC2 is a superset of ETC1
+            ETC1_RGB => (0x8D64, Some(0x9275)),
+            ETC2_RGBA => (0x9278, Some(0x9279)),
+            BC1_RGB => (0x83F0, Some(0x8C4C)),
+            BC3_RGBA => (0x83F3, Some(0x8C4F)),
+            BC4_R => (0x8DBB, None),
+            BC5_RG => (0x8DBD, None),
+            BC7_RGBA => (0x8E8C, Some(0x8E8D)),
+            PVRTC1_4_RGB => (0x8C00, Some(0x8A55)),
+            PVRTC1_4_RGBA => (0x8C02, Some(0x8A57)),
+            ASTC_4x4_RGBA => (0x93B0, Some(0x93D0)),
+            ATC_RGB => (0x8C92, None),
+            ATC_RGBA => (0x87EE, None),
+            FXT1_RGB => (0x86B0, None),
+            PVRTC2_4_RGB | PVRTC2_4_RGBA => (0x9138, Some(0x93F1)),
+            ETC2_EAC_R11 => (0x9270, None),
+            ETC2_EAC_RG11 => (0x9272, None),
+            RGBA32 => (0x8058, Some(0x8C43)),
+            RGB565 => (0x8D62, None),
+            RGBA4444 => (0x8056, None),
+            // Would need GL_UNSIGNED_SHORT_5_6_5_REV, which OpenGL ES doesn't have
+            BGR565 => return None,
+        };
+
+        Some(select_srgb(srgb, unorm, srgb_format))
+    }
+
+    /// The `MTLPixelFormat` for this format, or None if Metal can't sample it
+    pub fn mtl_pixel_format(
+        self,
+        srgb: bool,
+    ) -> Option<u32> {
+        use TranscoderTextureFormat::*;
+        let (unorm, srgb_format) = match self {
+            ETC1_RGB => (180, Some(181)),
+            ETC2_RGBA => (178, Some(179)),
+            BC1_RGB => (130, Some(131)),
+            BC3_RGBA => (134, Some(135)),
+            BC4_R => (140, None),
+            BC5_RG => (142, None),
+            BC7_RGBA => (152, Some(153)),
+            PVRTC1_4_RGB => (162, Some(163)),
+            PVRTC1_4_RGBA => (166, Some(167)),
+            ASTC_4x4_RGBA => (204, Some(186)),
+            ETC2_EAC_R11 => (170, None),
+            ETC2_EAC_RG11 => (174, None),
+            RGBA32 => (70, Some(71)),
+            // Metal names packed formats from the least significant bit
+            RGB565 => (40, None),
+            RGBA4444 => (42, None),
+            ATC_RGB | ATC_RGBA | FXT1_RGB | PVRTC2_4_RGB | PVRTC2_4_RGBA | BGR565 => return None,
+        };
+
+        Some(select_srgb(srgb, unorm, srgb_format))
+    }
+
+    /// The `wgpu::TextureFormat` for this format, or None if wgpu can't sample it
+    #[cfg(feature = "wgpu")]
+    pub fn wgpu_texture_format(
+        self,
+        srgb: bool,
+    ) -> Option<wgpu_types::TextureFormat> {
+        use wgpu_types::{AstcBlock, AstcChannel, TextureFormat};
+        use TranscoderTextureFormat::*;
+        let (unorm, srgb_format) = match self {
+            ETC1_RGB => (
+                TextureFormat::Etc2Rgb8Unorm,
+                Some(TextureFormat::Etc2Rgb8UnormSrgb),
+            ),
+            ETC2_RGBA => (
+                TextureFormat::Etc2Rgba8Unorm,
+                Some(TextureFormat::Etc2Rgba8UnormSrgb),
+            ),
+            BC1_RGB => (
+                TextureFormat::Bc1RgbaUnorm,
+                Some(TextureFormat::Bc1RgbaUnormSrgb),
+            ),
+            BC3_RGBA => (
+                TextureFormat::Bc3RgbaUnorm,
+                Some(TextureFormat::Bc3RgbaUnormSrgb),
+            ),
+            BC4_R => (TextureFormat::Bc4RUnorm, None),
+            BC5_RG => (TextureFormat::Bc5RgUnorm, None),
+            BC7_RGBA => (
+                TextureFormat::Bc7RgbaUnorm,
+                Some(TextureFormat::Bc7RgbaUnormSrgb),
+            ),
+            ASTC_4x4_RGBA => (
+                TextureFormat::Astc {
+                    block: AstcBlock::B4x4,
+                    channel: AstcChannel::Unorm,
+                },
+                Some(TextureFormat::Astc {
+                    block: AstcBlock::B4x4,
+                    channel: AstcChannel::UnormSrgb,
+                }),
+            ),
+            ETC2_EAC_R11 => (TextureFormat::EacR11Unorm, None),
+            ETC2_EAC_RG11 => (TextureFormat::EacRg11Unorm, None),
+            RGBA32 => (
+                TextureFormat::Rgba8Unorm,
+                Some(TextureFormat::Rgba8UnormSrgb),
+            ),
+            PVRTC1_4_RGB | PVRTC1_4_RGBA | ATC_RGB | ATC_RGBA | FXT1_RGB | PVRTC2_4_RGB
+            | PVRTC2_4_RGBA | RGB565 | BGR565 | RGBA4444 => return None,
+        };
+
+        Some(select_srgb(srgb, unorm, srgb_format))
+    }
+}
+
+fn select_srgb<T>(
+    srgb: bool,
+    unorm: T,
+    srgb_format: Option<T>,
+) -> T {
+    match srgb_format {
+        Some(srgb_format) if srgb => srgb_format,
+        _ => unorm,
+    }
+}
@@ -0,0 +1,855 @@
+//! Reading and writing transcoded textures as .ktx (KTX 1) and .ktx2 files
+//!
+//! KTX 1 files are written for formats that have an OpenGL internal format (see
+//! [TranscoderTextureFormat::gl_internal_format]) and KTX2 files for formats that have a `VkFormat`
+//! (see [TranscoderTextureFormat::vk_format]). KTX2 files are written without supercompression, so
+//! the level data can be uploaded as-is.
+
+use super::{
+    BasisTextureType, TranscodeError, TranscodeParameters, Transcoder, TranscoderTextureFormat,
+};
+use std::io::Write;
+
+const KTX1_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const KTX1_ENDIANNESS: u32 = 0x04030201;
+const KTX1_HEADER_SIZE: usize = 64;
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+// OpenGL pixel formats and types for uncompressed data
+const GL_RED: u32 = 0x1903;
+const GL_RGB: u32 = 0x1907;
+const GL_RGBA: u32 = 0x1908;
+const GL_RG: u32 = 0x8227;
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+const GL_UNSIGNED_SHORT_4_4_4_4: u32 = 0x8033;
+const GL_UNSIGNED_SHORT_5_6_5: u32 = 0x8363;
+
+// Khronos data format descriptor color models
+const KHR_DF_MODEL_RGBSDA: u8 = 1;
+const KHR_DF_MODEL_BC1A: u8 = 128;
+const KHR_DF_MODEL_BC3: u8 = 130;
+const KHR_DF_MODEL_BC4: u8 = 131;
+const KHR_DF_MODEL_BC5: u8 = 132;
+const KHR_DF_MODEL_BC7: u8 = 134;
+const KHR_DF_MODEL_ETC1: u8 = 160;
+const KHR_DF_MODEL_ETC2: u8 = 161;
+const KHR_DF_MODEL_ASTC: u8 = 162;
+const KHR_DF_MODEL_PVRTC: u8 = 164;
+const KHR_DF_MODEL_PVRTC2: u8 = 165;
+
+// Khronos data format descriptor channels. The meaning of the ids depends on the color model, so
+// only the ones that are shared by every model used here are named.
+const KHR_DF_CHANNEL_RED: u8 = 0;
+const KHR_DF_CHANNEL_GREEN: u8 = 1;
+const KHR_DF_CHANNEL_BLUE: u8 = 2;
+const KHR_DF_CHANNEL_ETC2_COLOR: u8 = 2;
+const KHR_DF_CHANNEL_ALPHA: u8 = 15;
+const KHR_DF_SAMPLE_DATATYPE_LINEAR: u8 = 0x80;
+
+const KHR_DF_PRIMARIES_BT709: u8 = 1;
+const KHR_DF_TRANSFER_LINEAR: u8 = 1;
+const KHR_DF_TRANSFER_SRGB: u8 = 2;
+
+// Every format the transcoder can produce except BGR565, which no graphics API can sample directly
+const KTX_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::BC4_R,
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::ETC2_EAC_R11,
+    TranscoderTextureFormat::ETC2_EAC_RG11,
+    TranscoderTextureFormat::RGBA32,
+    TranscoderTextureFormat::RGB565,
+    TranscoderTextureFormat::RGBA4444,
+];
+
+/// The version of the KTX container
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum KtxVersion {
+    /// KTX 1.1 (.ktx), described with OpenGL formats
+    Ktx1,
+    /// KTX 2.0 (.ktx2), described with Vulkan formats
+    Ktx2,
+}
+
+/// Error result from reading or writing a KTX file
+#[derive(Debug)]
+pub enum KtxError {
+    /// The basis data could not be transcoded
+    Transcode(TranscodeError),
+    /// Writing the file failed
+    Io(std::io::Error),
+    /// The format can't be stored in this version of KTX
+    FormatNotSupported(TranscoderTextureFormat),
+    /// Volume textures can't be written because their mip levels don't shrink in depth
+    TextureTypeNotSupported(BasisTextureType),
+    /// Images of an array or cubemap must all have the same size and number of mip levels
+    ImagesDiffer { image_index: u32 },
+    /// Cubemaps need a multiple of 6 images
+    InvalidCubemap { image_count: u32 },
+    /// The file is not a KTX file that can be read
+    InvalidFile(&'static str),
+}
+
+impl std::fmt::Display for KtxError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            KtxError::Transcode(error) => write!(f, "failed to transcode: {}", error),
+            KtxError::Io(error) => write!(f, "failed to write KTX file: {}", error),
+            KtxError::FormatNotSupported(format) => {
+                write!(f, "{:?} can't be stored in this version of KTX", format)
+            }
+            KtxError::TextureTypeNotSupported(texture_type) => {
+                write!(f, "{:?} can't be stored in a KTX file", texture_type)
+            }
+            KtxError::ImagesDiffer { image_index } => write!(
+                f,
+                "image {} has a different size or level count than image 0",
+                image_index
+            ),
+            KtxError::InvalidCubemap { image_count } => write!(
+                f,
+                "cubemaps need a multiple of 6 images, but there are {}",
+                image_count
+            ),
+            KtxError::InvalidFile(reason) => write!(f, "invalid KTX file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for KtxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KtxError::Transcode(error) => Some(error),
+            KtxError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TranscodeError> for KtxError {
+    fn from(error: TranscodeError) -> Self {
+        KtxError::Transcode(error)
+    }
+}
+
+impl From<std::io::Error> for KtxError {
+    fn from(error: std::io::Error) -> Self {
+        KtxError::Io(error)
+    }
+}
+
+/// A texture loaded from a KTX or KTX2 file. The surface data borrows from the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KtxTexture<'a> {
+    pub version: KtxVersion,
+    /// Formats that share an OpenGL/Vulkan format (such as PVRTC1 RGB and RGBA) are read back as
+    /// the opaque variant
+    pub format: TranscoderTextureFormat,
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Number of array layers, or 0 if the texture is not an array
+    pub array_size: u32,
+    /// 6 for cubemaps, otherwise 1
+    pub face_count: u32,
+    // Indexed by level, then layer and face
+    levels: Vec<Vec<&'a [u8]>>,
+}
+
+impl<'a> KtxTexture<'a> {
+    /// Reads a KTX 1 or uncompressed KTX2 file
+    pub fn read(ktx: &'a [u8]) -> Result<Self, KtxError> {
+        if ktx.len() < 12 {
+            return Err(KtxError::InvalidFile("missing KTX identifier"));
+        }
+
+        if ktx[0..12] == KTX1_IDENTIFIER {
+            Self::read_ktx1(ktx)
+        } else if ktx[0..12] == KTX2_IDENTIFIER {
+            Self::read_ktx2(ktx)
+        } else {
+            Err(KtxError::InvalidFile("missing KTX identifier"))
+        }
+    }
+
+    /// Number of mip levels
+    pub fn level_count(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// Number of 2D surfaces of each mip level (array layers * faces)
+    pub fn surface_count(&self) -> u32 {
+        self.array_size.max(1) * self.face_count
+    }
+
+    /// The surfaces of the given mip level, ordered by array layer and then cubemap face
+    pub fn level(
+        &self,
+        level_index: u32,
+    ) -> Option<&[&'a [u8]]> {
+        self.levels
+            .get(level_index as usize)
+            .map(|level| &level[..])
+    }
+
+    /// The data of a single surface
+    pub fn surface(
+        &self,
+        level_index: u32,
+        layer_index: u32,
+        face_index: u32,
+    ) -> Option<&'a [u8]> {
+        if layer_index >= self.array_size.max(1) || face_index >= self.face_count {
+            return None;
+        }
+
+        self.level(level_index)
+            .map(|level| level[(layer_index * self.face_count + face_index) as usize])
+    }
+
+    fn read_ktx1(ktx: &'a [u8]) -> Result<Self, KtxError> {
+        let header = read_u32s(ktx, 12, 13).ok_or(KtxError::InvalidFile("header is truncated"))?;
+        if header[0] != KTX1_ENDIANNESS {
+            return Err(KtxError::InvalidFile(
+                "only little endian files are supported",
+            ));
+        }
+
+        let gl_internal_format = header[4];
+        let (format, srgb) = KTX_FORMATS
+            .iter()
+            .find_map(|&format| {
+                if format.gl_internal_format(false) == Some(gl_internal_format) {
+                    Some((format, false))
+                } else if format.gl_internal_format(true) == Some(gl_internal_format) {
+                    Some((format, true))
+                } else {
+                    None
+                }
+            })
+            .ok_or(KtxError::InvalidFile("unsupported glInternalFormat"))?;
+
+        let mut texture = KtxTexture {
+            version: KtxVersion::Ktx1,
+            format,
+            srgb,
+            width: header[6],
+            height: header[7],
+            array_size: header[9],
+            face_count: header[10],
+            levels: Vec::new(),
+        };
+        texture.check_dimensions(header[8], ktx.len())?;
+
+        // The level count may be 0 to ask the loader to generate mips
+        let level_count = header[11].max(1);
+        let mut offset = KTX1_HEADER_SIZE
+            .checked_add(header[12] as usize)
+            .ok_or(KtxError::InvalidFile("key/value data is truncated"))?;
+
+        // Non-array cubemaps store the size of one face and pad each face, everything else stores
+        // the size of the whole level
+        let surface_count = texture.surface_count() as usize;
+        let is_cubemap = texture.face_count == 6 && texture.array_size == 0;
+        for _ in 0..level_count {
+            let image_size = read_u32s(ktx, offset, 1)
+                .ok_or(KtxError::InvalidFile("level is truncated"))?[0]
+                as usize;
+            offset += 4;
+
+            let mut surfaces = Vec::with_capacity(surface_count);
+            if is_cubemap {
+                for _ in 0..6 {
+                    surfaces.push(read_bytes(ktx, offset, image_size)?);
+                    offset += align(image_size, 4);
+                }
+            } else {
+                let level = read_bytes(ktx, offset, image_size)?;
+                surfaces.extend(split_level(level, surface_count)?);
+                offset += align(image_size, 4);
+            }
+            texture.levels.push(surfaces);
+        }
+
+        Ok(texture)
+    }
+
+    fn read_ktx2(ktx: &'a [u8]) -> Result<Self, KtxError> {
+        let header = read_u32s(ktx, 12, 9).ok_or(KtxError::InvalidFile("header is truncated"))?;
+        if header[8] != 0 {
+            return Err(KtxError::InvalidFile(
+                "supercompressed files are not supported",
+            ));
+        }
+
+        let vk_format = header[0];
+        let (format, srgb) = KTX_FORMATS
+            .iter()
+            .find_map(|&format| {
+                if format.vk_format(false) == Some(vk_format) {
+                    Some((format, false))
+                } else if format.vk_format(true) == Some(vk_format) {
+                    Some((format, true))
+                } else {
+                    None
+                }
+            })
+            .ok_or(KtxError::InvalidFile("unsupported vkFormat"))?;
+
+        let mut texture = KtxTexture {
+            version: KtxVersion::Ktx2,
+            format,
+            srgb,
+            width: header[2],
+            height: header[3],
+            array_size: header[5],
+            face_count: header[6],
+            levels: Vec::new(),
+        };
+        texture.check_dimensions(header[4], ktx.len())?;
+
+        let level_count = header[7].max(1) as usize;
+        let surface_count = texture.surface_count() as usize;
+        for level_index in 0..level_count {
+            let entry = KTX2_HEADER_SIZE + level_index * KTX2_LEVEL_INDEX_ENTRY_SIZE;
+            let index = read_u32s(ktx, entry, 4)
+                .ok_or(KtxError::InvalidFile("level index is truncated"))?;
+            if index[1] != 0 || index[3] != 0 {
+                return Err(KtxError::InvalidFile("level is out of bounds"));
+            }
+
+            let level = read_bytes(ktx, index[0] as usize, index[2] as usize)?;
+            texture.levels.push(split_level(level, surface_count)?);
+        }
+
+        Ok(texture)
+    }
+
+    // Only 2D textures and cubemaps are supported. Every surface takes at least one byte, which
+    // bounds the layer count.
+    fn check_dimensions(
+        &self,
+        depth: u32,
+        file_size: usize,
+    ) -> Result<(), KtxError> {
+        if self.width == 0 || self.height == 0 || depth > 1 {
+            return Err(KtxError::InvalidFile("only 2D textures are supported"));
+        }
+
+        if self.face_count != 1 && self.face_count != 6 {
+            return Err(KtxError::InvalidFile("invalid face count"));
+        }
+
+        if self.array_size as usize > file_size {
+            return Err(KtxError::InvalidFile("invalid layer count"));
+        }
+
+        Ok(())
+    }
+}
+
+fn read_u32s(
+    data: &[u8],
+    offset: usize,
+    count: usize,
+) -> Option<Vec<u32>> {
+    let bytes = data.get(offset..offset.checked_add(count * 4)?)?;
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect(),
+    )
+}
+
+fn read_bytes(
+    data: &[u8],
+    offset: usize,
+    length: usize,
+) -> Result<&[u8], KtxError> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(KtxError::InvalidFile("level is truncated"))
+}
+
+// Splits the data of a level into its layers and faces
+fn split_level(
+    level: &[u8],
+    surface_count: usize,
+) -> Result<Vec<&[u8]>, KtxError> {
+    let surface_size = level.len() / surface_count;
+    if surface_size == 0 || surface_size * surface_count != level.len() {
+        return Err(KtxError::InvalidFile(
+            "level size doesn't match layer count",
+        ));
+    }
+
+    Ok(level.chunks_exact(surface_size).collect())
+}
+
+fn align(
+    value: usize,
+    alignment: usize,
+) -> usize {
+    value + (alignment - value % alignment) % alignment
+}
+
+fn lcm(
+    a: usize,
+    b: usize,
+) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let remainder = x % y;
+        x = y;
+        y = remainder;
+    }
+    a / x * b
+}
+
+// glFormat, glType and glBaseInternalFormat. glFormat and glType are 0 for compressed formats.
+fn gl_format_and_type(format: TranscoderTextureFormat) -> (u32, u32, u32) {
+    use TranscoderTextureFormat::*;
+    match format {
+        RGBA32 => (GL_RGBA, GL_UNSIGNED_BYTE, GL_RGBA),
+        RGB565 => (GL_RGB, GL_UNSIGNED_SHORT_5_6_5, GL_RGB),
+        RGBA4444 => (GL_RGBA, GL_UNSIGNED_SHORT_4_4_4_4, GL_RGBA),
+        BC4_R | ETC2_EAC_R11 => (0, 0, GL_RED),
+        BC5_RG | ETC2_EAC_RG11 => (0, 0, GL_RG),
+        format if format.has_alpha() => (0, 0, GL_RGBA),
+        _ => (0, 0, GL_RGB),
+    }
+}
+
+// The size of the data type that needs byte swapping on big endian machines: 2 for the packed
+// 16-bit formats, 1 for byte data (RGBA32 and the compressed formats)
+fn type_size(format: TranscoderTextureFormat) -> u32 {
+    match format {
+        TranscoderTextureFormat::RGB565 | TranscoderTextureFormat::RGBA4444 => 2,
+        _ => 1,
+    }
+}
+
+// A sample of the data format descriptor: bit offset, bit length, channel and upper value
+type DfdSample = (u16, u8, u8, u32);
+
+// The color model and samples that describe the format in a data format descriptor
+fn dfd_model_and_samples(format: TranscoderTextureFormat) -> (u8, Vec<DfdSample>) {
+    use TranscoderTextureFormat::*;
+    let (model, samples) = match format {
+        ETC1_RGB => (KHR_DF_MODEL_ETC1, vec![(0, 64, 0)]),
+        ETC2_RGBA => (
+            KHR_DF_MODEL_ETC2,
+            vec![
+                (0, 64, KHR_DF_CHANNEL_ALPHA),
+                (64, 64, KHR_DF_CHANNEL_ETC2_COLOR),
+            ],
+        ),
+        ETC2_EAC_R11 => (KHR_DF_MODEL_ETC2, vec![(0, 64, KHR_DF_CHANNEL_RED)]),
+        ETC2_EAC_RG11 => (
+            KHR_DF_MODEL_ETC2,
+            vec![(0, 64, KHR_DF_CHANNEL_RED), (64, 64, KHR_DF_CHANNEL_GREEN)],
+        ),
+        BC1_RGB => (KHR_DF_MODEL_BC1A, vec![(0, 64, 0)]),
+        BC3_RGBA => (
+            KHR_DF_MODEL_BC3,
+            vec![(0, 64, KHR_DF_CHANNEL_ALPHA), (64, 64, 0)],
+        ),
+        BC4_R => (KHR_DF_MODEL_BC4, vec![(0, 64, 0)]),
+        BC5_RG => (
+            KHR_DF_MODEL_BC5,
+            vec![(0, 64, KHR_DF_CHANNEL_RED), (64, 64, KHR_DF_CHANNEL_GREEN)],
+        ),
+        BC7_RGBA => (KHR_DF_MODEL_BC7, vec![(0, 128, 0)]),
+        ASTC_4x4_RGBA => (KHR_DF_MODEL_ASTC, vec![(0, 128, 0)]),
+        PVRTC1_4_RGB | PVRTC1_4_RGBA => (KHR_DF_MODEL_PVRTC, vec![(0, 64, 0)]),
+        PVRTC2_4_RGB | PVRTC2_4_RGBA => (KHR_DF_MODEL_PVRTC2, vec![(0, 64, 0)]),
+        RGBA32 => {
+            let samples = [
+                KHR_DF_CHANNEL_RED,
+                KHR_DF_CHANNEL_GREEN,
+                KHR_DF_CHANNEL_BLUE,
+                KHR_DF_CHANNEL_ALPHA,
+            ]
+            .iter()
+            .enumerate()
+            .map(|(i, &channel)| (i as u16 * 8, 8, channel, 0xFF))
+            .collect();
+            return (KHR_DF_MODEL_RGBSDA, samples);
+        }
+        // Packed formats are described from the least significant bit
+        RGB565 => {
+            return (
+                KHR_DF_MODEL_RGBSDA,
+                vec![
+                    (0, 5, KHR_DF_CHANNEL_BLUE, 31),
+                    (5, 6, KHR_DF_CHANNEL_GREEN, 63),
+                    (11, 5, KHR_DF_CHANNEL_RED, 31),
+                ],
+            )
+        }
+        RGBA4444 => {
+            return (
+                KHR_DF_MODEL_RGBSDA,
+                vec![
+                    (0, 4, KHR_DF_CHANNEL_ALPHA, 15),
+                    (4, 4, KHR_DF_CHANNEL_BLUE, 15),
+                    (8, 4, KHR_DF_CHANNEL_GREEN, 15),
+                    (12, 4, KHR_DF_CHANNEL_RED, 15),
+                ],
+            )
+        }
+        ATC_RGB | ATC_RGBA | FXT1_RGB | BGR565 => unreachable!("format has no VkFormat"),
+    };
+
+    let samples = samples
+        .into_iter()
+        .map(|(bit_offset, bit_length, channel)| (bit_offset, bit_length, channel, u32::MAX))
+        .collect();
+    (model, samples)
+}
+
+// Builds the data format descriptor (including its total size) for a KTX2 file
+fn data_format_descriptor(
+    format: TranscoderTextureFormat,
+    srgb: bool,
+) -> Vec<u8> {
+    let (model, samples) = dfd_model_and_samples(format);
+    let block_size = 24 + 16 * samples.len();
+
+    let mut dfd = Vec::with_capacity(4 + block_size);
+    dfd.extend_from_slice(&(4 + block_size as u32).to_le_bytes());
+    // vendorId and descriptorType are both 0 for the basic descriptor block
+    dfd.extend_from_slice(&0_u32.to_le_bytes());
+    dfd.extend_from_slice(&2_u16.to_le_bytes());
+    dfd.extend_from_slice(&(block_size as u16).to_le_bytes());
+    dfd.push(model);
+    dfd.push(KHR_DF_PRIMARIES_BT709);
+    dfd.push(if srgb {
+        KHR_DF_TRANSFER_SRGB
+    } else {
+        KHR_DF_TRANSFER_LINEAR
+    });
+    // Straight alpha
+    dfd.push(0);
+
+    // texelBlockDimension holds the block size minus one
+    if format.is_compressed() {
+        dfd.push(format.block_width() as u8 - 1);
+        dfd.push(format.block_height() as u8 - 1);
+        dfd.extend_from_slice(&[0, 0]);
+    } else {
+        dfd.extend_from_slice(&[0; 4]);
+    }
+
+    // Only the first plane is used
+    dfd.push(format.bytes_per_block_or_pixel() as u8);
+    dfd.extend_from_slice(&[0; 7]);
+
+    for (bit_offset, bit_length, channel, upper) in samples {
+        // Alpha is never sRGB encoded
+        let channel_type = if srgb && channel == KHR_DF_CHANNEL_ALPHA {
+            channel | KHR_DF_SAMPLE_DATATYPE_LINEAR
+        } else {
+            channel
+        };
+
+        dfd.extend_from_slice(&bit_offset.to_le_bytes());
+        dfd.push(bit_length - 1);
+        dfd.push(channel_type);
+        dfd.extend_from_slice(&[0; 4]);
+        dfd.extend_from_slice(&0_u32.to_le_bytes());
+        dfd.extend_from_slice(&upper.to_le_bytes());
+    }
+
+    dfd
+}
+
+// How the images of basis data map to KTX surfaces
+struct KtxLayout {
+    width: u32,
+    height: u32,
+    level_count: u32,
+    array_size: u32,
+    face_count: u32,
+}
+
+impl Transcoder {
+    /// Transcodes every image and mip level of the basis data to `transcode_format` and writes
+    /// them as a KTX or KTX2 file. `prepare_transcoding()` must have been called first.
+    ///
+    /// Cubemap files are written as cubemaps, other files with more than one image are written as
+    /// arrays. Every image must have the same size and number of mip levels. `srgb` is ignored
+    /// for formats that don't have an sRGB variant.
+    pub fn write_ktx<W: Write>(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        srgb: bool,
+        version: KtxVersion,
+        writer: &mut W,
+    ) -> Result<(), KtxError> {
+        let (linear_format, srgb_format) = match version {
+            KtxVersion::Ktx1 => (
+                transcode_format.gl_internal_format(false),
+                transcode_format.gl_internal_format(true),
+            ),
+            KtxVersion::Ktx2 => (
+                transcode_format.vk_format(false),
+                transcode_format.vk_format(true),
+            ),
+        };
+        let (linear_format, srgb_format) = match (linear_format, srgb_format) {
+            (Some(linear_format), Some(srgb_format)) if KTX_FORMATS.contains(&transcode_format) => {
+                (linear_format, srgb_format)
+            }
+            _ => return Err(KtxError::FormatNotSupported(transcode_format)),
+        };
+        let format_value = if srgb { srgb_format } else { linear_format };
+        let srgb = format_value != linear_format;
+
+        let layout = self.ktx_layout(data)?;
+
+        // Transcode every surface, ordered by level, then layer and face
+        let surface_count = layout.array_size.max(1) * layout.face_count;
+        let mut levels = Vec::with_capacity(layout.level_count as usize);
+        for level_index in 0..layout.level_count {
+            let mut surfaces = Vec::with_capacity(surface_count as usize);
+            for image_index in 0..surface_count {
+                surfaces.push(self.transcode_image_level(
+                    data,
+                    transcode_format,
+                    TranscodeParameters {
+                        image_index,
+                        level_index,
+                        ..Default::default()
+                    },
+                )?);
+            }
+            levels.push(surfaces);
+        }
+
+        match version {
+            KtxVersion::Ktx1 => {
+                write_ktx1(writer, transcode_format, format_value, &layout, &levels)
+            }
+            KtxVersion::Ktx2 => write_ktx2(
+                writer,
+                transcode_format,
+                format_value,
+                srgb,
+                &layout,
+                &levels,
+            ),
+        }
+    }
+
+    // Works out how the basis data maps to KTX surfaces
+    fn ktx_layout(
+        &self,
+        data: &[u8],
+    ) -> Result<KtxLayout, KtxError> {
+        let texture_type = self.basis_texture_type(data)?;
+        let image_count = self.image_count(data)?;
+        let face_count = match texture_type {
+            BasisTextureType::TextureTypeCubemapArray => {
+                if image_count % 6 != 0 {
+                    return Err(KtxError::InvalidCubemap { image_count });
+                }
+                6
+            }
+            BasisTextureType::TextureTypeVolume => {
+                return Err(KtxError::TextureTypeNotSupported(texture_type))
+            }
+            _ => 1,
+        };
+
+        // A single image or cube is not written as an array
+        let layer_count = image_count / face_count;
+        let first_level = self.image_level_description(data, 0, 0)?;
+        let layout = KtxLayout {
+            width: first_level.original_width,
+            height: first_level.original_height,
+            level_count: self.image_level_count(data, 0)?,
+            array_size: if layer_count > 1 { layer_count } else { 0 },
+            face_count,
+        };
+
+        for image_index in 0..image_count {
+            if self.image_level_count(data, image_index)? != layout.level_count {
+                return Err(KtxError::ImagesDiffer { image_index });
+            }
+
+            for level_index in 0..layout.level_count {
+                let description = self.image_level_description(data, image_index, level_index)?;
+                let expected = (
+                    (layout.width >> level_index).max(1),
+                    (layout.height >> level_index).max(1),
+                );
+                if (description.original_width, description.original_height) != expected {
+                    return Err(KtxError::ImagesDiffer { image_index });
+                }
+            }
+        }
+
+        Ok(layout)
+    }
+}
+
+fn write_ktx1<W: Write>(
+    writer: &mut W,
+    format: TranscoderTextureFormat,
+    gl_internal_format: u32,
+    layout: &KtxLayout,
+    levels: &[Vec<Vec<u8>>],
+) -> Result<(), KtxError> {
+    let (gl_format, gl_type, gl_base_internal_format) = gl_format_and_type(format);
+    let gl_type_size = type_size(format);
+
+    writer.write_all(&KTX1_IDENTIFIER)?;
+    for value in [
+        KTX1_ENDIANNESS,
+        gl_type,
+        gl_type_size,
+        gl_format,
+        gl_internal_format,
+        gl_base_internal_format,
+        layout.width,
+        layout.height,
+        0,
+        layout.array_size,
+        layout.face_count,
+        layout.level_count,
+        0,
+    ]
+    .iter()
+    {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+
+    // Non-array cubemaps store the size of one face and pad each face, everything else stores the
+    // size of the whole level
+    let is_cubemap = layout.face_count == 6 && layout.array_size == 0;
+    for surfaces in levels {
+        if is_cubemap {
+            writer.write_all(&(surfaces[0].len() as u32).to_le_bytes())?;
+            for surface in surfaces {
+                writer.write_all(surface)?;
+                write_padding(writer, surface.len(), 4)?;
+            }
+        } else {
+            let image_size: usize = surfaces.iter().map(|surface| surface.len()).sum();
+            writer.write_all(&(image_size as u32).to_le_bytes())?;
+            for surface in surfaces {
+                writer.write_all(surface)?;
+            }
+            write_padding(writer, image_size, 4)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_ktx2<W: Write>(
+    writer: &mut W,
+    format: TranscoderTextureFormat,
+    vk_format: u32,
+    srgb: bool,
+    layout: &KtxLayout,
+    levels: &[Vec<Vec<u8>>],
+) -> Result<(), KtxError> {
+    let type_size = type_size(format);
+    let dfd = data_format_descriptor(format, srgb);
+
+    // Levels are stored smallest first, each aligned to the block size and 4 bytes
+    let dfd_offset = KTX2_HEADER_SIZE + KTX2_LEVEL_INDEX_ENTRY_SIZE * levels.len();
+    let level_alignment = lcm(format.bytes_per_block_or_pixel() as usize, 4);
+    let level_sizes: Vec<usize> = levels
+        .iter()
+        .map(|surfaces| surfaces.iter().map(|surface| surface.len()).sum())
+        .collect();
+    let mut level_offsets = vec![0; levels.len()];
+    let mut offset = dfd_offset + dfd.len();
+    for level_index in (0..levels.len()).rev() {
+        offset = align(offset, level_alignment);
+        level_offsets[level_index] = offset;
+        offset += level_sizes[level_index];
+    }
+
+    writer.write_all(&KTX2_IDENTIFIER)?;
+    for value in [
+        vk_format,
+        type_size,
+        layout.width,
+        layout.height,
+        0,
+        layout.array_size,
+        layout.face_count,
+        layout.level_count,
+        // No supercompression
+        0,
+        dfd_offset as u32,
+        dfd.len() as u32,
+        // No key/value data
+        0,
+        0,
+    ]
+    .iter()
+    {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    // No supercompression global data
+    writer.write_all(&[0; 16])?;
+
+    for (level_offset, level_size) in level_offsets.iter().zip(level_sizes.iter()) {
+        for value in [*level_offset, *level_size, *level_size].iter() {
+            writer.write_all(&(*value as u64).to_le_bytes())?;
+        }
+    }
+
+    writer.write_all(&dfd)?;
+
+    let mut offset = dfd_offset + dfd.len();
+    for level_index in (0..levels.len()).rev() {
+        write_padding(writer, offset, level_alignment)?;
+        for surface in &levels[level_index] {
+            writer.write_all(surface)?;
+        }
+        offset = level_offsets[level_index] + level_sizes[level_index];
+    }
+
+    Ok(())
+}
+
+// Writes zeros to pad from `offset` to the next multiple of `alignment`
+fn write_padding<W: Write>(
+    writer: &mut W,
+    offset: usize,
+    alignment: usize,
+) -> Result<(), KtxError> {
+    let padding = align(offset, alignment) - offset;
+    writer.write_all(&vec![0; padding])?;
+    Ok(())
+}
@@ -8,6 +8,28 @@ pub use enums::*;
 mod transcoder;
 pub use transcoder::*;
 
+mod basis_file;
+pub use basis_file::*;
+
+mod target_format;
+pub use target_format::*;
+
+mod gpu_formats;
+
+pub mod file_format;
+
+pub mod dds;
+
+pub mod ktx;
+
+#[cfg(feature = "image")]
+mod rgba_image;
+
+#[cfg(feature = "rayon")]
+mod transcoded_texture;
+#[cfg(feature = "rayon")]
+pub use transcoded_texture::*;
+
 #[cfg(test)]
 mod transcoding_tests;
 
@@ -0,0 +1,96 @@
+use super::*;
+use image::RgbaImage;
+
+impl Transcoder {
+    /// Transcodes an image level to [TranscoderTextureFormat::RGBA32] and returns it as an
+    /// [image::RgbaImage]. `prepare_transcoding()` must have been called first.
+    pub fn decode_to_rgba_image(
+        &self,
+        data: &[u8],
+        image_index: u32,
+        level_index: u32,
+    ) -> Result<RgbaImage, TranscodeError> {
+        self.decode_to_rgba_image_with_format(
+            data,
+            image_index,
+            level_index,
+            TranscoderTextureFormat::RGBA32,
+        )
+    }
+
+    /// Same as decode_to_rgba_image(), but transcodes to the given uncompressed format first. The
+    /// 16-bit formats are expanded to 8 bits per channel, which shows how the image looks when
+    /// uploaded in that format.
+    pub fn decode_to_rgba_image_with_format(
+        &self,
+        data: &[u8],
+        image_index: u32,
+        level_index: u32,
+        transcode_format: TranscoderTextureFormat,
+    ) -> Result<RgbaImage, TranscodeError> {
+        if transcode_format.is_compressed() {
+            return Err(TranscodeError::UncompressedFormatRequired { transcode_format });
+        }
+
+        let description = self.image_level_description(data, image_index, level_index)?;
+        let transcoded = self.transcode_image_level(
+            data,
+            transcode_format,
+            TranscodeParameters {
+                image_index,
+                level_index,
+                ..Default::default()
+            },
+        )?;
+
+        let rgba = match transcode_format {
+            TranscoderTextureFormat::RGBA32 => transcoded,
+            _ => transcoded
+                .chunks_exact(2)
+                .flat_map(|pixel| {
+                    expand_16_bit_pixel(transcode_format, u16::from_le_bytes([pixel[0], pixel[1]]))
+                })
+                .collect(),
+        };
+
+        Ok(RgbaImage::from_raw(
+            description.original_width,
+            description.original_height,
+            rgba,
+        )
+        .expect("transcoded data does not match the image level size"))
+    }
+}
+
+// RGB565 has red in the high bits, BGR565 has it in the low bits, and RGBA4444 has red in the high
+// bits and alpha in the low bits
+fn expand_16_bit_pixel(
+    transcode_format: TranscoderTextureFormat,
+    pixel: u16,
+) -> [u8; 4] {
+    let expand_5 = |value: u16| ((value << 3) | (value >> 2)) as u8;
+    let expand_6 = |value: u16| ((value << 2) | (value >> 4)) as u8;
+    let expand_4 = |value: u16| (value * 17) as u8;
+
+    match transcode_format {
+        TranscoderTextureFormat::RGB565 => [
+            expand_5(pixel >> 11),
+            expand_6((pixel >> 5) & 0x3F),
+            expand_5(pixel & 0x1F),
+            255,
+        ],
+        TranscoderTextureFormat::BGR565 => [
+            expand_5(pixel & 0x1F),
+            expand_6((pixel >> 5) & 0x3F),
+            expand_5(pixel >> 11),
+            255,
+        ],
+        TranscoderTextureFormat::RGBA4444 => [
+            expand_4(pixel >> 12),
+            expand_4((pixel >> 8) & 0xF),
+            expand_4((pixel >> 4) & 0xF),
+            expand_4(pixel & 0xF),
+        ],
+        _ => unreachable!("{:?} is not a 16-bit format", transcode_format),
+    }
+}
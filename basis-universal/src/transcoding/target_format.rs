@@ -0,0 +1,147 @@
+use super::*;
+
+/// The compressed texture formats supported by a GPU/graphics API. Uncompressed formats are always
+/// assumed to be supported.
+///
+/// PVRTC1 requires power of two dimensions, so leave `pvrtc1` unset when choosing a format for
+/// textures that aren't a power of two.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct TargetCapabilities {
+    /// ETC1 (`GL_OES_compressed_ETC1_RGB8_texture`)
+    pub etc1: bool,
+    /// ETC2 RGB/RGBA and EAC R11/RG11. Implies ETC1 support
+    pub etc2: bool,
+    /// BC1-BC5 (S3TC/DXT and RGTC)
+    pub bc: bool,
+    /// BC7 (BPTC)
+    pub bc7: bool,
+    /// ASTC LDR 4x4
+    pub astc: bool,
+    /// PVRTC1 4bpp
+    pub pvrtc1: bool,
+    /// PVRTC2 4bpp
+    pub pvrtc2: bool,
+    /// ATI ATC
+    pub atc: bool,
+    /// 3DFX FXT1
+    pub fxt1: bool,
+}
+
+impl TargetCapabilities {
+    /// Returns true if textures of the given format can be used
+    pub fn supports(
+        &self,
+        transcode_format: TranscoderTextureFormat,
+    ) -> bool {
+        use TranscoderTextureFormat::*;
+        match transcode_format {
+            ETC1_RGB => self.etc1 || self.etc2,
+            ETC2_RGBA | ETC2_EAC_R11 | ETC2_EAC_RG11 => self.etc2,
+            BC1_RGB | BC3_RGBA | BC4_R | BC5_RG => self.bc,
+            BC7_RGBA => self.bc7,
+            ASTC_4x4_RGBA => self.astc,
+            PVRTC1_4_RGB | PVRTC1_4_RGBA => self.pvrtc1,
+            PVRTC2_4_RGB | PVRTC2_4_RGBA => self.pvrtc2,
+            ATC_RGB | ATC_RGBA => self.atc,
+            FXT1_RGB => self.fxt1,
+            RGBA32 | RGB565 | BGR565 | RGBA4444 => true,
+        }
+    }
+}
+
+//
+// Preferred formats, best first. These follow the recommendations in the upstream documentation:
+// ETC1S transcodes fastest to ETC1/ETC2 and BC7, UASTC transcodes almost losslessly to ASTC and
+// BC7, and PVRTC is the lowest quality choice. RGBA32 is always available as a last resort.
+//
+const ETC1S_OPAQUE_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::RGBA32,
+];
+
+const ETC1S_ALPHA_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::RGBA32,
+];
+
+const UASTC_OPAQUE_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::RGBA32,
+];
+
+const UASTC_ALPHA_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::RGBA32,
+];
+
+// Normal maps are encoded with X in the color channels and Y in alpha. The two channel formats
+// store them separately, which gives the best quality.
+const NORMAL_MAP_FORMATS: &[TranscoderTextureFormat] = &[
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::ETC2_EAC_RG11,
+];
+
+/// Chooses the best format to transcode basis data into for a GPU with the given capabilities.
+///
+/// `has_alpha` should be true if the file has alpha (see [ImageInfo]). Normal maps are expected to
+/// store X in the color channels and Y in alpha (`-normal_map -separate_rg_to_color_alpha` in the
+/// upstream command line tool), so they always need a format that keeps the alpha channel. This
+/// falls back to [TranscoderTextureFormat::RGBA32] if none of the supported compressed formats can
+/// be used.
+pub fn choose_transcode_format(
+    capabilities: &TargetCapabilities,
+    basis_format: BasisTextureFormat,
+    has_alpha: bool,
+    is_normal_map: bool,
+) -> TranscoderTextureFormat {
+    let alpha_formats = match basis_format {
+        BasisTextureFormat::ETC1S => ETC1S_ALPHA_FORMATS,
+        BasisTextureFormat::UASTC4x4 => UASTC_ALPHA_FORMATS,
+    };
+
+    let candidates: &[&[TranscoderTextureFormat]] = if is_normal_map {
+        &[NORMAL_MAP_FORMATS, alpha_formats]
+    } else if has_alpha {
+        &[alpha_formats]
+    } else {
+        match basis_format {
+            BasisTextureFormat::ETC1S => &[ETC1S_OPAQUE_FORMATS],
+            BasisTextureFormat::UASTC4x4 => &[UASTC_OPAQUE_FORMATS],
+        }
+    };
+
+    candidates
+        .iter()
+        .flat_map(|formats| formats.iter())
+        .copied()
+        .find(|&format| {
+            capabilities.supports(format) && basis_format.can_transcode_to_format(format)
+        })
+        .unwrap_or(TranscoderTextureFormat::RGBA32)
+}
@@ -0,0 +1,156 @@
+use super::*;
+use rayon::prelude::*;
+use std::ops::Range;
+
+/// Description of a single transcoded mip level within a [TranscodedTexture]
+#[derive(Debug, Clone)]
+pub struct TranscodedLevel {
+    pub original_width: u32,
+    pub original_height: u32,
+    pub block_count: u32,
+    /// The location of this level's data within [TranscodedTexture::data]
+    pub byte_range: Range<usize>,
+}
+
+/// Every image and mip level of basis data, transcoded to a single format
+#[derive(Debug, Clone)]
+pub struct TranscodedTexture {
+    /// The format all levels were transcoded to
+    pub format: TranscoderTextureFormat,
+    /// Transcoded data for all images and levels, stored image by image, level by level
+    pub data: Vec<u8>,
+    /// Indexed by image, then by level
+    pub images: Vec<Vec<TranscodedLevel>>,
+}
+
+impl TranscodedTexture {
+    /// Number of images in the texture
+    pub fn image_count(&self) -> u32 {
+        self.images.len() as u32
+    }
+
+    /// Number of mip levels for the given image, or 0 if the image does not exist
+    pub fn level_count(
+        &self,
+        image_index: u32,
+    ) -> u32 {
+        self.images
+            .get(image_index as usize)
+            .map(|levels| levels.len() as u32)
+            .unwrap_or(0)
+    }
+
+    /// Description of the given image level
+    pub fn level(
+        &self,
+        image_index: u32,
+        level_index: u32,
+    ) -> Option<&TranscodedLevel> {
+        self.images
+            .get(image_index as usize)?
+            .get(level_index as usize)
+    }
+
+    /// Transcoded data for the given image level
+    pub fn level_data(
+        &self,
+        image_index: u32,
+        level_index: u32,
+    ) -> Option<&[u8]> {
+        let level = self.level(image_index, level_index)?;
+        Some(&self.data[level.byte_range.clone()])
+    }
+}
+
+impl Transcoder {
+    /// Transcodes every level of every image in the basis data to `transcode_format`, in parallel
+    /// on the rayon thread pool. prepare_transcoding() must be called first.
+    pub fn transcode_all(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+    ) -> Result<TranscodedTexture, TranscodeError> {
+        if !self.is_prepared_to_transcode() {
+            return Err(TranscodeError::NotPrepared);
+        }
+
+        self.validate_header(data)?;
+
+        //
+        // Lay out all levels in a single buffer
+        //
+        let mut images = Vec::default();
+        let mut total_bytes = 0;
+        for image_index in 0..self.image_count(data)? {
+            let mut levels = Vec::default();
+            for level_index in 0..self.image_level_count(data, image_index)? {
+                let transcode_parameters = TranscodeParameters {
+                    image_index,
+                    level_index,
+                    ..Default::default()
+                };
+                let description = self.image_level_description(data, image_index, level_index)?;
+                let level_bytes =
+                    self.required_output_size(data, transcode_format, &transcode_parameters)?;
+                levels.push(TranscodedLevel {
+                    original_width: description.original_width,
+                    original_height: description.original_height,
+                    block_count: description.block_count,
+                    byte_range: total_bytes..total_bytes + level_bytes,
+                });
+                total_bytes += level_bytes;
+            }
+            images.push(levels);
+        }
+
+        //
+        // Split the buffer so that each level can be written by a different thread
+        //
+        let mut output = vec![0_u8; total_bytes];
+        let mut jobs = Vec::default();
+        let mut remaining = &mut output[..];
+        for (image_index, levels) in images.iter().enumerate() {
+            for (level_index, level) in levels.iter().enumerate() {
+                let (level_output, rest) = remaining.split_at_mut(level.byte_range.len());
+                remaining = rest;
+                jobs.push((image_index as u32, level_index as u32, level_output));
+            }
+        }
+
+        //
+        // Transcode
+        //
+        let transcode_level =
+            |transcoder_state: &mut TranscoderState,
+             (image_index, level_index, level_output): (u32, u32, &mut [u8])| {
+                self.transcode_image_level_into_with_state(
+                    data,
+                    transcode_format,
+                    TranscodeParameters {
+                        image_index,
+                        level_index,
+                        ..Default::default()
+                    },
+                    transcoder_state,
+                    level_output,
+                )
+            };
+
+        if self.basis_texture_type(data)? == BasisTextureType::TextureTypeVideoFrames {
+            // Video frames may be predicted from the previous frame, so they have to be transcoded
+            // in order with a single state
+            let mut transcoder_state = TranscoderState::new();
+            jobs.into_iter()
+                .try_for_each(|job| transcode_level(&mut transcoder_state, job))?;
+        } else {
+            jobs.into_par_iter()
+                .try_for_each_init(TranscoderState::new, transcode_level)?;
+        }
+
+        Ok(TranscodedTexture {
+            format: transcode_format,
+            data: output,
+            images,
+        })
+    }
+}
@@ -1,10 +1,57 @@
 use super::*;
 use crate::UserData;
 use basis_universal_sys as sys;
+use std::convert::TryFrom;
+use std::sync::Mutex;
 
 /// A transcoder that can convert compressed basis-universal data to compressed GPU formats or raw
 /// color data
-pub struct Transcoder(*mut sys::Transcoder);
+///
+/// Once prepared, a transcoder can be shared between threads. Transcoding without a
+/// [TranscoderState] uses state stored inside the transcoder, so those calls are serialized. To
+/// transcode in parallel, give each thread its own [TranscoderState] and use the `_with_state`
+/// variants of the transcode functions.
+pub struct Transcoder {
+    transcoder: *mut sys::Transcoder,
+    // Guards the transcoder's internal state, which is used when no TranscoderState is provided
+    internal_state_lock: Mutex<()>,
+}
+
+// The underlying transcoder is not tied to a thread. Functions that take &self only read from it,
+// except for transcoding without a TranscoderState, which is guarded by internal_state_lock.
+unsafe impl Send for Transcoder {}
+unsafe impl Sync for Transcoder {}
+
+/// Scratch state used while transcoding. Reusing a state avoids reallocating it on every call,
+/// and giving each thread its own state allows one [Transcoder] to be used from several threads at
+/// once.
+///
+/// For video files (see [BasisTextureType::TextureTypeVideoFrames]), the state also stores the
+/// previous frame, so frames must be transcoded in order with the same state.
+pub struct TranscoderState(*mut sys::basist_basisu_transcoder_state);
+
+unsafe impl Send for TranscoderState {}
+
+impl Default for TranscoderState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscoderState {
+    /// Create an empty transcoder state
+    pub fn new() -> TranscoderState {
+        unsafe { TranscoderState(sys::transcoder_state_new()) }
+    }
+}
+
+impl Drop for TranscoderState {
+    fn drop(&mut self) {
+        unsafe {
+            sys::transcoder_state_delete(self.0);
+        }
+    }
+}
 
 /// Lightweight description of a mip level on a single image within basis data
 #[derive(Default, Debug, Copy, Clone)]
@@ -20,8 +67,23 @@ pub type ImageInfo = sys::basist_basisu_image_info;
 /// Info for a mip level of a single image within basis data
 pub type ImageLevelInfo = sys::basist_basisu_image_level_info;
 
+/// Summary of the complete basis file, as stored in the header
+pub type FileSummary = sys::FileInfo;
+
+/// Low-level info for a single slice within basis data. Files with alpha have two slices (color
+/// and alpha) per image level.
+pub type SliceInfo = sys::SliceInfo;
+
 /// Info for the complete basis file
-pub type FileInfo = sys::FileInfo;
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    /// Values read from the file header
+    pub summary: FileSummary,
+    /// Info for every slice in the file, in file order
+    pub slice_info: Vec<SliceInfo>,
+    /// Number of mipmap levels for each image
+    pub image_mipmap_levels: Vec<u32>,
+}
 
 /// Extra parameters for transcoding an image
 #[derive(Default, Debug, Clone)]
@@ -38,14 +100,319 @@ pub struct TranscodeParameters {
     pub output_rows_in_pixels: Option<u32>,
 }
 
-/// Error result from trying to transcode an image
+/// Extra parameters for transcoding a single slice with [Transcoder::transcode_slice]
+#[derive(Default, Debug, Clone)]
+pub struct SliceTranscodeParameters {
+    /// The slice to transcode. See [Transcoder::find_slice] and [FileInfo::slice_info]
+    pub slice_index: u32,
+    /// Optional flags can affect transcoding in various ways
+    pub decode_flags: Option<DecodeFlags>,
+    /// Optional override for the number of bytes between the start of each output block. Defaults
+    /// to the size of one block. A larger stride lets several slices be interleaved in one buffer,
+    /// e.g. BC4 alpha and BC1 color blocks making up BC3 blocks. Must be the pixel size for
+    /// uncompressed formats.
+    pub output_block_stride_in_bytes: Option<u32>,
+    /// Optional override for row pitch. Ignored for PVRTC1.
+    pub output_row_pitch_in_blocks_or_pixels: Option<u32>,
+    /// Optional override for number of rows to transcode. Only used for uncompressed formats.
+    pub output_rows_in_pixels: Option<u32>,
+    /// Optional source channel (0-3) for single channel formats like BC4 and ETC2_EAC_R11, or the
+    /// first channel of BC5 and ETC2_EAC_RG11. Only used for UASTC data.
+    pub channel0: Option<u32>,
+    /// Optional source channel (0-3) for the second channel of BC5 and ETC2_EAC_RG11. Only used for
+    /// UASTC data.
+    pub channel1: Option<u32>,
+}
+
+// How a slice is laid out in an output buffer
 #[derive(Debug, Copy, Clone)]
+pub(crate) struct SliceOutputLayout {
+    pub(crate) row_pitch_in_blocks_or_pixels: u32,
+    pub(crate) rows_in_pixels: u32,
+    pub(crate) block_stride_in_bytes: u32,
+    // Number of blocks or pixels the transcoder may write to
+    pub(crate) blocks_or_pixels: u32,
+    pub(crate) required_bytes: usize,
+}
+
+impl SliceOutputLayout {
+    // Works out the output layout of a slice, checking that the overrides won't make the
+    // transcoder write outside of the buffer
+    pub(crate) fn new(
+        block_format: TranscoderBlockFormat,
+        (original_width, original_height): (u32, u32),
+        (num_blocks_x, num_blocks_y): (u32, u32),
+        output_block_stride_in_bytes: Option<u32>,
+        output_row_pitch_in_blocks_or_pixels: Option<u32>,
+        output_rows_in_pixels: Option<u32>,
+    ) -> Result<Self, TranscodeError> {
+        let bytes_per_block_or_pixel = block_format.bytes_per_block_or_pixel();
+        let block_stride_in_bytes =
+            output_block_stride_in_bytes.unwrap_or(bytes_per_block_or_pixel);
+        let stride_is_valid = if block_format.is_compressed() {
+            block_stride_in_bytes >= bytes_per_block_or_pixel
+        } else {
+            block_stride_in_bytes == bytes_per_block_or_pixel
+        };
+        if !stride_is_valid {
+            return Err(TranscodeError::InvalidBlockStride {
+                block_format,
+                block_stride_in_bytes,
+            });
+        }
+
+        // Width and height of the slice in output blocks (or pixels for uncompressed formats)
+        let (width, height) = if !block_format.is_compressed() {
+            (
+                original_width,
+                output_rows_in_pixels.unwrap_or(original_height),
+            )
+        } else if block_format == TranscoderBlockFormat::FXT1_RGB {
+            // FXT1 blocks are 8 pixels wide. Rounded up without adding to the width, which could
+            // overflow
+            (
+                original_width / 8 + u32::from(original_width % 8 != 0),
+                num_blocks_y,
+            )
+        } else {
+            (num_blocks_x, num_blocks_y)
+        };
+
+        let row_pitch_in_blocks_or_pixels = match block_format {
+            // Row pitch is ignored for PVRTC1 due to texture swizzling
+            TranscoderBlockFormat::PVRTC1_4_RGB | TranscoderBlockFormat::PVRTC1_4_RGBA => width,
+            _ => output_row_pitch_in_blocks_or_pixels.unwrap_or(width),
+        };
+        if row_pitch_in_blocks_or_pixels < width {
+            return Err(TranscodeError::InvalidRowPitch {
+                row_pitch_in_blocks_or_pixels,
+                min_row_pitch_in_blocks_or_pixels: width,
+            });
+        }
+
+        let blocks_or_pixels = if height == 0 || width == 0 {
+            0
+        } else if !block_format.is_compressed() {
+            // The transcoder expects whole rows of pixels
+            height as u64 * row_pitch_in_blocks_or_pixels as u64
+        } else {
+            (height as u64 - 1) * row_pitch_in_blocks_or_pixels as u64 + width as u64
+        };
+        let required_bytes = if blocks_or_pixels == 0 {
+            0
+        } else {
+            (blocks_or_pixels - 1) * block_stride_in_bytes as u64 + bytes_per_block_or_pixel as u64
+        };
+
+        // Row count only applies to uncompressed formats
+        let rows_in_pixels = if block_format.is_compressed() {
+            0
+        } else {
+            height
+        };
+
+        Ok(SliceOutputLayout {
+            row_pitch_in_blocks_or_pixels,
+            rows_in_pixels,
+            block_stride_in_bytes,
+            blocks_or_pixels: blocks_or_pixels.min(u32::MAX as u64) as u32,
+            required_bytes: required_bytes.min(usize::MAX as u64) as usize,
+        })
+    }
+}
+
+/// Error result from querying or transcoding basis data
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TranscodeError {
-    TranscodeFormatNotSupported,
-    ImageLevelNotFound,
-    TranscodeFailed,
+    /// The data does not start with a valid .basis header
+    InvalidHeader,
+    /// The header or data crc16 checksum does not match the contents of the file
+    ChecksumMismatch,
+    /// The requested image does not exist in the basis data
+    ImageNotFound { image_index: u32, image_count: u32 },
+    /// The requested mip level does not exist on the image
+    ImageLevelNotFound {
+        image_index: u32,
+        level_index: u32,
+        level_count: u32,
+    },
+    /// `prepare_transcoding()` must be called before transcoding
+    NotPrepared,
+    /// The selector/endpoint codebooks or huffman tables could not be decoded
+    PrepareFailed,
+    /// The basis texture format cannot be transcoded to the requested format
+    TranscodeFormatNotSupported {
+        basis_format: BasisTextureFormat,
+        transcode_format: TranscoderTextureFormat,
+    },
+    /// PVRTC1 requires the (block-aligned) dimensions of the image level to be a power of two
+    PvrtcNotPowerOfTwo {
+        image_index: u32,
+        level_index: u32,
+        width: u32,
+        height: u32,
+    },
+    /// The output buffer is too small to hold the transcoded data
+    OutputBufferTooSmall {
+        required_bytes: usize,
+        provided_bytes: usize,
+    },
+    /// The underlying library failed to transcode the image level
+    TranscodeFailed { image_index: u32, level_index: u32 },
+    /// The underlying library failed to transcode a low-level slice
+    SliceTranscodeFailed,
+    /// The requested slice does not exist in the basis data
+    SliceNotFound { slice_index: u32, slice_count: u32 },
+    /// The block format cannot be used for this operation
+    BlockFormatNotSupported { block_format: TranscoderBlockFormat },
+    /// The block stride is smaller than a block, or is not the pixel size for uncompressed formats
+    InvalidBlockStride {
+        block_format: TranscoderBlockFormat,
+        block_stride_in_bytes: u32,
+    },
+    /// The row pitch is smaller than the width of the slice
+    InvalidRowPitch {
+        row_pitch_in_blocks_or_pixels: u32,
+        min_row_pitch_in_blocks_or_pixels: u32,
+    },
+    /// Channel indices must be in the range 0-3
+    InvalidChannel { channel: u32 },
+    /// Only uncompressed formats can be decoded to an image
+    UncompressedFormatRequired {
+        transcode_format: TranscoderTextureFormat,
+    },
+    /// The image level has more blocks or pixels than the transcoder can address
+    OutputTooLarge { image_index: u32, level_index: u32 },
+    /// The block counts describe a slice that is too large for its pixel dimensions to fit in a u32
+    BlockCountTooLarge {
+        num_blocks_x: u32,
+        num_blocks_y: u32,
+    },
+}
+
+impl std::fmt::Display for TranscodeError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match *self {
+            TranscodeError::InvalidHeader => write!(f, "invalid .basis header"),
+            TranscodeError::ChecksumMismatch => write!(f, ".basis file checksum mismatch"),
+            TranscodeError::ImageNotFound {
+                image_index,
+                image_count,
+            } => write!(
+                f,
+                "image {} not found (file has {} images)",
+                image_index, image_count
+            ),
+            TranscodeError::ImageLevelNotFound {
+                image_index,
+                level_index,
+                level_count,
+            } => write!(
+                f,
+                "level {} not found on image {} (image has {} levels)",
+                level_index, image_index, level_count
+            ),
+            TranscodeError::NotPrepared => {
+                write!(f, "prepare_transcoding() was not called before transcoding")
+            }
+            TranscodeError::PrepareFailed => {
+                write!(f, "failed to prepare .basis data for transcoding")
+            }
+            TranscodeError::TranscodeFormatNotSupported {
+                basis_format,
+                transcode_format,
+            } => write!(
+                f,
+                "cannot transcode {:?} data to {:?}",
+                basis_format, transcode_format
+            ),
+            TranscodeError::PvrtcNotPowerOfTwo {
+                image_index,
+                level_index,
+                width,
+                height,
+            } => write!(
+                f,
+                "PVRTC1 requires power of two dimensions, but image {} level {} is {}x{}",
+                image_index, level_index, width, height
+            ),
+            TranscodeError::OutputBufferTooSmall {
+                required_bytes,
+                provided_bytes,
+            } => write!(
+                f,
+                "output buffer is too small ({} bytes required, {} bytes provided)",
+                required_bytes, provided_bytes
+            ),
+            TranscodeError::TranscodeFailed {
+                image_index,
+                level_index,
+            } => write!(
+                f,
+                "failed to transcode image {} level {}",
+                image_index, level_index
+            ),
+            TranscodeError::SliceTranscodeFailed => write!(f, "failed to transcode slice"),
+            TranscodeError::SliceNotFound {
+                slice_index,
+                slice_count,
+            } => write!(
+                f,
+                "slice {} not found (file has {} slices)",
+                slice_index, slice_count
+            ),
+            TranscodeError::BlockFormatNotSupported { block_format } => {
+                write!(f, "block format {:?} is not supported here", block_format)
+            }
+            TranscodeError::InvalidBlockStride {
+                block_format,
+                block_stride_in_bytes,
+            } => write!(
+                f,
+                "invalid block stride of {} bytes for {:?}",
+                block_stride_in_bytes, block_format
+            ),
+            TranscodeError::InvalidRowPitch {
+                row_pitch_in_blocks_or_pixels,
+                min_row_pitch_in_blocks_or_pixels,
+            } => write!(
+                f,
+                "row pitch of {} is smaller than the slice width of {}",
+                row_pitch_in_blocks_or_pixels, min_row_pitch_in_blocks_or_pixels
+            ),
+            TranscodeError::InvalidChannel { channel } => {
+                write!(f, "channel {} is out of range (must be 0-3)", channel)
+            }
+            TranscodeError::UncompressedFormatRequired { transcode_format } => write!(
+                f,
+                "{:?} is compressed and can't be decoded to an image",
+                transcode_format
+            ),
+            TranscodeError::OutputTooLarge {
+                image_index,
+                level_index,
+            } => write!(
+                f,
+                "image {} level {} is too large to transcode",
+                image_index, level_index
+            ),
+            TranscodeError::BlockCountTooLarge {
+                num_blocks_x,
+                num_blocks_y,
+            } => write!(
+                f,
+                "{}x{} blocks is too large to address",
+                num_blocks_x, num_blocks_y
+            ),
+        }
+    }
 }
 
+impl std::error::Error for TranscodeError {}
+
 impl Default for Transcoder {
     fn default() -> Self {
         Self::new()
@@ -55,7 +422,12 @@ impl Default for Transcoder {
 impl Transcoder {
     /// Create a transcoder
     pub fn new() -> Transcoder {
-        unsafe { Transcoder(sys::transcoder_new()) }
+        unsafe {
+            Transcoder {
+                transcoder: sys::transcoder_new(),
+                internal_state_lock: Mutex::default(),
+            }
+        }
     }
 
     /// Validates the .basis file. This computes a crc16 over the entire file, so it's slow.
@@ -63,14 +435,21 @@ impl Transcoder {
         &self,
         data: &[u8],
         full_validation: bool,
-    ) -> bool {
-        unsafe {
+    ) -> Result<(), TranscodeError> {
+        self.validate_header(data)?;
+        let valid = unsafe {
             sys::transcoder_validate_file_checksums(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 full_validation,
             )
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(TranscodeError::ChecksumMismatch)
         }
     }
 
@@ -78,38 +457,53 @@ impl Transcoder {
     pub fn validate_header(
         &self,
         data: &[u8],
-    ) -> bool {
-        unsafe { sys::transcoder_validate_header(self.0, data.as_ptr() as _, data.len() as u32) }
+    ) -> Result<(), TranscodeError> {
+        let valid = unsafe {
+            sys::transcoder_validate_header(self.transcoder, data.as_ptr() as _, data.len() as u32)
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(TranscodeError::InvalidHeader)
+        }
     }
 
     /// The type of texture represented by the basis data
     pub fn basis_texture_type(
         &self,
         data: &[u8],
-    ) -> BasisTextureType {
-        unsafe {
-            sys::transcoder_get_texture_type(self.0, data.as_ptr() as _, data.len() as u32).into()
-        }
+    ) -> Result<BasisTextureType, TranscodeError> {
+        self.validate_header(data)?;
+        let texture_type = unsafe {
+            sys::transcoder_get_texture_type(self.transcoder, data.as_ptr() as _, data.len() as u32)
+        };
+        BasisTextureType::try_from(texture_type).map_err(|_| TranscodeError::InvalidHeader)
     }
 
     /// The basis texture format of the basis data
     pub fn basis_texture_format(
         &self,
         data: &[u8],
-    ) -> BasisTextureFormat {
-        unsafe {
-            sys::transcoder_get_tex_format(self.0, data.as_ptr() as _, data.len() as u32).into()
-        }
+    ) -> Result<BasisTextureFormat, TranscodeError> {
+        // The underlying library reads the format from the header without checking it
+        self.validate_header(data)?;
+        let texture_format = unsafe {
+            sys::transcoder_get_tex_format(self.transcoder, data.as_ptr() as _, data.len() as u32)
+        };
+        BasisTextureFormat::try_from(texture_format).map_err(|_| TranscodeError::InvalidHeader)
     }
 
+    /// Arbitrary data that was attached to the basis data when it was encoded
     pub fn user_data(
         &self,
         data: &[u8],
-    ) -> Result<UserData, ()> {
+    ) -> Result<UserData, TranscodeError> {
+        self.validate_header(data)?;
         let mut userdata = UserData::default();
         let result = unsafe {
             sys::transcoder_get_userdata(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 &mut userdata.userdata0,
@@ -120,7 +514,7 @@ impl Transcoder {
         if result {
             Ok(userdata)
         } else {
-            Err(())
+            Err(TranscodeError::InvalidHeader)
         }
     }
 
@@ -128,8 +522,11 @@ impl Transcoder {
     pub fn image_count(
         &self,
         data: &[u8],
-    ) -> u32 {
-        unsafe { sys::transcoder_get_total_images(self.0, data.as_ptr() as _, data.len() as u32) }
+    ) -> Result<u32, TranscodeError> {
+        self.validate_header(data)?;
+        Ok(unsafe {
+            sys::transcoder_get_total_images(self.transcoder, data.as_ptr() as _, data.len() as u32)
+        })
     }
 
     /// Number of mipmap levels for the specified image in the basis data
@@ -137,15 +534,52 @@ impl Transcoder {
         &self,
         data: &[u8],
         image_index: u32,
-    ) -> u32 {
-        unsafe {
+    ) -> Result<u32, TranscodeError> {
+        self.check_image(data, image_index)?;
+        Ok(unsafe {
             sys::transcoder_get_total_image_levels(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 image_index,
             )
+        })
+    }
+
+    /// Returns an error if the image does not exist in the basis data
+    fn check_image(
+        &self,
+        data: &[u8],
+        image_index: u32,
+    ) -> Result<(), TranscodeError> {
+        let image_count = self.image_count(data)?;
+        if image_index >= image_count {
+            return Err(TranscodeError::ImageNotFound {
+                image_index,
+                image_count,
+            });
         }
+
+        Ok(())
+    }
+
+    /// Returns an error if the image or the mip level on that image does not exist in the basis data
+    fn check_image_level(
+        &self,
+        data: &[u8],
+        image_index: u32,
+        level_index: u32,
+    ) -> Result<(), TranscodeError> {
+        let level_count = self.image_level_count(data, image_index)?;
+        if level_index >= level_count {
+            return Err(TranscodeError::ImageLevelNotFound {
+                image_index,
+                level_index,
+                level_count,
+            });
+        }
+
+        Ok(())
     }
 
     /// Returns basic information about an image. Note that orig_width/orig_height may not be a multiple of 4.
@@ -154,11 +588,12 @@ impl Transcoder {
         data: &[u8],
         image_index: u32,
         level_index: u32,
-    ) -> Option<ImageLevelDescription> {
+    ) -> Result<ImageLevelDescription, TranscodeError> {
+        self.check_image_level(data, image_index, level_index)?;
         let mut description = ImageLevelDescription::default();
         unsafe {
             if sys::transcoder_get_image_level_desc(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 image_index,
@@ -167,9 +602,9 @@ impl Transcoder {
                 &mut description.original_height,
                 &mut description.block_count,
             ) {
-                Some(description)
+                Ok(description)
             } else {
-                None
+                Err(TranscodeError::InvalidHeader)
             }
         }
     }
@@ -179,19 +614,20 @@ impl Transcoder {
         &self,
         data: &[u8],
         image_index: u32,
-    ) -> Option<ImageInfo> {
+    ) -> Result<ImageInfo, TranscodeError> {
+        self.check_image(data, image_index)?;
         let mut image_info = unsafe { std::mem::zeroed::<ImageInfo>() };
         unsafe {
             if sys::transcoder_get_image_info(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 &mut image_info,
                 image_index,
             ) {
-                Some(image_info)
+                Ok(image_info)
             } else {
-                None
+                Err(TranscodeError::InvalidHeader)
             }
         }
     }
@@ -202,20 +638,21 @@ impl Transcoder {
         data: &[u8],
         image_index: u32,
         level_index: u32,
-    ) -> Option<ImageLevelInfo> {
+    ) -> Result<ImageLevelInfo, TranscodeError> {
+        self.check_image_level(data, image_index, level_index)?;
         let mut image_level_info = unsafe { std::mem::zeroed::<ImageLevelInfo>() };
         unsafe {
             if sys::transcoder_get_image_level_info(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 &mut image_level_info,
                 image_index,
                 level_index,
             ) {
-                Some(image_level_info)
+                Ok(image_level_info)
             } else {
-                None
+                Err(TranscodeError::InvalidHeader)
             }
         }
     }
@@ -224,20 +661,50 @@ impl Transcoder {
     pub fn file_info(
         &self,
         data: &[u8],
-    ) -> Option<FileInfo> {
-        let mut file_info = unsafe { std::mem::zeroed::<FileInfo>() };
+    ) -> Result<FileInfo, TranscodeError> {
+        self.validate_header(data)?;
+        let mut summary = unsafe { std::mem::zeroed::<FileSummary>() };
         unsafe {
-            if sys::transcoder_get_file_info(
-                self.0,
+            if !sys::transcoder_get_file_info(
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
-                &mut file_info,
+                &mut summary,
             ) {
-                Some(file_info)
-            } else {
-                None
+                return Err(TranscodeError::InvalidHeader);
+            }
+        }
+
+        let mut slice_info =
+            vec![unsafe { std::mem::zeroed::<SliceInfo>() }; summary.m_total_slices as usize];
+        let mut image_mipmap_levels = vec![0_u32; summary.m_total_images as usize];
+        unsafe {
+            if !sys::transcoder_get_slice_info(
+                self.transcoder,
+                data.as_ptr() as _,
+                data.len() as u32,
+                slice_info.as_mut_ptr(),
+                slice_info.len() as u32,
+            ) {
+                return Err(TranscodeError::InvalidHeader);
+            }
+
+            if !sys::transcoder_get_image_mipmap_levels(
+                self.transcoder,
+                data.as_ptr() as _,
+                data.len() as u32,
+                image_mipmap_levels.as_mut_ptr(),
+                image_mipmap_levels.len() as u32,
+            ) {
+                return Err(TranscodeError::InvalidHeader);
             }
         }
+
+        Ok(FileInfo {
+            summary,
+            slice_info,
+            image_mipmap_levels,
+        })
     }
 
     /// prepare_transcoding() must be called before calling transcode_slice() or transcode_image_level().
@@ -246,13 +713,18 @@ impl Transcoder {
     pub fn prepare_transcoding(
         &mut self,
         data: &[u8],
-    ) -> Result<(), ()> {
+    ) -> Result<(), TranscodeError> {
         transcoder_init();
+        self.validate_header(data)?;
         unsafe {
-            if sys::transcoder_start_transcoding(self.0, data.as_ptr() as _, data.len() as u32) {
+            if sys::transcoder_start_transcoding(
+                self.transcoder,
+                data.as_ptr() as _,
+                data.len() as u32,
+            ) {
                 Ok(())
             } else {
-                Err(())
+                Err(TranscodeError::PrepareFailed)
             }
         }
     }
@@ -260,7 +732,7 @@ impl Transcoder {
     /// Parallel with `prepare_transcoding()`, named `stop_transcoding` in the original library
     pub fn end_transcoding(&mut self) {
         unsafe {
-            let result = sys::transcoder_stop_transcoding(self.0);
+            let result = sys::transcoder_stop_transcoding(self.transcoder);
             // I think this function is actually infallible, so don't return a result
             debug_assert!(result);
         }
@@ -268,7 +740,7 @@ impl Transcoder {
 
     /// Returns true if prepare_transcoding() has been called.
     pub fn is_prepared_to_transcode(&self) -> bool {
-        unsafe { sys::transcoder_get_ready_to_transcode(self.0) }
+        unsafe { sys::transcoder_get_ready_to_transcode(self.transcoder) }
     }
 
     /// transcode_image_level() decodes a single mipmap level from the .basis file to any of the supported output texture formats.
@@ -288,30 +760,233 @@ impl Transcoder {
         transcode_format: TranscoderTextureFormat,
         transcode_parameters: TranscodeParameters,
     ) -> Result<Vec<u8>, TranscodeError> {
+        let description =
+            self.check_transcode_image_level(data, transcode_format, &transcode_parameters)?;
+        let required_buffer_bytes =
+            Self::required_output_bytes(&description, transcode_format, &transcode_parameters)?;
+
+        let mut output = vec![0_u8; required_buffer_bytes];
+        self.do_transcode_image_level(
+            data,
+            transcode_format,
+            transcode_parameters,
+            &description,
+            None,
+            &mut output,
+        )?;
+        Ok(output)
+    }
+
+    /// Same as transcode_image_level(), but uses the given state instead of the transcoder's
+    /// internal state. This allows several threads to transcode with the same transcoder at once.
+    pub fn transcode_image_level_with_state(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        transcoder_state: &mut TranscoderState,
+    ) -> Result<Vec<u8>, TranscodeError> {
+        let description =
+            self.check_transcode_image_level(data, transcode_format, &transcode_parameters)?;
+        let required_buffer_bytes =
+            Self::required_output_bytes(&description, transcode_format, &transcode_parameters)?;
+
+        let mut output = vec![0_u8; required_buffer_bytes];
+        self.do_transcode_image_level(
+            data,
+            transcode_format,
+            transcode_parameters,
+            &description,
+            Some(transcoder_state),
+            &mut output,
+        )?;
+        Ok(output)
+    }
+
+    /// Same as transcode_image_level(), but writes the transcoded data into a caller-provided
+    /// buffer instead of allocating one. The buffer must be at least `required_output_size()`
+    /// bytes long. Data beyond the required size is left untouched.
+    pub fn transcode_image_level_into(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        let description =
+            self.check_transcode_image_level(data, transcode_format, &transcode_parameters)?;
+        self.do_transcode_image_level(
+            data,
+            transcode_format,
+            transcode_parameters,
+            &description,
+            None,
+            output,
+        )
+    }
+
+    /// Same as transcode_image_level_into(), but uses the given state instead of the transcoder's
+    /// internal state. This allows several threads to transcode with the same transcoder at once.
+    pub fn transcode_image_level_into_with_state(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        transcoder_state: &mut TranscoderState,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        let description =
+            self.check_transcode_image_level(data, transcode_format, &transcode_parameters)?;
+        self.do_transcode_image_level(
+            data,
+            transcode_format,
+            transcode_parameters,
+            &description,
+            Some(transcoder_state),
+            output,
+        )
+    }
+
+    /// Returns the number of bytes needed to hold the given image level when transcoded to
+    /// `transcode_format`. This takes the optional row pitch and row count overrides in
+    /// `transcode_parameters` into account.
+    pub fn required_output_size(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: &TranscodeParameters,
+    ) -> Result<usize, TranscodeError> {
+        let description = self.image_level_description(
+            data,
+            transcode_parameters.image_index,
+            transcode_parameters.level_index,
+        )?;
+        Self::required_output_bytes(&description, transcode_format, transcode_parameters)
+    }
+
+    pub(crate) fn required_output_bytes(
+        description: &ImageLevelDescription,
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: &TranscodeParameters,
+    ) -> Result<usize, TranscodeError> {
+        let blocks_or_pixels = transcode_format.calculate_minimum_output_buffer_blocks_or_pixels(
+            description.original_width,
+            description.original_height,
+            description.block_count,
+            transcode_parameters.output_row_pitch_in_blocks_or_pixels,
+            transcode_parameters.output_rows_in_pixels,
+        );
+
+        // The count saturates, and the transcoder can't address more blocks or pixels than fit in
+        // a u32
+        if blocks_or_pixels == u32::MAX {
+            return Err(TranscodeError::OutputTooLarge {
+                image_index: transcode_parameters.image_index,
+                level_index: transcode_parameters.level_index,
+            });
+        }
+
+        Ok(blocks_or_pixels as usize * transcode_format.bytes_per_block_or_pixel() as usize)
+    }
+
+    // Checks everything that can be checked before touching the output buffer and returns the
+    // description of the image level that will be transcoded
+    fn check_transcode_image_level(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: &TranscodeParameters,
+    ) -> Result<ImageLevelDescription, TranscodeError> {
         let image_index = transcode_parameters.image_index;
         let level_index = transcode_parameters.level_index;
 
-        //
-        // Check that the transcode format is supported for the stored texture's basis format
-        //
-        let basis_format = self.basis_texture_format(data);
+        if !self.is_prepared_to_transcode() {
+            return Err(TranscodeError::NotPrepared);
+        }
+
+        let basis_format = self.basis_texture_format(data)?;
+        let description = self.image_level_description(data, image_index, level_index)?;
+        Self::check_transcode_format(
+            basis_format,
+            transcode_format,
+            image_index,
+            level_index,
+            &description,
+        )?;
+
+        Ok(description)
+    }
+
+    // Checks that the image level can be transcoded from the stored texture's basis format to
+    // transcode_format
+    pub(crate) fn check_transcode_format(
+        basis_format: BasisTextureFormat,
+        transcode_format: TranscoderTextureFormat,
+        image_index: u32,
+        level_index: u32,
+        description: &ImageLevelDescription,
+    ) -> Result<(), TranscodeError> {
         if !basis_format.can_transcode_to_format(transcode_format) {
-            return Err(TranscodeError::TranscodeFormatNotSupported);
+            return Err(TranscodeError::TranscodeFormatNotSupported {
+                basis_format,
+                transcode_format,
+            });
+        }
+
+        if transcode_format == TranscoderTextureFormat::PVRTC1_4_RGB
+            || transcode_format == TranscoderTextureFormat::PVRTC1_4_RGBA
+        {
+            // The transcoder checks the block-aligned size, not the original size
+            let width = (description.original_width + 3) & !3;
+            let height = (description.original_height + 3) & !3;
+            if !width.is_power_of_two() || !height.is_power_of_two() {
+                return Err(TranscodeError::PvrtcNotPowerOfTwo {
+                    image_index,
+                    level_index,
+                    width,
+                    height,
+                });
+            }
         }
 
+        Ok(())
+    }
+
+    pub(crate) fn do_transcode_image_level(
+        &self,
+        data: &[u8],
+        transcode_format: TranscoderTextureFormat,
+        transcode_parameters: TranscodeParameters,
+        description: &ImageLevelDescription,
+        transcoder_state: Option<&mut TranscoderState>,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        let image_index = transcode_parameters.image_index;
+        let level_index = transcode_parameters.level_index;
+
         //
-        // Determine required size for the buffer
+        // Check that the buffer is large enough
         //
-        let description = self
-            .image_level_description(data, image_index, level_index)
-            .ok_or(TranscodeError::ImageLevelNotFound)?;
-        let required_buffer_bytes = transcode_format.calculate_minimum_output_buffer_bytes(
-            description.original_width,
-            description.original_height,
-            description.block_count,
-            transcode_parameters.output_row_pitch_in_blocks_or_pixels,
-            transcode_parameters.output_rows_in_pixels,
-        ) as usize;
+        let output_blocks_buf_size_in_blocks_or_pixels = (output.len()
+            / transcode_format.bytes_per_block_or_pixel() as usize)
+            .min(u32::MAX as usize) as u32;
+        let required_buffer_bytes =
+            Self::required_output_bytes(description, transcode_format, &transcode_parameters)?;
+        if output.len() < required_buffer_bytes
+            || !transcode_format.validate_output_buffer_size(
+                output_blocks_buf_size_in_blocks_or_pixels,
+                description.original_width,
+                description.original_height,
+                description.block_count,
+                transcode_parameters.output_row_pitch_in_blocks_or_pixels,
+                transcode_parameters.output_rows_in_pixels,
+            )
+        {
+            return Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: required_buffer_bytes,
+                provided_bytes: output.len(),
+            });
+        }
 
         //
         // unwrap_or() the optional parameters
@@ -323,21 +998,30 @@ impl Transcoder {
             .output_row_pitch_in_blocks_or_pixels
             .unwrap_or(0);
         let output_rows_in_pixels = transcode_parameters.output_rows_in_pixels.unwrap_or(0);
-        let transcoder_state = std::ptr::null_mut();
+
+        // Passing null makes the transcoder use its internal state, so hold the lock for it until
+        // transcoding is finished
+        let _internal_state_guard;
+        let transcoder_state = match transcoder_state {
+            Some(transcoder_state) => transcoder_state.0,
+            None => {
+                _internal_state_guard = self.internal_state_lock.lock().unwrap();
+                std::ptr::null_mut()
+            }
+        };
 
         //
         // Transcode
         //
-        let mut output = vec![0_u8; required_buffer_bytes];
         let success = unsafe {
             sys::transcoder_transcode_image_level(
-                self.0,
+                self.transcoder,
                 data.as_ptr() as _,
                 data.len() as u32,
                 image_index,
                 level_index,
                 output.as_mut_ptr() as _,
-                output.len() as u32,
+                output_blocks_buf_size_in_blocks_or_pixels,
                 transcode_format.into(),
                 decode_flags.bits(),
                 output_row_pitch_in_blocks_or_pixels,
@@ -347,41 +1031,553 @@ impl Transcoder {
         };
 
         if success {
-            Ok(output)
+            Ok(())
         } else {
-            Err(TranscodeError::TranscodeFailed)
-        }
-    }
-
-    // Not implemented
-    //
-    //    // Finds the basis slice corresponding to the specified image/level/alpha params, or -1 if the slice can't be found.
-    //    int find_slice(const void *pData, uint32_t data_size, uint32_t image_index, uint32_t level_index, bool alpha_data) const;
-    //
-    //    // transcode_slice() decodes a single slice from the .basis file. It's a low-level API - most likely you want to use transcode_image_level().
-    //    // This is a low-level API, and will be needed to be called multiple times to decode some texture formats (like BC3, BC5, or ETC2).
-    //    // output_blocks_buf_size_in_blocks_or_pixels is just used for verification to make sure the output buffer is large enough.
-    //    // output_blocks_buf_size_in_blocks_or_pixels should be at least the image level's total_blocks (num_blocks_x * num_blocks_y), or the total number of output pixels if fmt==cTFRGBA32.
-    //    // output_block_stride_in_bytes: Number of bytes between each output block.
-    //    // output_row_pitch_in_blocks_or_pixels: Number of blocks or pixels per row. If 0, the transcoder uses the slice's num_blocks_x or orig_width (NOT num_blocks_x * 4). Ignored for PVRTC1 (due to texture swizzling).
-    //    // output_rows_in_pixels: Ignored unless fmt is cRGBA32. The total number of output rows in the output buffer. If 0, the transcoder assumes the slice's orig_height (NOT num_blocks_y * 4).
-    //    // Notes:
-    //    // - basisu_transcoder_init() must have been called first to initialize the transcoder lookup tables before calling this function.
-    //    bool transcode_slice(const void *pData, uint32_t data_size, uint32_t slice_index,
-    //                         void *pOutput_blocks, uint32_t output_blocks_buf_size_in_blocks_or_pixels,
-    //                         block_format fmt, uint32_t output_block_stride_in_bytes, uint32_t decode_flags = 0, uint32_t output_row_pitch_in_blocks_or_pixels = 0, basisu_transcoder_state * pState = nullptr, void* pAlpha_blocks = nullptr,
-    //                         uint32_t output_rows_in_pixels = 0, int channel0 = -1, int channel1 = -1) const;
-    //
-    //    static void write_opaque_alpha_blocks(
-    //            uint32_t num_blocks_x, uint32_t num_blocks_y,
-    //            void* pOutput_blocks, block_format fmt,
-    //            uint32_t block_stride_in_bytes, uint32_t output_row_pitch_in_blocks_or_pixels);
+            Err(TranscodeError::TranscodeFailed {
+                image_index,
+                level_index,
+            })
+        }
+    }
+
+    /// Finds the slice holding the color data (or alpha data if `alpha_data` is true) of the given
+    /// image level. Returns None if there is no such slice, e.g. alpha data for an ETC1S file
+    /// without alpha.
+    pub fn find_slice(
+        &self,
+        data: &[u8],
+        image_index: u32,
+        level_index: u32,
+        alpha_data: bool,
+    ) -> Result<Option<u32>, TranscodeError> {
+        self.check_image_level(data, image_index, level_index)?;
+        let slice_index = unsafe {
+            sys::transcoder_find_slice(
+                self.transcoder,
+                data.as_ptr() as _,
+                data.len() as u32,
+                image_index,
+                level_index,
+                alpha_data,
+            )
+        };
+
+        if slice_index < 0 {
+            Ok(None)
+        } else {
+            Ok(Some(slice_index as u32))
+        }
+    }
+
+    /// Decodes a single slice to a low-level block format. This is a low-level API - most likely you
+    /// want to use `transcode_image_level()`.
+    ///
+    /// ETC1S color and alpha data are stored in separate slices, so formats with alpha (like BC3 or
+    /// ETC2_RGBA) need one call per slice, interleaving the blocks with
+    /// `output_block_stride_in_bytes`. Use `transcode_slice_into()` to write the blocks at an offset
+    /// within a shared buffer.
+    pub fn transcode_slice(
+        &self,
+        data: &[u8],
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+    ) -> Result<Vec<u8>, TranscodeError> {
+        let layout = self.check_transcode_slice(data, block_format, slice_parameters)?;
+        let mut output = vec![0_u8; layout.required_bytes];
+        self.do_transcode_slice(data, block_format, slice_parameters, &layout, &mut output)?;
+        Ok(output)
+    }
+
+    /// Same as transcode_slice(), but writes the transcoded data into a caller-provided buffer
+    /// instead of allocating one. The buffer must be at least `required_slice_output_size()` bytes
+    /// long.
+    pub fn transcode_slice_into(
+        &self,
+        data: &[u8],
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        let layout = self.check_transcode_slice(data, block_format, slice_parameters)?;
+        self.do_transcode_slice(data, block_format, slice_parameters, &layout, output)
+    }
+
+    /// Returns the number of bytes needed to hold the slice when transcoded to `block_format`. This
+    /// takes the optional block stride, row pitch and row count overrides into account.
+    pub fn required_slice_output_size(
+        &self,
+        data: &[u8],
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+    ) -> Result<usize, TranscodeError> {
+        let slice_info = self.slice_info(data, slice_parameters.slice_index)?;
+        Ok(Self::slice_output_layout(&slice_info, block_format, slice_parameters)?.required_bytes)
+    }
+
+    /// Writes fully opaque alpha blocks. This is useful for filling in the alpha half of BC3 or
+    /// ETC2_RGBA blocks when the basis data has no alpha slice. Only `ETC2_EAC_A8`, `ETC2_EAC_R11`
+    /// and `BC4` are supported.
+    pub fn write_opaque_alpha_blocks(
+        num_blocks_x: u32,
+        num_blocks_y: u32,
+        output: &mut [u8],
+        block_format: TranscoderBlockFormat,
+        block_stride_in_bytes: u32,
+        output_row_pitch_in_blocks: Option<u32>,
+    ) -> Result<(), TranscodeError> {
+        match block_format {
+            TranscoderBlockFormat::ETC2_EAC_A8
+            | TranscoderBlockFormat::ETC2_EAC_R11
+            | TranscoderBlockFormat::BC4 => {}
+            _ => return Err(TranscodeError::BlockFormatNotSupported { block_format }),
+        }
+
+        let block_count_too_large = TranscodeError::BlockCountTooLarge {
+            num_blocks_x,
+            num_blocks_y,
+        };
+        let original_width = num_blocks_x.checked_mul(4).ok_or(block_count_too_large)?;
+        let original_height = num_blocks_y.checked_mul(4).ok_or(block_count_too_large)?;
+        let layout = SliceOutputLayout::new(
+            block_format,
+            (original_width, original_height),
+            (num_blocks_x, num_blocks_y),
+            Some(block_stride_in_bytes),
+            output_row_pitch_in_blocks,
+            None,
+        )?;
+        if output.len() < layout.required_bytes {
+            return Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: layout.required_bytes,
+                provided_bytes: output.len(),
+            });
+        }
+
+        unsafe {
+            sys::transcoder_write_opaque_alpha_blocks(
+                num_blocks_x,
+                num_blocks_y,
+                output.as_mut_ptr() as _,
+                block_format.into(),
+                layout.block_stride_in_bytes,
+                layout.row_pitch_in_blocks_or_pixels,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn slice_info(
+        &self,
+        data: &[u8],
+        slice_index: u32,
+    ) -> Result<SliceInfo, TranscodeError> {
+        let file_info = self.file_info(data)?;
+        file_info
+            .slice_info
+            .get(slice_index as usize)
+            .copied()
+            .ok_or(TranscodeError::SliceNotFound {
+                slice_index,
+                slice_count: file_info.slice_info.len() as u32,
+            })
+    }
+
+    fn slice_output_layout(
+        slice_info: &SliceInfo,
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+    ) -> Result<SliceOutputLayout, TranscodeError> {
+        SliceOutputLayout::new(
+            block_format,
+            (slice_info.m_orig_width, slice_info.m_orig_height),
+            (slice_info.m_num_blocks_x, slice_info.m_num_blocks_y),
+            slice_parameters.output_block_stride_in_bytes,
+            slice_parameters.output_row_pitch_in_blocks_or_pixels,
+            slice_parameters.output_rows_in_pixels,
+        )
+    }
+
+    // Checks everything that can be checked before touching the output buffer and returns where
+    // the slice will be written
+    fn check_transcode_slice(
+        &self,
+        data: &[u8],
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+    ) -> Result<SliceOutputLayout, TranscodeError> {
+        if !self.is_prepared_to_transcode() {
+            return Err(TranscodeError::NotPrepared);
+        }
+
+        let slice_info = self.slice_info(data, slice_parameters.slice_index)?;
+
+        // ETC1S to PVRTC1 RGBA needs the alpha slice to be passed in separately, which isn't exposed
+        if self.basis_texture_format(data)? == BasisTextureFormat::ETC1S
+            && block_format == TranscoderBlockFormat::PVRTC1_4_RGBA
+        {
+            return Err(TranscodeError::BlockFormatNotSupported { block_format });
+        }
+
+        for channel in [slice_parameters.channel0, slice_parameters.channel1]
+            .iter()
+            .flatten()
+        {
+            if *channel > 3 {
+                return Err(TranscodeError::InvalidChannel { channel: *channel });
+            }
+        }
+
+        Self::slice_output_layout(&slice_info, block_format, slice_parameters)
+    }
+
+    fn do_transcode_slice(
+        &self,
+        data: &[u8],
+        block_format: TranscoderBlockFormat,
+        slice_parameters: &SliceTranscodeParameters,
+        layout: &SliceOutputLayout,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        if output.len() < layout.required_bytes {
+            return Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: layout.required_bytes,
+                provided_bytes: output.len(),
+            });
+        }
+
+        let decode_flags = slice_parameters
+            .decode_flags
+            .unwrap_or_else(DecodeFlags::empty);
+        let channel0 = slice_parameters.channel0.map(|c| c as i32).unwrap_or(-1);
+        let channel1 = slice_parameters.channel1.map(|c| c as i32).unwrap_or(-1);
+
+        // Passing null makes the transcoder use its internal state, so hold the lock for it until
+        // transcoding is finished
+        let _internal_state_guard = self.internal_state_lock.lock().unwrap();
+
+        let success = unsafe {
+            sys::transcoder_transcode_slice(
+                self.transcoder,
+                data.as_ptr() as _,
+                data.len() as u32,
+                slice_parameters.slice_index,
+                output.as_mut_ptr() as _,
+                layout.blocks_or_pixels,
+                block_format.into(),
+                layout.block_stride_in_bytes,
+                decode_flags.bits() as u32,
+                layout.row_pitch_in_blocks_or_pixels,
+                std::ptr::null_mut(),
+                layout.rows_in_pixels,
+                channel0,
+                channel1,
+            )
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(TranscodeError::SliceTranscodeFailed)
+        }
+    }
 }
 
 impl Drop for Transcoder {
     fn drop(&mut self) {
         unsafe {
-            sys::transcoder_delete(self.0);
+            sys::transcoder_delete(self.transcoder);
+        }
+    }
+}
+
+/// Transcodes raw ETC1S slices that share codebooks and huffman tables, without a .basis file
+/// around them. `decode_palettes()` and `decode_tables()` must be called before transcoding.
+pub struct LowLevelEtc1sTranscoder {
+    transcoder: *mut sys::LowLevelEtc1sTranscoder,
+    palettes_decoded: bool,
+    tables_decoded: bool,
+}
+
+// The decoded codebooks and tables are only modified through &mut self
+unsafe impl Send for LowLevelEtc1sTranscoder {}
+unsafe impl Sync for LowLevelEtc1sTranscoder {}
+
+impl Default for LowLevelEtc1sTranscoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Describes a raw ETC1S slice passed to [LowLevelEtc1sTranscoder::transcode_slice]
+#[derive(Debug)]
+pub struct SliceParametersEtc1s {
+    pub num_blocks_x: u32,
+    pub num_blocks_y: u32,
+    /// Mip level of the slice. Used to find the previous frame of video slices.
+    pub level_index: u32,
+    pub original_width: u32,
+    pub original_height: u32,
+    /// The slice is a frame of a video texture
+    pub is_video: bool,
+    /// The slice holds alpha data
+    pub is_alpha_slice: bool,
+}
+
+/// Extra options for [LowLevelEtc1sTranscoder::transcode_slice]
+#[derive(Default, Debug, Clone)]
+pub struct Etc1sSliceTranscodeOptions {
+    /// Allow BC1 blocks to use three-color mode
+    pub bc1_allow_threecolor_blocks: bool,
+    /// For ASTC output, the output buffer already contains the alpha slice transcoded to
+    /// [TranscoderBlockFormat::Indices] (with a 16 byte block stride), which is combined with this
+    /// color slice
+    pub astc_transcode_alpha: bool,
+    /// Optional override for the number of bytes between the start of each output block. Must be
+    /// the pixel size for uncompressed formats.
+    pub output_block_stride_in_bytes: Option<u32>,
+    /// Optional override for row pitch. Ignored for PVRTC1.
+    pub output_row_pitch_in_blocks_or_pixels: Option<u32>,
+    /// Optional override for number of rows to transcode. Only used for uncompressed formats.
+    pub output_rows_in_pixels: Option<u32>,
+}
+
+impl LowLevelEtc1sTranscoder {
+    /// Create a LowLevelEtc1sTranscoder
+    pub fn new() -> LowLevelEtc1sTranscoder {
+        transcoder_init();
+        unsafe {
+            LowLevelEtc1sTranscoder {
+                transcoder: sys::low_level_etc1s_transcoder_new(),
+                palettes_decoded: false,
+                tables_decoded: false,
+            }
+        }
+    }
+
+    /// Decodes the endpoint and selector codebooks
+    pub fn decode_palettes(
+        &mut self,
+        num_endpoints: u32,
+        endpoints_data: &[u8],
+        num_selectors: u32,
+        selectors_data: &[u8],
+    ) -> Result<(), TranscodeError> {
+        self.palettes_decoded = unsafe {
+            sys::low_level_etc1s_transcoder_decode_palettes(
+                self.transcoder,
+                num_endpoints,
+                endpoints_data.as_ptr(),
+                endpoints_data.len() as u32,
+                num_selectors,
+                selectors_data.as_ptr(),
+                selectors_data.len() as u32,
+            )
+        };
+
+        if self.palettes_decoded {
+            Ok(())
+        } else {
+            Err(TranscodeError::PrepareFailed)
+        }
+    }
+
+    /// Decodes the huffman tables used by the slices
+    pub fn decode_tables(
+        &mut self,
+        tables_data: &[u8],
+    ) -> Result<(), TranscodeError> {
+        self.tables_decoded = unsafe {
+            sys::low_level_etc1s_transcoder_decode_tables(
+                self.transcoder,
+                tables_data.as_ptr(),
+                tables_data.len() as u32,
+            )
+        };
+
+        if self.tables_decoded {
+            Ok(())
+        } else {
+            Err(TranscodeError::PrepareFailed)
+        }
+    }
+
+    /// Returns true if both the codebooks and huffman tables have been decoded
+    pub fn is_prepared_to_transcode(&self) -> bool {
+        self.palettes_decoded && self.tables_decoded
+    }
+
+    /// Frees the decoded codebooks and tables
+    pub fn clear(&mut self) {
+        unsafe {
+            sys::low_level_etc1s_transcoder_clear(self.transcoder);
+        }
+        self.palettes_decoded = false;
+        self.tables_decoded = false;
+    }
+
+    /// Transcodes a single slice. Video slices are decoded relative to the previous frame, which
+    /// is remembered by the transcoder.
+    pub fn transcode_slice(
+        &mut self,
+        data: &[u8],
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+    ) -> Result<Vec<u8>, TranscodeError> {
+        let required_buffer_bytes =
+            self.required_output_size(slice_parameters, options, transcode_block_format)?;
+        let mut output = vec![0_u8; required_buffer_bytes];
+        self.do_transcode_slice(
+            data,
+            slice_parameters,
+            options,
+            transcode_block_format,
+            None,
+            &mut output,
+        )?;
+        Ok(output)
+    }
+
+    /// Same as transcode_slice(), but writes the transcoded data into a caller-provided buffer
+    /// instead of allocating one. The buffer must be at least `required_output_size()` bytes long.
+    pub fn transcode_slice_into(
+        &mut self,
+        data: &[u8],
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        self.do_transcode_slice(
+            data,
+            slice_parameters,
+            options,
+            transcode_block_format,
+            None,
+            output,
+        )
+    }
+
+    /// Same as transcode_slice_into(), but video frames are tracked in `transcoder_state` instead of
+    /// the transcoder. This allows several threads to use one transcoder at once.
+    pub fn transcode_slice_into_with_state(
+        &self,
+        data: &[u8],
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+        transcoder_state: &mut TranscoderState,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        self.do_transcode_slice(
+            data,
+            slice_parameters,
+            options,
+            transcode_block_format,
+            Some(transcoder_state),
+            output,
+        )
+    }
+
+    /// Returns the number of bytes needed to hold the slice when transcoded to
+    /// `transcode_block_format`
+    pub fn required_output_size(
+        &self,
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+    ) -> Result<usize, TranscodeError> {
+        Ok(Self::output_layout(slice_parameters, options, transcode_block_format)?.required_bytes)
+    }
+
+    fn output_layout(
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+    ) -> Result<SliceOutputLayout, TranscodeError> {
+        SliceOutputLayout::new(
+            transcode_block_format,
+            (
+                slice_parameters.original_width,
+                slice_parameters.original_height,
+            ),
+            (slice_parameters.num_blocks_x, slice_parameters.num_blocks_y),
+            options.output_block_stride_in_bytes,
+            options.output_row_pitch_in_blocks_or_pixels,
+            options.output_rows_in_pixels,
+        )
+    }
+
+    fn do_transcode_slice(
+        &self,
+        data: &[u8],
+        slice_parameters: &SliceParametersEtc1s,
+        options: &Etc1sSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+        transcoder_state: Option<&mut TranscoderState>,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        if !self.is_prepared_to_transcode() {
+            return Err(TranscodeError::NotPrepared);
+        }
+
+        // PVRTC1 RGBA needs the alpha slice to be passed in separately, which isn't exposed
+        if transcode_block_format == TranscoderBlockFormat::PVRTC1_4_RGBA {
+            return Err(TranscodeError::BlockFormatNotSupported {
+                block_format: transcode_block_format,
+            });
+        }
+
+        let layout = Self::output_layout(slice_parameters, options, transcode_block_format)?;
+        if output.len() < layout.required_bytes {
+            return Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: layout.required_bytes,
+                provided_bytes: output.len(),
+            });
+        }
+
+        // Passing null makes the transcoder use its internal state. Callers without a
+        // TranscoderState hold &mut self.
+        let transcoder_state = transcoder_state
+            .map(|transcoder_state| transcoder_state.0)
+            .unwrap_or(std::ptr::null_mut());
+
+        let success = unsafe {
+            sys::low_level_etc1s_transcoder_transcode_slice(
+                self.transcoder,
+                output.as_mut_ptr() as _,
+                slice_parameters.num_blocks_x,
+                slice_parameters.num_blocks_y,
+                data.as_ptr(),
+                data.len() as u32,
+                transcode_block_format.into(),
+                layout.block_stride_in_bytes,
+                options.bc1_allow_threecolor_blocks,
+                slice_parameters.is_video,
+                slice_parameters.is_alpha_slice,
+                slice_parameters.level_index,
+                slice_parameters.original_width,
+                slice_parameters.original_height,
+                layout.row_pitch_in_blocks_or_pixels,
+                transcoder_state,
+                options.astc_transcode_alpha,
+                layout.rows_in_pixels,
+            )
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(TranscodeError::SliceTranscodeFailed)
+        }
+    }
+}
+
+impl Drop for LowLevelEtc1sTranscoder {
+    fn drop(&mut self) {
+        unsafe {
+            sys::low_level_etc1s_transcoder_delete(self.transcoder);
         }
     }
 }
@@ -403,6 +1599,41 @@ pub struct SliceParametersUastc {
     pub original_height: u32,
 }
 
+/// Extra options for [LowLevelUastcTranscoder::transcode_slice]
+#[derive(Debug, Clone)]
+pub struct UastcSliceTranscodeOptions {
+    /// Optional flags can affect transcoding in various ways
+    pub decode_flags: Option<DecodeFlags>,
+    /// Source channel (0-3) for single channel formats like BC4 and ETC2_EAC_R11, or the first
+    /// channel of BC5 and ETC2_EAC_RG11. Defaults to 0 (red).
+    pub channel0: u32,
+    /// Source channel (0-3) for the second channel of BC5 and ETC2_EAC_RG11. Defaults to 3 (alpha).
+    pub channel1: u32,
+    /// Allow BC1 blocks to use three-color mode (punch-through)
+    pub bc1_allow_threecolor_blocks: bool,
+    /// Optional override for the number of bytes between the start of each output block. Must be
+    /// the pixel size for uncompressed formats.
+    pub output_block_stride_in_bytes: Option<u32>,
+    /// Optional override for row pitch. Ignored for PVRTC1.
+    pub output_row_pitch_in_blocks_or_pixels: Option<u32>,
+    /// Optional override for number of rows to transcode. Only used for uncompressed formats.
+    pub output_rows_in_pixels: Option<u32>,
+}
+
+impl Default for UastcSliceTranscodeOptions {
+    fn default() -> Self {
+        UastcSliceTranscodeOptions {
+            decode_flags: None,
+            channel0: 0,
+            channel1: 3,
+            bc1_allow_threecolor_blocks: false,
+            output_block_stride_in_bytes: None,
+            output_row_pitch_in_blocks_or_pixels: None,
+            output_rows_in_pixels: None,
+        }
+    }
+}
+
 impl LowLevelUastcTranscoder {
     /// Create a LowLevelUastcTranscoder
     pub fn new() -> LowLevelUastcTranscoder {
@@ -414,31 +1645,51 @@ impl LowLevelUastcTranscoder {
         &self,
         data: &[u8],
         slice_parameters: SliceParametersUastc,
-        decode_flags: DecodeFlags,
+        options: &UastcSliceTranscodeOptions,
         transcode_block_format: TranscoderBlockFormat,
     ) -> Result<Vec<u8>, TranscodeError> {
-        let bc1_allow_threecolor_blocks = false;
+        let required_buffer_bytes =
+            self.required_output_size(&slice_parameters, options, transcode_block_format)?;
+        let mut output = vec![0_u8; required_buffer_bytes];
+        self.transcode_slice_into(
+            data,
+            slice_parameters,
+            options,
+            transcode_block_format,
+            &mut output,
+        )?;
+        Ok(output)
+    }
+
+    /// Same as transcode_slice(), but writes the transcoded data into a caller-provided buffer
+    /// instead of allocating one. The buffer must be at least `required_output_size()` bytes long.
+    pub fn transcode_slice_into(
+        &self,
+        data: &[u8],
+        slice_parameters: SliceParametersUastc,
+        options: &UastcSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+        output: &mut [u8],
+    ) -> Result<(), TranscodeError> {
+        for &channel in &[options.channel0, options.channel1] {
+            if channel > 3 {
+                return Err(TranscodeError::InvalidChannel { channel });
+            }
+        }
+
+        let layout = Self::output_layout(&slice_parameters, options, transcode_block_format)?;
+        if output.len() < layout.required_bytes {
+            return Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: layout.required_bytes,
+                provided_bytes: output.len(),
+            });
+        }
+
+        let decode_flags = options.decode_flags.unwrap_or_else(DecodeFlags::empty);
+
+        // The UASTC transcoder does not use the state
         let transcoder_state = std::ptr::null_mut();
-        let channel0 = 0;
-        let channel1 = 3;
-
-        let output_row_pitch_in_blocks_or_pixels =
-            (slice_parameters.original_width + transcode_block_format.block_width() - 1)
-                / transcode_block_format.block_width();
-        let output_rows_in_pixels = slice_parameters.original_height;
-        let total_slice_blocks = slice_parameters.num_blocks_x * slice_parameters.num_blocks_y;
-        let required_buffer_bytes = transcode_block_format.calculate_minimum_output_buffer_bytes(
-            slice_parameters.original_width,
-            slice_parameters.original_height,
-            total_slice_blocks,
-            Some(output_row_pitch_in_blocks_or_pixels),
-            Some(output_rows_in_pixels),
-        ) as usize;
-
-        let output_block_or_pixel_stride_in_bytes =
-            transcode_block_format.bytes_per_block_or_pixel();
 
-        let mut output = vec![0_u8; required_buffer_bytes];
         let success = unsafe {
             sys::low_level_uastc_transcoder_transcode_slice(
                 self.0,
@@ -448,26 +1699,56 @@ impl LowLevelUastcTranscoder {
                 data.as_ptr() as _,
                 data.len() as u32,
                 transcode_block_format.into(),
-                output_block_or_pixel_stride_in_bytes,
-                bc1_allow_threecolor_blocks,
+                layout.block_stride_in_bytes,
+                options.bc1_allow_threecolor_blocks,
                 slice_parameters.has_alpha,
                 slice_parameters.original_width,
                 slice_parameters.original_height,
-                output_row_pitch_in_blocks_or_pixels,
+                layout.row_pitch_in_blocks_or_pixels,
                 transcoder_state,
-                output_rows_in_pixels,
-                channel0,
-                channel1,
+                layout.rows_in_pixels,
+                options.channel0 as i32,
+                options.channel1 as i32,
                 decode_flags.bits(),
             )
         };
 
         if success {
-            Ok(output)
+            Ok(())
         } else {
-            Err(TranscodeError::TranscodeFailed)
+            Err(TranscodeError::SliceTranscodeFailed)
         }
     }
+
+    /// Returns the number of bytes needed to hold the slice when transcoded to
+    /// `transcode_block_format`. This takes the optional block stride, row pitch and row count
+    /// overrides in `options` into account.
+    pub fn required_output_size(
+        &self,
+        slice_parameters: &SliceParametersUastc,
+        options: &UastcSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+    ) -> Result<usize, TranscodeError> {
+        Ok(Self::output_layout(slice_parameters, options, transcode_block_format)?.required_bytes)
+    }
+
+    fn output_layout(
+        slice_parameters: &SliceParametersUastc,
+        options: &UastcSliceTranscodeOptions,
+        transcode_block_format: TranscoderBlockFormat,
+    ) -> Result<SliceOutputLayout, TranscodeError> {
+        SliceOutputLayout::new(
+            transcode_block_format,
+            (
+                slice_parameters.original_width,
+                slice_parameters.original_height,
+            ),
+            (slice_parameters.num_blocks_x, slice_parameters.num_blocks_y),
+            options.output_block_stride_in_bytes,
+            options.output_row_pitch_in_blocks_or_pixels,
+            options.output_rows_in_pixels,
+        )
+    }
 }
 
 impl Drop for LowLevelUastcTranscoder {
@@ -1,4 +1,5 @@
 use super::*;
+use std::convert::TryFrom;
 
 #[test]
 fn test_get_bytes_per_block_or_pixel() {
@@ -19,6 +20,45 @@ fn test_transcoder_format_has_alpha() {
     assert_eq!(TranscoderTextureFormat::BC7_RGBA.has_alpha(), true);
 }
 
+#[test]
+fn test_enum_try_from() {
+    for &format in &[
+        TranscoderTextureFormat::ETC1_RGB,
+        TranscoderTextureFormat::BC7_RGBA,
+        TranscoderTextureFormat::RGBA4444,
+    ] {
+        let value: sys::basist_transcoder_texture_format = format.into();
+        assert_eq!(TranscoderTextureFormat::try_from(value), Ok(format));
+    }
+
+    // 7 is the unused BC7_ALT slot
+    assert_eq!(TranscoderTextureFormat::try_from(7), Err(7));
+    assert_eq!(
+        TranscoderTextureFormat::try_from(
+            sys::basist_transcoder_texture_format_cTFTotalTextureFormats
+        ),
+        Err(sys::basist_transcoder_texture_format_cTFTotalTextureFormats)
+    );
+    assert_eq!(
+        TranscoderBlockFormat::try_from(sys::basist_block_format_cBC7),
+        Ok(TranscoderBlockFormat::BC7)
+    );
+    assert_eq!(TranscoderBlockFormat::try_from(-1), Err(-1));
+    assert_eq!(
+        BasisTextureFormat::try_from(1),
+        Ok(BasisTextureFormat::UASTC4x4)
+    );
+    assert_eq!(BasisTextureFormat::try_from(2), Err(2));
+    assert_eq!(
+        BasisTextureType::try_from(4),
+        Ok(BasisTextureType::TextureTypeVolume)
+    );
+    assert_eq!(
+        BasisTextureType::try_from(sys::basist_basis_texture_type_cBASISTexTypeTotal),
+        Err(sys::basist_basis_texture_type_cBASISTexTypeTotal)
+    );
+}
+
 #[test]
 fn test_get_texture_type_name() {
     assert_eq!(BasisTextureType::TextureType2D.texture_type_name(), "2D");
@@ -34,7 +74,7 @@ fn test_new_transcoder() {
 fn test_transcoder_get_total_images() {
     let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
     let transcoder = Transcoder::new();
-    assert_eq!(transcoder.image_count(basis_file), 1);
+    assert_eq!(transcoder.image_count(basis_file).unwrap(), 1);
     std::mem::drop(transcoder);
 }
 
@@ -46,38 +86,481 @@ fn test_transcoder_info() {
     let file_info = transcoder.file_info(basis_file).unwrap();
 
     // These should all return valid results
-    assert!(transcoder.image_info(basis_file, 0).is_some());
-    assert!(transcoder
-        .image_level_description(basis_file, 0, 0)
-        .is_some());
-    assert!(transcoder.image_level_info(basis_file, 0, 0).is_some());
+    assert!(transcoder.image_info(basis_file, 0).is_ok());
+    assert!(transcoder.image_level_description(basis_file, 0, 0).is_ok());
+    assert!(transcoder.image_level_info(basis_file, 0, 0).is_ok());
 
     // These return invalid results because we are passing image index > image count
-    assert!(transcoder
-        .image_info(basis_file, file_info.m_total_images + 1)
-        .is_none());
-    assert!(transcoder
-        .image_level_description(basis_file, file_info.m_total_images + 1, 0)
-        .is_none());
-    assert!(transcoder
-        .image_level_info(basis_file, file_info.m_total_images + 1, 0)
-        .is_none());
+    let image_not_found = Err(TranscodeError::ImageNotFound {
+        image_index: file_info.summary.m_total_images + 1,
+        image_count: file_info.summary.m_total_images,
+    });
+    assert_eq!(
+        transcoder
+            .image_info(basis_file, file_info.summary.m_total_images + 1)
+            .map(|_| ()),
+        image_not_found
+    );
+    assert_eq!(
+        transcoder
+            .image_level_description(basis_file, file_info.summary.m_total_images + 1, 0)
+            .map(|_| ()),
+        image_not_found
+    );
+    assert_eq!(
+        transcoder
+            .image_level_info(basis_file, file_info.summary.m_total_images + 1, 0)
+            .map(|_| ()),
+        image_not_found
+    );
 
     // These return invalid results because we are passing level index > level count
-    assert!(transcoder
-        .image_level_description(basis_file, 0, 100)
-        .is_none());
-    assert!(transcoder.image_level_info(basis_file, 0, 100).is_none());
+    let level_not_found = Err(TranscodeError::ImageLevelNotFound {
+        image_index: 0,
+        level_index: 100,
+        level_count: 7,
+    });
+    assert_eq!(
+        transcoder
+            .image_level_description(basis_file, 0, 100)
+            .map(|_| ()),
+        level_not_found
+    );
+    assert_eq!(
+        transcoder.image_level_info(basis_file, 0, 100).map(|_| ()),
+        level_not_found
+    );
 
     std::mem::drop(transcoder);
 }
 
+#[test]
+fn test_transcoder_slice_info() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let transcoder = Transcoder::new();
+    let file_info = transcoder.file_info(basis_file).unwrap();
+
+    // Color and alpha slices for each of the 7 levels
+    assert_eq!(file_info.summary.m_total_slices, 14);
+    assert_eq!(file_info.slice_info.len(), 14);
+    assert_eq!(file_info.image_mipmap_levels, vec![7]);
+
+    let first = &file_info.slice_info[0];
+    assert_eq!(first.m_orig_width, 64);
+    assert_eq!(first.m_orig_height, 64);
+    assert_eq!(first.m_num_blocks_x, 16);
+    assert_eq!(first.m_num_blocks_y, 16);
+    assert_eq!(first.m_file_ofs, 1278);
+    assert_eq!(first.m_compressed_size, 252);
+    assert_eq!(first.m_unpacked_slice_crc16, 22759);
+
+    for (slice_index, slice) in file_info.slice_info.iter().enumerate() {
+        assert_eq!(slice.m_slice_index, slice_index as u32);
+        assert_eq!(slice.m_image_index, 0);
+        assert_eq!(slice.m_level_index, slice_index as u32 / 2);
+        assert_eq!(slice.m_alpha_flag, slice_index % 2 == 1);
+        assert!((slice.m_file_ofs + slice.m_compressed_size) as usize <= basis_file.len());
+
+        // Slices line up with the per-level offsets reported by image_level_info()
+        let level_info = transcoder
+            .image_level_info(basis_file, 0, slice.m_level_index)
+            .unwrap();
+        if slice.m_alpha_flag {
+            assert_eq!(slice.m_file_ofs, level_info.m_alpha_file_ofs);
+            assert_eq!(slice.m_compressed_size, level_info.m_alpha_file_len);
+        } else {
+            assert_eq!(slice.m_file_ofs, level_info.m_rgb_file_ofs);
+            assert_eq!(slice.m_compressed_size, level_info.m_rgb_file_len);
+        }
+    }
+
+    let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
+    let file_info = transcoder.file_info(basis_file).unwrap();
+    assert_eq!(file_info.slice_info.len(), 7);
+    assert_eq!(file_info.image_mipmap_levels, vec![7]);
+    assert_eq!(file_info.slice_info[0].m_file_ofs, 238);
+    assert_eq!(file_info.slice_info[0].m_compressed_size, 4096);
+    assert_eq!(file_info.slice_info[0].m_unpacked_slice_crc16, 3144);
+    // UASTC stores alpha in the same slice as color
+    assert!(file_info.slice_info.iter().all(|slice| slice.m_alpha_flag));
+}
+
+#[test]
+fn test_file_format_matches_transcoder() {
+    let transcoder = Transcoder::new();
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ]
+    .iter()
+    {
+        let view = file_format::BasisFileView::parse(basis_file).unwrap();
+        view.validate_checksums().unwrap();
+
+        let file_info = transcoder.file_info(basis_file).unwrap();
+        let header = view.header();
+        let summary = &file_info.summary;
+        assert_eq!(header.version as u32, summary.m_version);
+        assert_eq!(header.total_slices, summary.m_total_slices);
+        assert_eq!(header.total_images, summary.m_total_images);
+        assert_eq!(
+            BasisTextureFormat::from(view.texture_format()),
+            BasisTextureFormat::try_from(summary.m_tex_format).unwrap()
+        );
+        assert_eq!(
+            BasisTextureType::from(view.texture_type()),
+            BasisTextureType::try_from(summary.m_tex_type).unwrap()
+        );
+        assert_eq!(header.us_per_frame, summary.m_us_per_frame);
+        assert_eq!(header.userdata0, summary.m_userdata0);
+        assert_eq!(header.userdata1, summary.m_userdata1);
+        assert_eq!(header.total_endpoints, summary.m_total_endpoints);
+        assert_eq!(
+            header.endpoint_codebook_offset,
+            summary.m_endpoint_codebook_ofs
+        );
+        assert_eq!(
+            header.endpoint_codebook_size,
+            summary.m_endpoint_codebook_size
+        );
+        assert_eq!(header.total_selectors, summary.m_total_selectors);
+        assert_eq!(
+            header.selector_codebook_offset,
+            summary.m_selector_codebook_ofs
+        );
+        assert_eq!(
+            header.selector_codebook_size,
+            summary.m_selector_codebook_size
+        );
+        assert_eq!(header.tables_offset, summary.m_tables_ofs);
+        assert_eq!(header.tables_size, summary.m_tables_size);
+        assert_eq!(
+            header.flags.contains(file_format::HeaderFlags::Y_FLIPPED),
+            summary.m_y_flipped
+        );
+        assert_eq!(
+            header.flags.contains(file_format::HeaderFlags::ETC1S),
+            summary.m_etc1s
+        );
+        assert_eq!(
+            header
+                .flags
+                .contains(file_format::HeaderFlags::HAS_ALPHA_SLICES),
+            summary.m_has_alpha_slices
+        );
+
+        assert_eq!(view.slices().len(), file_info.slice_info.len());
+        for (slice, slice_info) in view.slices().zip(file_info.slice_info.iter()) {
+            assert_eq!(slice.image_index, slice_info.m_image_index);
+            assert_eq!(slice.level_index, slice_info.m_level_index);
+            assert_eq!(slice.has_alpha(), slice_info.m_alpha_flag);
+            assert_eq!(slice.is_iframe(), slice_info.m_iframe_flag);
+            assert_eq!(slice.original_width, slice_info.m_orig_width);
+            assert_eq!(slice.original_height, slice_info.m_orig_height);
+            assert_eq!(slice.num_blocks_x, slice_info.m_num_blocks_x);
+            assert_eq!(slice.num_blocks_y, slice_info.m_num_blocks_y);
+            assert_eq!(slice.file_offset, slice_info.m_file_ofs);
+            assert_eq!(slice.file_size, slice_info.m_compressed_size);
+            assert_eq!(
+                slice.slice_data_crc16 as u32,
+                slice_info.m_unpacked_slice_crc16
+            );
+        }
+
+        assert_eq!(
+            view.image_count(),
+            transcoder.image_count(basis_file).unwrap()
+        );
+        for image_index in 0..view.image_count() {
+            let level_count = view.image_level_count(image_index).unwrap();
+            assert_eq!(
+                level_count,
+                transcoder
+                    .image_level_count(basis_file, image_index)
+                    .unwrap()
+            );
+            assert_eq!(
+                level_count,
+                file_info.image_mipmap_levels[image_index as usize]
+            );
+
+            for level_index in 0..level_count {
+                let expected = transcoder
+                    .image_level_description(basis_file, image_index, level_index)
+                    .unwrap();
+                let actual = view
+                    .image_level_description(image_index, level_index)
+                    .unwrap();
+                assert_eq!(actual.original_width, expected.original_width);
+                assert_eq!(actual.original_height, expected.original_height);
+                assert_eq!(actual.block_count, expected.block_count);
+
+                for &alpha_data in &[false, true] {
+                    assert_eq!(
+                        view.find_slice(image_index, level_index, alpha_data),
+                        transcoder
+                            .find_slice(basis_file, image_index, level_index, alpha_data)
+                            .unwrap()
+                    );
+                }
+            }
+
+            assert_eq!(
+                view.image_level_description(image_index, level_count)
+                    .unwrap_err(),
+                file_format::ParseError::ImageLevelNotFound {
+                    image_index,
+                    level_index: level_count,
+                    level_count
+                }
+            );
+        }
+    }
+}
+
+#[test]
+fn test_file_format_errors() {
+    use file_format::{BasisFileView, ParseError};
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+
+    assert_eq!(
+        BasisFileView::parse(&basis_file[0..10]).unwrap_err(),
+        ParseError::TooSmall {
+            required_bytes: file_format::HEADER_SIZE,
+            provided_bytes: 10
+        }
+    );
+    assert!(matches!(
+        BasisFileView::parse(&basis_file[0..basis_file.len() - 1]),
+        Err(ParseError::TooSmall { .. })
+    ));
+
+    let mut data = basis_file.to_vec();
+    data[0] = 0;
+    assert_eq!(
+        BasisFileView::parse(&data).unwrap_err(),
+        ParseError::InvalidSignature(0x4200)
+    );
+
+    let mut data = basis_file.to_vec();
+    data[20] = 7;
+    assert_eq!(
+        BasisFileView::parse(&data).unwrap_err(),
+        ParseError::InvalidTextureFormat(7)
+    );
+
+    let mut data = basis_file.to_vec();
+    data[20] = 1;
+    assert_eq!(
+        BasisFileView::parse(&data).unwrap_err(),
+        ParseError::FormatFlagMismatch
+    );
+
+    let mut data = basis_file.to_vec();
+    data[23] = 9;
+    assert_eq!(
+        BasisFileView::parse(&data).unwrap_err(),
+        ParseError::InvalidTextureType(9)
+    );
+
+    // Point the slice table past the end of the file
+    let mut data = basis_file.to_vec();
+    data[65..69].copy_from_slice(&(basis_file.len() as u32).to_le_bytes());
+    assert!(matches!(
+        BasisFileView::parse(&data),
+        Err(ParseError::SliceTableOutOfBounds { .. })
+    ));
+
+    // Grow the first slice past the end of the file
+    let view = BasisFileView::parse(basis_file).unwrap();
+    let slice_table = view.header().slice_descriptions_offset as usize;
+    let mut data = basis_file.to_vec();
+    data[slice_table + 17..slice_table + 21].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        BasisFileView::parse(&data),
+        Err(ParseError::SliceOutOfBounds { slice_index: 0, .. })
+    ));
+
+    // Corrupted data is only caught by the checksums
+    let mut data = basis_file.to_vec();
+    let last = data.len() - 1;
+    data[last] ^= 0xFF;
+    let view = BasisFileView::parse(&data).unwrap();
+    assert!(matches!(
+        view.validate_checksums(),
+        Err(ParseError::DataChecksumMismatch { .. })
+    ));
+
+    let mut data = basis_file.to_vec();
+    data[31] ^= 0xFF;
+    let view = BasisFileView::parse(&data).unwrap();
+    assert!(matches!(
+        view.validate_checksums(),
+        Err(ParseError::HeaderChecksumMismatch { .. })
+    ));
+
+    let view = BasisFileView::parse(basis_file).unwrap();
+    assert_eq!(
+        view.slice(14),
+        Err(ParseError::SliceNotFound {
+            slice_index: 14,
+            slice_count: 14
+        })
+    );
+    assert_eq!(
+        view.image_level_count(1),
+        Err(ParseError::ImageNotFound {
+            image_index: 1,
+            image_count: 1
+        })
+    );
+    assert_eq!(view.slice_data(0).unwrap().len(), 252);
+}
+
+#[test]
+fn test_file_format_header_flags() {
+    use file_format::{BasisFileView, HeaderFlags};
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ]
+    .iter()
+    {
+        let stored_flags = u16::from_le_bytes([basis_file[21], basis_file[22]]);
+        let view = BasisFileView::parse(basis_file).unwrap();
+        assert_eq!(view.header().flags.bits(), stored_flags);
+
+        // The assets predate the sRGB flag, so set it and the global codebook flag by hand
+        let mut data = basis_file.to_vec();
+        let flags = stored_flags | (HeaderFlags::USES_GLOBAL_CODEBOOK | HeaderFlags::SRGB).bits();
+        data[21..23].copy_from_slice(&flags.to_le_bytes());
+        let view = BasisFileView::parse(&data).unwrap();
+        assert_eq!(view.header().flags.bits(), flags);
+        assert!(view.header().flags.contains(HeaderFlags::SRGB));
+        assert!(view
+            .header()
+            .flags
+            .contains(HeaderFlags::USES_GLOBAL_CODEBOOK));
+    }
+}
+
+#[test]
+fn test_transcoder_errors() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let mut transcoder = Transcoder::new();
+
+    assert!(transcoder.validate_header(basis_file).is_ok());
+    assert!(transcoder.validate_file_checksums(basis_file, true).is_ok());
+
+    // Garbage data should be rejected before it is parsed any further
+    let garbage = [0xCD_u8; 128];
+    assert_eq!(
+        transcoder.validate_header(&garbage),
+        Err(TranscodeError::InvalidHeader)
+    );
+    assert_eq!(
+        transcoder.prepare_transcoding(&garbage),
+        Err(TranscodeError::InvalidHeader)
+    );
+    assert!(transcoder.user_data(&garbage).is_err());
+    assert!(transcoder.file_info(&garbage).is_err());
+    assert_eq!(
+        transcoder.basis_texture_format(&garbage),
+        Err(TranscodeError::InvalidHeader)
+    );
+    assert_eq!(
+        transcoder.image_count(&garbage),
+        Err(TranscodeError::InvalidHeader)
+    );
+    assert_eq!(
+        transcoder.image_level_count(&garbage, 0),
+        Err(TranscodeError::InvalidHeader)
+    );
+    assert_eq!(
+        transcoder.image_level_count(basis_file, 1),
+        Err(TranscodeError::ImageNotFound {
+            image_index: 1,
+            image_count: 1
+        })
+    );
+    assert_eq!(
+        transcoder.basis_texture_type(&basis_file[0..10]),
+        Err(TranscodeError::InvalidHeader)
+    );
+
+    // Flipping a byte in the slice data should fail the checksum but leave the header intact
+    let mut corrupted = basis_file.to_vec();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0xFF;
+    assert!(transcoder.validate_header(&corrupted).is_ok());
+    assert_eq!(
+        transcoder.validate_file_checksums(&corrupted, true),
+        Err(TranscodeError::ChecksumMismatch)
+    );
+
+    // Transcoding requires prepare_transcoding() first
+    assert_eq!(
+        transcoder.transcode_image_level(
+            basis_file,
+            TranscoderTextureFormat::RGBA32,
+            TranscodeParameters::default()
+        ),
+        Err(TranscodeError::NotPrepared)
+    );
+
+    transcoder.prepare_transcoding(basis_file).unwrap();
+    assert_eq!(
+        transcoder.transcode_image_level(
+            basis_file,
+            TranscoderTextureFormat::RGBA32,
+            TranscodeParameters {
+                level_index: 7,
+                ..Default::default()
+            }
+        ),
+        Err(TranscodeError::ImageLevelNotFound {
+            image_index: 0,
+            level_index: 7,
+            level_count: 7,
+        })
+    );
+
+    // Output sizes that the transcoder can't address are rejected instead of overflowing
+    assert_eq!(
+        transcoder.transcode_image_level(
+            basis_file,
+            TranscoderTextureFormat::RGBA32,
+            TranscodeParameters {
+                output_row_pitch_in_blocks_or_pixels: Some(u32::MAX),
+                output_rows_in_pixels: Some(u32::MAX),
+                ..Default::default()
+            }
+        ),
+        Err(TranscodeError::OutputTooLarge {
+            image_index: 0,
+            level_index: 0,
+        })
+    );
+
+    transcoder.end_transcoding();
+
+    // Errors are printable
+    assert_eq!(
+        TranscodeError::ImageNotFound {
+            image_index: 3,
+            image_count: 1
+        }
+        .to_string(),
+        "image 3 not found (file has 1 images)"
+    );
+}
+
 #[test]
 fn test_transcoder_get_tex_format() {
     let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
     let transcoder = Transcoder::new();
     assert_eq!(
-        transcoder.basis_texture_format(basis_file),
+        transcoder.basis_texture_format(basis_file).unwrap(),
         BasisTextureFormat::ETC1S
     );
     std::mem::drop(transcoder);
@@ -85,7 +568,7 @@ fn test_transcoder_get_tex_format() {
     let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
     let transcoder = Transcoder::new();
     assert_eq!(
-        transcoder.basis_texture_format(basis_file),
+        transcoder.basis_texture_format(basis_file).unwrap(),
         BasisTextureFormat::UASTC4x4
     );
     std::mem::drop(transcoder);
@@ -95,7 +578,7 @@ fn test_transcoder_get_tex_format() {
 fn test_transcoder_get_total_image_levels() {
     let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
     let transcoder = Transcoder::new();
-    assert_eq!(transcoder.image_level_count(basis_file, 0), 7);
+    assert_eq!(transcoder.image_level_count(basis_file, 0).unwrap(), 7);
     std::mem::drop(transcoder);
 }
 
@@ -143,7 +626,7 @@ fn do_test_transcoder_transcode(
         )
         .unwrap();
 
-    if transcoder.basis_texture_format(basis_file) == BasisTextureFormat::ETC1S {
+    if transcoder.basis_texture_format(basis_file).unwrap() == BasisTextureFormat::ETC1S {
         transcoder
             .transcode_image_level(
                 basis_file,
@@ -176,3 +659,1793 @@ fn do_test_transcoder_transcode(
 
     std::mem::drop(transcoder);
 }
+
+#[test]
+fn test_transcoder_transcode_into() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+
+    for &format in &[
+        TranscoderTextureFormat::BC7_RGBA,
+        TranscoderTextureFormat::RGBA32,
+    ] {
+        let parameters = TranscodeParameters {
+            level_index: 1,
+            ..Default::default()
+        };
+
+        let expected = transcoder
+            .transcode_image_level(basis_file, format, parameters.clone())
+            .unwrap();
+        let required_size = transcoder
+            .required_output_size(basis_file, format, &parameters)
+            .unwrap();
+        assert_eq!(expected.len(), required_size);
+
+        // A larger buffer is fine, the data past the required size is left untouched
+        let mut output = vec![0xAB_u8; required_size + 16];
+        transcoder
+            .transcode_image_level_into(basis_file, format, parameters.clone(), &mut output)
+            .unwrap();
+        assert_eq!(&output[..required_size], &expected[..]);
+        assert!(output[required_size..].iter().all(|&x| x == 0xAB));
+
+        let mut output = vec![0_u8; required_size - 1];
+        assert_eq!(
+            transcoder.transcode_image_level_into(basis_file, format, parameters, &mut output),
+            Err(TranscodeError::OutputBufferTooSmall {
+                required_bytes: required_size,
+                provided_bytes: required_size - 1,
+            })
+        );
+    }
+
+    transcoder.end_transcoding();
+}
+
+#[test]
+fn test_transcoder_find_slice() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let transcoder = Transcoder::new();
+
+    // ETC1S files with alpha store a color and an alpha slice for each level
+    assert_eq!(transcoder.find_slice(basis_file, 0, 0, false), Ok(Some(0)));
+    assert_eq!(transcoder.find_slice(basis_file, 0, 0, true), Ok(Some(1)));
+    assert_eq!(transcoder.find_slice(basis_file, 0, 3, false), Ok(Some(6)));
+    assert_eq!(transcoder.find_slice(basis_file, 0, 3, true), Ok(Some(7)));
+    assert_eq!(
+        transcoder.find_slice(basis_file, 0, 7, false),
+        Err(TranscodeError::ImageLevelNotFound {
+            image_index: 0,
+            level_index: 7,
+            level_count: 7
+        })
+    );
+}
+
+#[test]
+fn test_transcoder_transcode_slice() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let mut transcoder = Transcoder::new();
+
+    let color_slice = SliceTranscodeParameters {
+        slice_index: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        transcoder.transcode_slice(basis_file, TranscoderBlockFormat::BC1, &color_slice),
+        Err(TranscodeError::NotPrepared)
+    );
+    transcoder.prepare_transcoding(basis_file).unwrap();
+
+    // Build BC3 blocks out of the alpha slice (BC4) and color slice (BC1) and compare against
+    // transcoding the whole level
+    let expected = transcoder
+        .transcode_image_level(
+            basis_file,
+            TranscoderTextureFormat::BC3_RGBA,
+            TranscodeParameters::default(),
+        )
+        .unwrap();
+
+    let alpha_slice = SliceTranscodeParameters {
+        slice_index: transcoder
+            .find_slice(basis_file, 0, 0, true)
+            .unwrap()
+            .unwrap(),
+        output_block_stride_in_bytes: Some(16),
+        ..Default::default()
+    };
+    let color_slice = SliceTranscodeParameters {
+        slice_index: transcoder
+            .find_slice(basis_file, 0, 0, false)
+            .unwrap()
+            .unwrap(),
+        decode_flags: Some(DecodeFlags::BC1_FORBID_THREE_COLOR_BLOCKS),
+        output_block_stride_in_bytes: Some(16),
+        ..Default::default()
+    };
+
+    let mut output = vec![0_u8; expected.len()];
+    assert_eq!(
+        transcoder.required_slice_output_size(basis_file, TranscoderBlockFormat::BC4, &alpha_slice),
+        Ok(expected.len() - 8)
+    );
+    transcoder
+        .transcode_slice_into(
+            basis_file,
+            TranscoderBlockFormat::BC4,
+            &alpha_slice,
+            &mut output,
+        )
+        .unwrap();
+    transcoder
+        .transcode_slice_into(
+            basis_file,
+            TranscoderBlockFormat::BC1,
+            &color_slice,
+            &mut output[8..],
+        )
+        .unwrap();
+    assert_eq!(output, expected);
+
+    // Block formats that TranscoderTextureFormat doesn't reach
+    let result = transcoder
+        .transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::RGBA4444_ALPHA,
+            &alpha_slice,
+        )
+        .unwrap_err();
+    assert_eq!(
+        result,
+        TranscodeError::InvalidBlockStride {
+            block_format: TranscoderBlockFormat::RGBA4444_ALPHA,
+            block_stride_in_bytes: 16
+        }
+    );
+    let alpha_4444 = transcoder
+        .transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::RGBA4444_ALPHA,
+            &SliceTranscodeParameters {
+                slice_index: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(alpha_4444.len(), 64 * 64 * 2);
+
+    //
+    // Invalid parameters
+    //
+    assert_eq!(
+        transcoder.transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::BC1,
+            &SliceTranscodeParameters {
+                slice_index: 14,
+                ..Default::default()
+            },
+        ),
+        Err(TranscodeError::SliceNotFound {
+            slice_index: 14,
+            slice_count: 14
+        })
+    );
+    assert_eq!(
+        transcoder.transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::BC1,
+            &SliceTranscodeParameters {
+                output_block_stride_in_bytes: Some(4),
+                ..Default::default()
+            },
+        ),
+        Err(TranscodeError::InvalidBlockStride {
+            block_format: TranscoderBlockFormat::BC1,
+            block_stride_in_bytes: 4
+        })
+    );
+    assert_eq!(
+        transcoder.transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::RGBA32,
+            &SliceTranscodeParameters {
+                output_row_pitch_in_blocks_or_pixels: Some(32),
+                ..Default::default()
+            },
+        ),
+        Err(TranscodeError::InvalidRowPitch {
+            row_pitch_in_blocks_or_pixels: 32,
+            min_row_pitch_in_blocks_or_pixels: 64
+        })
+    );
+    assert_eq!(
+        transcoder.transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::BC4,
+            &SliceTranscodeParameters {
+                channel0: Some(4),
+                ..Default::default()
+            },
+        ),
+        Err(TranscodeError::InvalidChannel { channel: 4 })
+    );
+    assert_eq!(
+        transcoder.transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::PVRTC1_4_RGBA,
+            &SliceTranscodeParameters::default(),
+        ),
+        Err(TranscodeError::BlockFormatNotSupported {
+            block_format: TranscoderBlockFormat::PVRTC1_4_RGBA
+        })
+    );
+    assert_eq!(
+        transcoder.transcode_slice_into(
+            basis_file,
+            TranscoderBlockFormat::BC1,
+            &SliceTranscodeParameters::default(),
+            &mut output[..100],
+        ),
+        Err(TranscodeError::OutputBufferTooSmall {
+            required_bytes: 16 * 16 * 8,
+            provided_bytes: 100
+        })
+    );
+}
+
+#[test]
+fn test_transcoder_transcode_slice_uastc_channels() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+
+    // The rust logo is black on a transparent background, so red and alpha differ
+    let transcode_channel = |channel| {
+        transcoder
+            .transcode_slice(
+                basis_file,
+                TranscoderBlockFormat::ETC2_EAC_R11,
+                &SliceTranscodeParameters {
+                    channel0: Some(channel),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+    };
+    let red = transcode_channel(0);
+    let alpha = transcode_channel(3);
+    assert_eq!(red.len(), 16 * 16 * 8);
+    assert_eq!(alpha.len(), 16 * 16 * 8);
+    assert_ne!(red, alpha);
+
+    // Pad each row of blocks out to a pitch of 20 blocks
+    let padded = transcoder
+        .transcode_slice(
+            basis_file,
+            TranscoderBlockFormat::BC7,
+            &SliceTranscodeParameters {
+                output_row_pitch_in_blocks_or_pixels: Some(20),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(padded.len(), (15 * 20 + 16) * 16);
+}
+
+#[test]
+fn test_transcoder_write_opaque_alpha_blocks() {
+    // Every other 8 bytes, as the alpha half of BC3 blocks
+    let mut output = vec![0xAA_u8; 4 * 4 * 16];
+    Transcoder::write_opaque_alpha_blocks(4, 4, &mut output, TranscoderBlockFormat::BC4, 16, None)
+        .unwrap();
+    for block in output.chunks(16) {
+        assert_eq!(block[0], 255);
+        assert_eq!(block[1], 255);
+        assert!(block[8..].iter().all(|&x| x == 0xAA));
+    }
+
+    assert_eq!(
+        Transcoder::write_opaque_alpha_blocks(
+            4,
+            4,
+            &mut output[..100],
+            TranscoderBlockFormat::BC4,
+            16,
+            None
+        ),
+        Err(TranscodeError::OutputBufferTooSmall {
+            required_bytes: 15 * 16 + 8,
+            provided_bytes: 100
+        })
+    );
+    assert_eq!(
+        Transcoder::write_opaque_alpha_blocks(
+            4,
+            4,
+            &mut output,
+            TranscoderBlockFormat::BC1,
+            16,
+            None
+        ),
+        Err(TranscodeError::BlockFormatNotSupported {
+            block_format: TranscoderBlockFormat::BC1
+        })
+    );
+    assert_eq!(
+        Transcoder::write_opaque_alpha_blocks(
+            u32::MAX / 4 + 1,
+            1,
+            &mut output,
+            TranscoderBlockFormat::BC4,
+            16,
+            None
+        ),
+        Err(TranscodeError::BlockCountTooLarge {
+            num_blocks_x: u32::MAX / 4 + 1,
+            num_blocks_y: 1
+        })
+    );
+}
+
+#[test]
+fn test_low_level_etc1s_transcode_slice() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+    let file_info = transcoder.file_info(basis_file).unwrap();
+    let summary = &file_info.summary;
+
+    let section = |offset: u32, size: u32| &basis_file[offset as usize..(offset + size) as usize];
+
+    let mut low_level_transcoder = LowLevelEtc1sTranscoder::new();
+    let slice = &file_info.slice_info[0];
+    let slice_parameters = SliceParametersEtc1s {
+        num_blocks_x: slice.m_num_blocks_x,
+        num_blocks_y: slice.m_num_blocks_y,
+        level_index: slice.m_level_index,
+        original_width: slice.m_orig_width,
+        original_height: slice.m_orig_height,
+        is_video: false,
+        is_alpha_slice: slice.m_alpha_flag,
+    };
+    let slice_data = section(slice.m_file_ofs, slice.m_compressed_size);
+    let options = Etc1sSliceTranscodeOptions::default();
+
+    assert_eq!(
+        low_level_transcoder.transcode_slice(
+            slice_data,
+            &slice_parameters,
+            &options,
+            TranscoderBlockFormat::ETC1
+        ),
+        Err(TranscodeError::NotPrepared)
+    );
+
+    low_level_transcoder
+        .decode_palettes(
+            summary.m_total_endpoints,
+            section(
+                summary.m_endpoint_codebook_ofs,
+                summary.m_endpoint_codebook_size,
+            ),
+            summary.m_total_selectors,
+            section(
+                summary.m_selector_codebook_ofs,
+                summary.m_selector_codebook_size,
+            ),
+        )
+        .unwrap();
+    assert!(!low_level_transcoder.is_prepared_to_transcode());
+    low_level_transcoder
+        .decode_tables(section(summary.m_tables_ofs, summary.m_tables_size))
+        .unwrap();
+    assert!(low_level_transcoder.is_prepared_to_transcode());
+
+    // Decoding the slice by itself should match decoding it from the file
+    for &format in &[
+        TranscoderBlockFormat::ETC1,
+        TranscoderBlockFormat::BC7_M5_COLOR,
+        TranscoderBlockFormat::RGBA32,
+    ] {
+        let expected = transcoder
+            .transcode_slice(basis_file, format, &SliceTranscodeParameters::default())
+            .unwrap();
+        let result = low_level_transcoder
+            .transcode_slice(slice_data, &slice_parameters, &options, format)
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    let mut transcoder_state = TranscoderState::new();
+    let mut output = vec![0_u8; 16 * 16 * 8];
+    low_level_transcoder
+        .transcode_slice_into_with_state(
+            slice_data,
+            &slice_parameters,
+            &options,
+            TranscoderBlockFormat::ETC1,
+            &mut transcoder_state,
+            &mut output,
+        )
+        .unwrap();
+
+    assert_eq!(
+        low_level_transcoder.transcode_slice_into(
+            slice_data,
+            &slice_parameters,
+            &options,
+            TranscoderBlockFormat::ETC1,
+            &mut output[..8],
+        ),
+        Err(TranscodeError::OutputBufferTooSmall {
+            required_bytes: 16 * 16 * 8,
+            provided_bytes: 8
+        })
+    );
+    assert_eq!(
+        low_level_transcoder.transcode_slice(
+            slice_data,
+            &slice_parameters,
+            &options,
+            TranscoderBlockFormat::PVRTC1_4_RGBA
+        ),
+        Err(TranscodeError::BlockFormatNotSupported {
+            block_format: TranscoderBlockFormat::PVRTC1_4_RGBA
+        })
+    );
+
+    low_level_transcoder.clear();
+    assert!(!low_level_transcoder.is_prepared_to_transcode());
+}
+
+#[test]
+fn test_low_level_uastc_transcode_slice_into() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
+    let transcoder = Transcoder::new();
+    let level_info = transcoder.image_level_info(basis_file, 0, 0).unwrap();
+    let slice_offset = level_info.m_rgb_file_ofs as usize;
+    let slice_data = &basis_file[slice_offset..slice_offset + level_info.m_rgb_file_len as usize];
+    let slice_parameters = || SliceParametersUastc {
+        num_blocks_x: level_info.m_num_blocks_x,
+        num_blocks_y: level_info.m_num_blocks_y,
+        has_alpha: level_info.m_alpha_flag,
+        original_width: level_info.m_orig_width,
+        original_height: level_info.m_orig_height,
+    };
+    let options = UastcSliceTranscodeOptions {
+        decode_flags: Some(DecodeFlags::HIGH_QUALITY),
+        ..Default::default()
+    };
+
+    let low_level_transcoder = LowLevelUastcTranscoder::new();
+    let expected = low_level_transcoder
+        .transcode_slice(
+            slice_data,
+            slice_parameters(),
+            &options,
+            TranscoderBlockFormat::BC7,
+        )
+        .unwrap();
+    let required_size = low_level_transcoder
+        .required_output_size(&slice_parameters(), &options, TranscoderBlockFormat::BC7)
+        .unwrap();
+    assert_eq!(expected.len(), required_size);
+    assert_eq!(
+        required_size,
+        (level_info.m_total_blocks * TranscoderBlockFormat::BC7.bytes_per_block_or_pixel())
+            as usize
+    );
+
+    let mut output = vec![0_u8; required_size];
+    low_level_transcoder
+        .transcode_slice_into(
+            slice_data,
+            slice_parameters(),
+            &options,
+            TranscoderBlockFormat::BC7,
+            &mut output,
+        )
+        .unwrap();
+    assert_eq!(output, expected);
+
+    let mut output = vec![0_u8; required_size / 2];
+    assert_eq!(
+        low_level_transcoder.transcode_slice_into(
+            slice_data,
+            slice_parameters(),
+            &options,
+            TranscoderBlockFormat::BC7,
+            &mut output,
+        ),
+        Err(TranscodeError::OutputBufferTooSmall {
+            required_bytes: required_size,
+            provided_bytes: required_size / 2,
+        })
+    );
+}
+
+#[test]
+fn test_low_level_uastc_transcode_slice_options() {
+    let basis_file = include_bytes!("../../test_assets/rust-logo-uastc.basis");
+    let transcoder = Transcoder::new();
+    let level_info = transcoder.image_level_info(basis_file, 0, 0).unwrap();
+    let slice_offset = level_info.m_rgb_file_ofs as usize;
+    let slice_data = &basis_file[slice_offset..slice_offset + level_info.m_rgb_file_len as usize];
+    let slice_parameters = || SliceParametersUastc {
+        num_blocks_x: level_info.m_num_blocks_x,
+        num_blocks_y: level_info.m_num_blocks_y,
+        has_alpha: level_info.m_alpha_flag,
+        original_width: level_info.m_orig_width,
+        original_height: level_info.m_orig_height,
+    };
+    let low_level_transcoder = LowLevelUastcTranscoder::new();
+
+    // Uncompressed formats are sized in pixels, not blocks
+    let rgba = low_level_transcoder
+        .transcode_slice(
+            slice_data,
+            slice_parameters(),
+            &UastcSliceTranscodeOptions::default(),
+            TranscoderBlockFormat::RGBA32,
+        )
+        .unwrap();
+    assert_eq!(rgba.len(), 64 * 64 * 4);
+
+    // Rows padded out to 80 pixels hold the same pixels
+    let padded = low_level_transcoder
+        .transcode_slice(
+            slice_data,
+            slice_parameters(),
+            &UastcSliceTranscodeOptions {
+                output_row_pitch_in_blocks_or_pixels: Some(80),
+                ..Default::default()
+            },
+            TranscoderBlockFormat::RGBA32,
+        )
+        .unwrap();
+    assert_eq!(padded.len(), 80 * 64 * 4);
+    for (row, padded_row) in rgba.chunks(64 * 4).zip(padded.chunks(80 * 4)) {
+        assert_eq!(row, &padded_row[..64 * 4]);
+    }
+
+    // Pick the channels to decode, e.g. normal map XY stored in R and G
+    let transcode_channels = |channel0, channel1, block_format| {
+        low_level_transcoder
+            .transcode_slice(
+                slice_data,
+                slice_parameters(),
+                &UastcSliceTranscodeOptions {
+                    channel0,
+                    channel1,
+                    ..Default::default()
+                },
+                block_format,
+            )
+            .unwrap()
+    };
+    let red = transcode_channels(0, 3, TranscoderBlockFormat::ETC2_EAC_R11);
+    let alpha = transcode_channels(3, 3, TranscoderBlockFormat::ETC2_EAC_R11);
+    assert_ne!(red, alpha);
+    let red_alpha = transcode_channels(0, 3, TranscoderBlockFormat::BC5);
+    let alpha_red = transcode_channels(3, 0, TranscoderBlockFormat::BC5);
+    assert_eq!(red_alpha.len(), 16 * 16 * 16);
+    assert_ne!(red_alpha, alpha_red);
+
+    low_level_transcoder
+        .transcode_slice(
+            slice_data,
+            slice_parameters(),
+            &UastcSliceTranscodeOptions {
+                bc1_allow_threecolor_blocks: true,
+                ..Default::default()
+            },
+            TranscoderBlockFormat::BC1,
+        )
+        .unwrap();
+
+    assert_eq!(
+        low_level_transcoder.transcode_slice(
+            slice_data,
+            slice_parameters(),
+            &UastcSliceTranscodeOptions {
+                channel1: 4,
+                ..Default::default()
+            },
+            TranscoderBlockFormat::BC5,
+        ),
+        Err(TranscodeError::InvalidChannel { channel: 4 })
+    );
+}
+
+#[test]
+fn test_transcoder_parallel_with_state() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Transcoder>();
+
+    let basis_file = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+    let level_count = transcoder.image_level_count(basis_file, 0).unwrap();
+
+    let parameters = |level_index| TranscodeParameters {
+        image_index: 0,
+        level_index,
+        ..Default::default()
+    };
+
+    let serial: Vec<_> = (0..level_count)
+        .map(|level_index| {
+            transcoder
+                .transcode_image_level(
+                    basis_file,
+                    TranscoderTextureFormat::BC7_RGBA,
+                    parameters(level_index),
+                )
+                .unwrap()
+        })
+        .collect();
+
+    let transcoder = &transcoder;
+    let parallel: Vec<_> = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..level_count)
+            .map(|level_index| {
+                scope.spawn(move || {
+                    let mut state = TranscoderState::new();
+                    transcoder
+                        .transcode_image_level_with_state(
+                            basis_file,
+                            TranscoderTextureFormat::BC7_RGBA,
+                            parameters(level_index),
+                            &mut state,
+                        )
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(serial, parallel);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_transcoder_transcode_all() {
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ] {
+        let mut transcoder = Transcoder::new();
+        transcoder.prepare_transcoding(basis_file).unwrap();
+
+        for &format in &[
+            TranscoderTextureFormat::BC7_RGBA,
+            TranscoderTextureFormat::ASTC_4x4_RGBA,
+            TranscoderTextureFormat::RGBA32,
+        ] {
+            let texture = transcoder.transcode_all(basis_file, format).unwrap();
+            assert_eq!(texture.format, format);
+            assert_eq!(
+                texture.image_count(),
+                transcoder.image_count(basis_file).unwrap()
+            );
+
+            let mut total_bytes = 0;
+            for image_index in 0..texture.image_count() {
+                assert_eq!(
+                    texture.level_count(image_index),
+                    transcoder
+                        .image_level_count(basis_file, image_index)
+                        .unwrap()
+                );
+
+                for level_index in 0..texture.level_count(image_index) {
+                    let serial = transcoder
+                        .transcode_image_level(
+                            basis_file,
+                            format,
+                            TranscodeParameters {
+                                image_index,
+                                level_index,
+                                ..Default::default()
+                            },
+                        )
+                        .unwrap();
+                    let description = transcoder
+                        .image_level_description(basis_file, image_index, level_index)
+                        .unwrap();
+
+                    let level = texture.level(image_index, level_index).unwrap();
+                    assert_eq!(level.original_width, description.original_width);
+                    assert_eq!(level.original_height, description.original_height);
+                    assert_eq!(
+                        texture.level_data(image_index, level_index).unwrap(),
+                        &serial[..]
+                    );
+                    total_bytes += serial.len();
+                }
+            }
+
+            assert_eq!(texture.data.len(), total_bytes);
+            assert!(texture.level(0, texture.level_count(0)).is_none());
+        }
+
+        transcoder.end_transcoding();
+    }
+}
+
+#[test]
+fn test_basis_file() {
+    let basis_data = include_bytes!("../../test_assets/rust-logo-etc.basis");
+    let transcoder = Transcoder::new();
+    let basis_file = BasisFile::new(basis_data).unwrap();
+
+    assert_eq!(basis_file.basis_texture_format(), BasisTextureFormat::ETC1S);
+    assert_eq!(
+        basis_file.basis_texture_type(),
+        BasisTextureType::TextureType2D
+    );
+    assert_eq!(basis_file.image_count(), 1);
+    assert_eq!(basis_file.images().len(), 1);
+    assert!(basis_file.transcoder().is_prepared_to_transcode());
+
+    // The cached descriptions match what the transcoder reports
+    for image in basis_file.images() {
+        let image_index = image.image_index();
+        assert_eq!(
+            image.level_count(),
+            transcoder
+                .image_level_count(basis_data, image_index)
+                .unwrap()
+        );
+        assert_eq!(image.levels().len(), basis_file.levels(image_index).len());
+
+        for (level_index, level_info) in image.levels().enumerate() {
+            let level_index = level_index as u32;
+            let expected = transcoder
+                .image_level_description(basis_data, image_index, level_index)
+                .unwrap();
+            let description = basis_file
+                .image_level_description(image_index, level_index)
+                .unwrap();
+            assert_eq!(level_info.m_level_index, level_index);
+            assert_eq!(description.original_width, expected.original_width);
+            assert_eq!(description.original_height, expected.original_height);
+            assert_eq!(description.block_count, expected.block_count);
+        }
+    }
+    assert_eq!(basis_file.levels(1).len(), 0);
+    assert!(basis_file.image(1).is_none());
+
+    // Transcoding gives the same result as the transcoder
+    let mut transcoder = transcoder;
+    transcoder.prepare_transcoding(basis_data).unwrap();
+    let parameters = TranscodeParameters {
+        level_index: 2,
+        ..Default::default()
+    };
+    let expected = transcoder
+        .transcode_image_level(
+            basis_data,
+            TranscoderTextureFormat::BC3_RGBA,
+            parameters.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        basis_file
+            .transcode(TranscoderTextureFormat::BC3_RGBA, parameters.clone())
+            .unwrap(),
+        expected
+    );
+
+    // An owned file works the same way
+    let basis_file = basis_file.into_owned();
+    let mut state = TranscoderState::new();
+    let mut output = vec![0_u8; expected.len()];
+    basis_file
+        .transcode_into_with_state(
+            TranscoderTextureFormat::BC3_RGBA,
+            parameters,
+            &mut state,
+            &mut output,
+        )
+        .unwrap();
+    assert_eq!(output, expected);
+
+    assert_eq!(
+        basis_file.transcode(
+            TranscoderTextureFormat::BC3_RGBA,
+            TranscodeParameters {
+                level_index: 9,
+                ..Default::default()
+            }
+        ),
+        Err(TranscodeError::ImageLevelNotFound {
+            image_index: 0,
+            level_index: 9,
+            level_count: 7,
+        })
+    );
+
+    let basis_file = BasisFileBuf::from_vec(basis_data.to_vec()).unwrap();
+    assert_eq!(basis_file.data(), &basis_data[..]);
+    assert!(matches!(
+        BasisFileBuf::from_vec(vec![0; 100]),
+        Err(TranscodeError::InvalidHeader)
+    ));
+}
+
+#[test]
+fn test_choose_transcode_format() {
+    use TranscoderTextureFormat::*;
+
+    let none = TargetCapabilities::default();
+    let all = TargetCapabilities {
+        etc1: true,
+        etc2: true,
+        bc: true,
+        bc7: true,
+        astc: true,
+        pvrtc1: true,
+        pvrtc2: true,
+        atc: true,
+        fxt1: true,
+    };
+
+    // Expected formats for ETC1S opaque/alpha, UASTC opaque/alpha and ETC1S/UASTC normal maps
+    let expected = [
+        (none, [RGBA32, RGBA32, RGBA32, RGBA32, RGBA32, RGBA32]),
+        (
+            TargetCapabilities { etc1: true, ..none },
+            [ETC1_RGB, RGBA32, ETC1_RGB, RGBA32, RGBA32, RGBA32],
+        ),
+        (
+            TargetCapabilities { etc2: true, ..none },
+            [
+                ETC1_RGB,
+                ETC2_RGBA,
+                ETC1_RGB,
+                ETC2_RGBA,
+                ETC2_EAC_RG11,
+                ETC2_EAC_RG11,
+            ],
+        ),
+        (
+            TargetCapabilities { bc: true, ..none },
+            [BC1_RGB, BC3_RGBA, BC1_RGB, BC3_RGBA, BC5_RG, BC5_RG],
+        ),
+        (
+            TargetCapabilities { bc7: true, ..none },
+            [BC7_RGBA, BC7_RGBA, BC7_RGBA, BC7_RGBA, BC7_RGBA, BC7_RGBA],
+        ),
+        (
+            TargetCapabilities { astc: true, ..none },
+            [
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+            ],
+        ),
+        (
+            TargetCapabilities {
+                pvrtc1: true,
+                ..none
+            },
+            [
+                PVRTC1_4_RGB,
+                PVRTC1_4_RGBA,
+                PVRTC1_4_RGB,
+                PVRTC1_4_RGBA,
+                PVRTC1_4_RGBA,
+                PVRTC1_4_RGBA,
+            ],
+        ),
+        // UASTC can't be transcoded to PVRTC2, ATC or FXT1
+        (
+            TargetCapabilities {
+                pvrtc2: true,
+                ..none
+            },
+            [
+                PVRTC2_4_RGB,
+                PVRTC2_4_RGBA,
+                RGBA32,
+                RGBA32,
+                PVRTC2_4_RGBA,
+                RGBA32,
+            ],
+        ),
+        (
+            TargetCapabilities { atc: true, ..none },
+            [ATC_RGB, ATC_RGBA, RGBA32, RGBA32, ATC_RGBA, RGBA32],
+        ),
+        (
+            TargetCapabilities { fxt1: true, ..none },
+            [FXT1_RGB, RGBA32, RGBA32, RGBA32, RGBA32, RGBA32],
+        ),
+        // Typical desktop GPU
+        (
+            TargetCapabilities {
+                bc: true,
+                bc7: true,
+                ..none
+            },
+            [BC7_RGBA, BC7_RGBA, BC7_RGBA, BC7_RGBA, BC5_RG, BC5_RG],
+        ),
+        // Typical mobile GPU
+        (
+            TargetCapabilities {
+                etc1: true,
+                etc2: true,
+                astc: true,
+                ..none
+            },
+            [
+                ETC1_RGB,
+                ETC2_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                ETC2_EAC_RG11,
+                ETC2_EAC_RG11,
+            ],
+        ),
+        (
+            all,
+            [
+                ETC1_RGB,
+                ETC2_RGBA,
+                ASTC_4x4_RGBA,
+                ASTC_4x4_RGBA,
+                BC5_RG,
+                BC5_RG,
+            ],
+        ),
+    ];
+
+    for (capabilities, formats) in expected.iter() {
+        let chosen = [
+            choose_transcode_format(capabilities, BasisTextureFormat::ETC1S, false, false),
+            choose_transcode_format(capabilities, BasisTextureFormat::ETC1S, true, false),
+            choose_transcode_format(capabilities, BasisTextureFormat::UASTC4x4, false, false),
+            choose_transcode_format(capabilities, BasisTextureFormat::UASTC4x4, true, false),
+            choose_transcode_format(capabilities, BasisTextureFormat::ETC1S, false, true),
+            choose_transcode_format(capabilities, BasisTextureFormat::UASTC4x4, false, true),
+        ];
+        assert_eq!(&chosen, formats, "{:?}", capabilities);
+    }
+
+    // Every combination of capabilities must give a usable format that keeps alpha when needed
+    for bits in 0..(1 << 9) {
+        let capabilities = TargetCapabilities {
+            etc1: bits & 1 != 0,
+            etc2: bits & 2 != 0,
+            bc: bits & 4 != 0,
+            bc7: bits & 8 != 0,
+            astc: bits & 16 != 0,
+            pvrtc1: bits & 32 != 0,
+            pvrtc2: bits & 64 != 0,
+            atc: bits & 128 != 0,
+            fxt1: bits & 256 != 0,
+        };
+
+        for &basis_format in &[BasisTextureFormat::ETC1S, BasisTextureFormat::UASTC4x4] {
+            for &(has_alpha, is_normal_map) in &[(false, false), (true, false), (false, true)] {
+                let format =
+                    choose_transcode_format(&capabilities, basis_format, has_alpha, is_normal_map);
+                assert!(capabilities.supports(format));
+                assert!(basis_format.can_transcode_to_format(format));
+                if is_normal_map {
+                    assert!(format.has_alpha() || format == BC5_RG || format == ETC2_EAC_RG11);
+                } else if has_alpha {
+                    assert!(format.has_alpha());
+                }
+            }
+        }
+    }
+}
+
+const ALL_TRANSCODER_TEXTURE_FORMATS: [TranscoderTextureFormat; 21] = [
+    TranscoderTextureFormat::ETC1_RGB,
+    TranscoderTextureFormat::ETC2_RGBA,
+    TranscoderTextureFormat::BC1_RGB,
+    TranscoderTextureFormat::BC3_RGBA,
+    TranscoderTextureFormat::BC4_R,
+    TranscoderTextureFormat::BC5_RG,
+    TranscoderTextureFormat::BC7_RGBA,
+    TranscoderTextureFormat::PVRTC1_4_RGB,
+    TranscoderTextureFormat::PVRTC1_4_RGBA,
+    TranscoderTextureFormat::ASTC_4x4_RGBA,
+    TranscoderTextureFormat::ATC_RGB,
+    TranscoderTextureFormat::ATC_RGBA,
+    TranscoderTextureFormat::FXT1_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGB,
+    TranscoderTextureFormat::PVRTC2_4_RGBA,
+    TranscoderTextureFormat::ETC2_EAC_R11,
+    TranscoderTextureFormat::ETC2_EAC_RG11,
+    TranscoderTextureFormat::RGBA32,
+    TranscoderTextureFormat::RGB565,
+    TranscoderTextureFormat::BGR565,
+    TranscoderTextureFormat::RGBA4444,
+];
+
+// (format value, bytes per block or pixel) as given by each API's specification
+const VK_FORMAT_SIZES: &[(u32, u32)] = &[
+    (2, 2),
+    (4, 2),
+    (5, 2),
+    (37, 4),
+    (43, 4),
+    (131, 8),
+    (132, 8),
+    (137, 16),
+    (138, 16),
+    (139, 8),
+    (141, 16),
+    (145, 16),
+    (146, 16),
+    (147, 8),
+    (148, 8),
+    (151, 16),
+    (152, 16),
+    (153, 8),
+    (155, 16),
+    (157, 16),
+    (158, 16),
+    (1000054001, 8),
+    (1000054003, 8),
+    (1000054005, 8),
+    (1000054007, 8),
+];
+
+const DXGI_FORMAT_SIZES: &[(u32, u32)] = &[
+    (28, 4),
+    (29, 4),
+    (71, 8),
+    (72, 8),
+    (77, 16),
+    (78, 16),
+    (80, 8),
+    (83, 16),
+    (85, 2),
+    (98, 16),
+    (99, 16),
+    (191, 2),
+];
+
+const GL_INTERNAL_FORMAT_SIZES: &[(u32, u32)] = &[
+    (0x8056, 2),
+    (0x8058, 4),
+    (0x8C43, 4),
+    (0x8D62, 2),
+    (0x83F0, 8),
+    (0x8C4C, 8),
+    (0x83F3, 16),
+    (0x8C4F, 16),
+    (0x8DBB, 8),
+    (0x8DBD, 16),
+    (0x8E8C, 16),
+    (0x8E8D, 16),
+    (0x8D64, 8),
+    (0x9275, 8),
+    (0x9278, 16),
+    (0x9279, 16),
+    (0x9270, 8),
+    (0x9272, 16),
+    (0x93B0, 16),
+    (0x93D0, 16),
+    (0x8C00, 8),
+    (0x8A55, 8),
+    (0x8C02, 8),
+    (0x8A57, 8),
+    (0x9138, 8),
+    (0x93F1, 8),
+    (0x8C92, 8),
+    (0x87EE, 16),
+    (0x86B0, 16),
+];
+
+const MTL_PIXEL_FORMAT_SIZES: &[(u32, u32)] = &[
+    (40, 2),
+    (42, 2),
+    (70, 4),
+    (71, 4),
+    (130, 8),
+    (131, 8),
+    (134, 16),
+    (135, 16),
+    (140, 8),
+    (142, 16),
+    (152, 16),
+    (153, 16),
+    (162, 8),
+    (163, 8),
+    (166, 8),
+    (167, 8),
+    (170, 8),
+    (174, 16),
+    (178, 16),
+    (179, 16),
+    (180, 8),
+    (181, 8),
+    (186, 16),
+    (204, 16),
+];
+
+#[test]
+fn test_gpu_format_mappings() {
+    use TranscoderTextureFormat::*;
+
+    // Formats with no mapping for (Vulkan, D3D, OpenGL, Metal)
+    let unmapped: &[(TranscoderTextureFormat, [bool; 4])] = &[
+        (ETC1_RGB, [false, true, false, false]),
+        (ETC2_RGBA, [false, true, false, false]),
+        (PVRTC1_4_RGB, [false, true, false, false]),
+        (PVRTC1_4_RGBA, [false, true, false, false]),
+        (ASTC_4x4_RGBA, [false, true, false, false]),
+        (ATC_RGB, [true, true, false, true]),
+        (ATC_RGBA, [true, true, false, true]),
+        (FXT1_RGB, [true, true, false, true]),
+        (PVRTC2_4_RGB, [false, true, false, true]),
+        (PVRTC2_4_RGBA, [false, true, false, true]),
+        (ETC2_EAC_R11, [false, true, false, false]),
+        (ETC2_EAC_RG11, [false, true, false, false]),
+        (BGR565, [false, true, true, true]),
+    ];
+
+    let size_of = |table: &[(u32, u32)], value: u32| {
+        table
+            .iter()
+            .find(|(format, _)| *format == value)
+            .map(|(_, size)| *size)
+            .unwrap_or_else(|| panic!("no size for format {}", value))
+    };
+
+    for &format in &ALL_TRANSCODER_TEXTURE_FORMATS {
+        let expected_unmapped = unmapped
+            .iter()
+            .find(|(unmapped_format, _)| *unmapped_format == format)
+            .map(|(_, apis)| *apis)
+            .unwrap_or([false; 4]);
+
+        for &srgb in &[false, true] {
+            let mappings = [
+                (format.vk_format(srgb), VK_FORMAT_SIZES),
+                (format.dxgi_format(srgb), DXGI_FORMAT_SIZES),
+                (format.gl_internal_format(srgb), GL_INTERNAL_FORMAT_SIZES),
+                (format.mtl_pixel_format(srgb), MTL_PIXEL_FORMAT_SIZES),
+            ];
+
+            for (api_index, (mapped, sizes)) in mappings.iter().enumerate() {
+                assert_eq!(
+                    mapped.is_none(),
+                    expected_unmapped[api_index],
+                    "{:?} srgb: {} api: {}",
+                    format,
+                    srgb,
+                    api_index
+                );
+
+                if let Some(mapped) = mapped {
+                    assert_eq!(
+                        size_of(sizes, *mapped),
+                        format.bytes_per_block_or_pixel(),
+                        "{:?} srgb: {} api: {}",
+                        format,
+                        srgb,
+                        api_index
+                    );
+                }
+            }
+        }
+    }
+
+    // sRGB only changes formats that store color
+    assert_eq!(BC7_RGBA.vk_format(false), Some(145));
+    assert_eq!(BC7_RGBA.vk_format(true), Some(146));
+    assert_eq!(BC5_RG.vk_format(true), BC5_RG.vk_format(false));
+    assert_eq!(RGBA32.dxgi_format(true), Some(29));
+    assert_eq!(ETC1_RGB.gl_internal_format(true), Some(0x9275));
+    assert_eq!(ASTC_4x4_RGBA.mtl_pixel_format(true), Some(186));
+}
+
+#[cfg(feature = "wgpu")]
+#[test]
+fn test_wgpu_format_mappings() {
+    for &format in &ALL_TRANSCODER_TEXTURE_FORMATS {
+        for &srgb in &[false, true] {
+            let wgpu_format = match format.wgpu_texture_format(srgb) {
+                Some(wgpu_format) => wgpu_format,
+                None => continue,
+            };
+
+            if !srgb {
+                assert!(!wgpu_format.is_srgb());
+            }
+            assert_eq!(
+                wgpu_format.block_copy_size(None),
+                Some(format.bytes_per_block_or_pixel())
+            );
+            if format.is_compressed() {
+                assert_eq!(
+                    wgpu_format.block_dimensions(),
+                    (format.block_width(), format.block_height())
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dds_round_trip() {
+    use crate::dds;
+
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ] {
+        let mut transcoder = Transcoder::new();
+        transcoder.prepare_transcoding(basis_file).unwrap();
+
+        for &(format, legacy_fourcc) in &[
+            (TranscoderTextureFormat::BC1_RGB, Some(b"DXT1")),
+            (TranscoderTextureFormat::BC3_RGBA, Some(b"DXT5")),
+            (TranscoderTextureFormat::BC4_R, Some(b"ATI1")),
+            (TranscoderTextureFormat::BC5_RG, Some(b"ATI2")),
+            (TranscoderTextureFormat::BC7_RGBA, None),
+            (TranscoderTextureFormat::RGBA32, None),
+        ] {
+            for &srgb in &[false, true] {
+                let mut file = Vec::new();
+                transcoder
+                    .write_dds(basis_file, format, srgb, &mut file)
+                    .unwrap();
+
+                // sRGB formats always need the DX10 header, formats without an sRGB variant don't
+                let has_srgb_variant = format.dxgi_format(true) != format.dxgi_format(false);
+                let fourcc = &file[84..88];
+                match legacy_fourcc {
+                    Some(legacy_fourcc) if !(srgb && has_srgb_variant) => {
+                        assert_eq!(fourcc, &legacy_fourcc[..])
+                    }
+                    _ if format == TranscoderTextureFormat::RGBA32 && !srgb => {
+                        assert_eq!(fourcc, &[0, 0, 0, 0])
+                    }
+                    _ => assert_eq!(fourcc, b"DX10"),
+                }
+
+                let (layout, data) = dds::read_dds(&file).unwrap();
+                let description = transcoder
+                    .image_level_description(basis_file, 0, 0)
+                    .unwrap();
+                assert_eq!(layout.format, format);
+                assert_eq!(layout.srgb, srgb && has_srgb_variant);
+                assert_eq!(layout.width, description.original_width);
+                assert_eq!(layout.height, description.original_height);
+                assert_eq!(
+                    layout.level_count,
+                    transcoder.image_level_count(basis_file, 0).unwrap()
+                );
+                assert_eq!(layout.array_size, 1);
+                assert!(!layout.is_cubemap);
+                assert_eq!(data.len(), layout.data_size());
+
+                for level_index in 0..layout.level_count {
+                    let expected = transcoder
+                        .transcode_image_level(
+                            basis_file,
+                            format,
+                            TranscodeParameters {
+                                level_index,
+                                ..Default::default()
+                            },
+                        )
+                        .unwrap();
+                    let range = layout.surface_range(0, level_index).unwrap();
+                    assert_eq!(&data[range], &expected[..]);
+                }
+            }
+        }
+
+        let mut file = Vec::new();
+        assert!(matches!(
+            transcoder.write_dds(
+                basis_file,
+                TranscoderTextureFormat::ASTC_4x4_RGBA,
+                false,
+                &mut file
+            ),
+            Err(dds::DdsError::FormatNotSupported(
+                TranscoderTextureFormat::ASTC_4x4_RGBA
+            ))
+        ));
+        assert!(file.is_empty());
+
+        transcoder.end_transcoding();
+    }
+}
+
+#[test]
+fn test_dds_array() {
+    use crate::dds;
+    use crate::{Compressor, CompressorParams};
+
+    // Two 32x32 images, each a different solid color
+    let mut compressor_params = CompressorParams::new();
+    compressor_params.set_generate_mipmaps(true);
+    compressor_params.resize_source_image_list(2);
+    for image_index in 0..2 {
+        let pixels: Vec<u8> = [255 * image_index as u8, 128, 0, 255].repeat(32 * 32);
+        compressor_params
+            .source_image_mut(image_index)
+            .init(&pixels, 32, 32, 4);
+    }
+
+    let mut compressor = Compressor::default();
+    unsafe {
+        assert!(compressor.init(&compressor_params));
+        compressor.process().unwrap();
+    }
+    let basis_file = compressor.basis_file();
+
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+
+    let mut file = Vec::new();
+    transcoder
+        .write_dds(
+            basis_file,
+            TranscoderTextureFormat::BC1_RGB,
+            false,
+            &mut file,
+        )
+        .unwrap();
+
+    // Arrays can only be described by the DX10 header
+    assert_eq!(&file[84..88], b"DX10");
+
+    let (layout, data) = dds::read_dds(&file).unwrap();
+    assert_eq!(layout.array_size, 2);
+    assert_eq!(layout.layer_count(), 2);
+    assert_eq!((layout.width, layout.height), (32, 32));
+    assert_eq!(layout.level_count, 6);
+    for image_index in 0..2 {
+        for level_index in 0..layout.level_count {
+            let expected = transcoder
+                .transcode_image_level(
+                    basis_file,
+                    TranscoderTextureFormat::BC1_RGB,
+                    TranscodeParameters {
+                        image_index,
+                        level_index,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            let range = layout.surface_range(image_index, level_index).unwrap();
+            assert_eq!(&data[range], &expected[..]);
+        }
+    }
+    assert!(layout.surface_range(2, 0).is_none());
+    assert!(layout.surface_range(0, 6).is_none());
+
+    transcoder.end_transcoding();
+}
+
+#[test]
+fn test_dds_layouts() {
+    use crate::dds::*;
+
+    let layouts = [
+        // Non-multiple of 4 sizes with mips down to 1x1
+        DdsLayout {
+            format: TranscoderTextureFormat::BC7_RGBA,
+            srgb: true,
+            width: 13,
+            height: 6,
+            level_count: 4,
+            array_size: 1,
+            is_cubemap: false,
+        },
+        DdsLayout {
+            format: TranscoderTextureFormat::BC4_R,
+            srgb: false,
+            width: 8,
+            height: 8,
+            level_count: 1,
+            array_size: 1,
+            is_cubemap: true,
+        },
+        DdsLayout {
+            format: TranscoderTextureFormat::BC3_RGBA,
+            srgb: false,
+            width: 16,
+            height: 16,
+            level_count: 5,
+            array_size: 2,
+            is_cubemap: true,
+        },
+        DdsLayout {
+            format: TranscoderTextureFormat::RGB565,
+            srgb: false,
+            width: 5,
+            height: 3,
+            level_count: 3,
+            array_size: 3,
+            is_cubemap: false,
+        },
+        DdsLayout {
+            format: TranscoderTextureFormat::RGBA32,
+            srgb: false,
+            width: 7,
+            height: 9,
+            level_count: 4,
+            array_size: 1,
+            is_cubemap: true,
+        },
+    ];
+
+    for layout in &layouts {
+        let data: Vec<u8> = (0..layout.data_size()).map(|i| i as u8).collect();
+        let mut file = Vec::new();
+        write_dds(&mut file, layout, &data).unwrap();
+
+        let (read_layout, read_data) = read_dds(&file).unwrap();
+        assert_eq!(&read_layout, layout);
+        assert_eq!(read_data, &data[..]);
+
+        // Trailing data is ignored, missing data is an error
+        file.push(0);
+        assert_eq!(read_dds(&file).unwrap().1, &data[..]);
+        file.truncate(file.len() - 2);
+        assert!(matches!(
+            read_dds(&file),
+            Err(DdsError::DataSizeMismatch { .. })
+        ));
+    }
+
+    let layout = layouts[0];
+    assert_eq!(layout.level_dimensions(3), (1, 1));
+    assert_eq!(layout.level_size(0), 4 * 2 * 16);
+    assert_eq!(layout.level_size(3), 16);
+    assert_eq!(layouts[2].layer_count(), 12);
+    assert_eq!(layouts[3].level_size(0), 5 * 3 * 2);
+
+    let mut file = Vec::new();
+    assert!(matches!(
+        write_dds(&mut file, &layout, &[0; 4]),
+        Err(DdsError::DataSizeMismatch {
+            provided_bytes: 4,
+            ..
+        })
+    ));
+    assert!(matches!(
+        write_dds(
+            &mut file,
+            &DdsLayout {
+                format: TranscoderTextureFormat::ETC1_RGB,
+                ..layout
+            },
+            &[]
+        ),
+        Err(DdsError::FormatNotSupported(
+            TranscoderTextureFormat::ETC1_RGB
+        ))
+    ));
+    assert!(file.is_empty());
+
+    assert!(matches!(read_dds(&[]), Err(DdsError::InvalidFile(_))));
+    assert!(matches!(read_dds(&[0; 256]), Err(DdsError::InvalidFile(_))));
+
+    // Dimensions whose size doesn't fit in a usize are rejected instead of overflowing
+    let layout = DdsLayout {
+        format: TranscoderTextureFormat::RGBA32,
+        srgb: false,
+        width: 1,
+        height: 1,
+        level_count: 1,
+        array_size: 1,
+        is_cubemap: false,
+    };
+    let mut file = Vec::new();
+    write_dds(&mut file, &layout, &[0; 4]).unwrap();
+    // Height and width follow the magic, size and flags
+    file[12..20].copy_from_slice(&[0xFF; 8]);
+    assert!(matches!(read_dds(&file), Err(DdsError::InvalidFile(_))));
+}
+
+#[test]
+fn test_ktx_round_trip() {
+    use crate::ktx::*;
+
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ] {
+        let mut transcoder = Transcoder::new();
+        transcoder.prepare_transcoding(basis_file).unwrap();
+        let basis_format = transcoder.basis_texture_format(basis_file).unwrap();
+
+        for &format in &[
+            TranscoderTextureFormat::ETC1_RGB,
+            TranscoderTextureFormat::ETC2_RGBA,
+            TranscoderTextureFormat::BC1_RGB,
+            TranscoderTextureFormat::BC3_RGBA,
+            TranscoderTextureFormat::BC4_R,
+            TranscoderTextureFormat::BC5_RG,
+            TranscoderTextureFormat::BC7_RGBA,
+            TranscoderTextureFormat::ASTC_4x4_RGBA,
+            TranscoderTextureFormat::ATC_RGBA,
+            TranscoderTextureFormat::ETC2_EAC_RG11,
+            TranscoderTextureFormat::RGBA32,
+            TranscoderTextureFormat::RGB565,
+            TranscoderTextureFormat::RGBA4444,
+        ] {
+            if !basis_format.can_transcode_to_format(format) {
+                continue;
+            }
+
+            for &version in &[KtxVersion::Ktx1, KtxVersion::Ktx2] {
+                for &srgb in &[false, true] {
+                    let mut file = Vec::new();
+                    let result = transcoder.write_ktx(basis_file, format, srgb, version, &mut file);
+
+                    // ATC has no VkFormat
+                    if version == KtxVersion::Ktx2 && format == TranscoderTextureFormat::ATC_RGBA {
+                        assert!(matches!(
+                            result,
+                            Err(KtxError::FormatNotSupported(
+                                TranscoderTextureFormat::ATC_RGBA
+                            ))
+                        ));
+                        continue;
+                    }
+                    result.unwrap();
+
+                    let texture = KtxTexture::read(&file).unwrap();
+                    let description = transcoder
+                        .image_level_description(basis_file, 0, 0)
+                        .unwrap();
+
+                    // glTypeSize follows the endianness and glType in KTX1, typeSize follows
+                    // vkFormat in KTX2
+                    let type_size_offset = match version {
+                        KtxVersion::Ktx1 => 20,
+                        KtxVersion::Ktx2 => 16,
+                    };
+                    let type_size = u32::from_le_bytes([
+                        file[type_size_offset],
+                        file[type_size_offset + 1],
+                        file[type_size_offset + 2],
+                        file[type_size_offset + 3],
+                    ]);
+                    let expected_type_size = match format {
+                        TranscoderTextureFormat::RGB565 | TranscoderTextureFormat::RGBA4444 => 2,
+                        _ => 1,
+                    };
+                    assert_eq!(type_size, expected_type_size);
+                    let has_srgb_variant = match version {
+                        KtxVersion::Ktx1 => {
+                            format.gl_internal_format(true) != format.gl_internal_format(false)
+                        }
+                        KtxVersion::Ktx2 => format.vk_format(true) != format.vk_format(false),
+                    };
+                    assert_eq!(texture.version, version);
+                    assert_eq!(texture.format, format);
+                    assert_eq!(texture.srgb, srgb && has_srgb_variant);
+                    assert_eq!(texture.width, description.original_width);
+                    assert_eq!(texture.height, description.original_height);
+                    assert_eq!(texture.array_size, 0);
+                    assert_eq!(texture.face_count, 1);
+                    assert_eq!(texture.surface_count(), 1);
+                    assert_eq!(
+                        texture.level_count(),
+                        transcoder.image_level_count(basis_file, 0).unwrap()
+                    );
+
+                    for level_index in 0..texture.level_count() {
+                        let expected = transcoder
+                            .transcode_image_level(
+                                basis_file,
+                                format,
+                                TranscodeParameters {
+                                    level_index,
+                                    ..Default::default()
+                                },
+                            )
+                            .unwrap();
+                        assert_eq!(texture.level(level_index).unwrap(), &[&expected[..]]);
+                        assert_eq!(texture.surface(level_index, 0, 0).unwrap(), &expected[..]);
+                    }
+                    assert!(texture.level(texture.level_count()).is_none());
+                    assert!(texture.surface(0, 1, 0).is_none());
+                    assert!(texture.surface(0, 0, 1).is_none());
+                }
+            }
+        }
+
+        let mut file = Vec::new();
+        assert!(matches!(
+            transcoder.write_ktx(
+                basis_file,
+                TranscoderTextureFormat::BGR565,
+                false,
+                KtxVersion::Ktx1,
+                &mut file
+            ),
+            Err(KtxError::FormatNotSupported(
+                TranscoderTextureFormat::BGR565
+            ))
+        ));
+        assert!(file.is_empty());
+
+        transcoder.end_transcoding();
+    }
+}
+
+#[test]
+fn test_ktx_array() {
+    use crate::ktx::*;
+    use crate::{Compressor, CompressorParams};
+
+    // Three 16x8 images, each a different solid color
+    let mut compressor_params = CompressorParams::new();
+    compressor_params.set_generate_mipmaps(true);
+    compressor_params.resize_source_image_list(3);
+    for image_index in 0..3 {
+        let pixels: Vec<u8> = [100 * image_index as u8, 50, 200, 255].repeat(16 * 8);
+        compressor_params
+            .source_image_mut(image_index)
+            .init(&pixels, 16, 8, 4);
+    }
+
+    let mut compressor = Compressor::default();
+    unsafe {
+        assert!(compressor.init(&compressor_params));
+        compressor.process().unwrap();
+    }
+    let basis_file = compressor.basis_file();
+
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(basis_file).unwrap();
+
+    for &version in &[KtxVersion::Ktx1, KtxVersion::Ktx2] {
+        // RGB565 is used so that the surfaces aren't a multiple of 4 bytes
+        for &format in &[
+            TranscoderTextureFormat::BC7_RGBA,
+            TranscoderTextureFormat::RGB565,
+        ] {
+            let mut file = Vec::new();
+            transcoder
+                .write_ktx(basis_file, format, false, version, &mut file)
+                .unwrap();
+
+            let texture = KtxTexture::read(&file).unwrap();
+            assert_eq!(texture.array_size, 3);
+            assert_eq!(texture.face_count, 1);
+            assert_eq!((texture.width, texture.height), (16, 8));
+            assert_eq!(texture.level_count(), 5);
+            for level_index in 0..texture.level_count() {
+                assert_eq!(texture.level(level_index).unwrap().len(), 3);
+                for image_index in 0..3 {
+                    let expected = transcoder
+                        .transcode_image_level(
+                            basis_file,
+                            format,
+                            TranscodeParameters {
+                                image_index,
+                                level_index,
+                                ..Default::default()
+                            },
+                        )
+                        .unwrap();
+                    assert_eq!(
+                        texture.surface(level_index, image_index, 0).unwrap(),
+                        &expected[..]
+                    );
+                }
+            }
+            assert!(texture.surface(0, 3, 0).is_none());
+        }
+    }
+
+    transcoder.end_transcoding();
+
+    assert!(matches!(
+        KtxTexture::read(&[]),
+        Err(KtxError::InvalidFile(_))
+    ));
+    assert!(matches!(
+        KtxTexture::read(&[0; 128]),
+        Err(KtxError::InvalidFile(_))
+    ));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_decode_to_rgba_image() {
+    for basis_file in [
+        &include_bytes!("../../test_assets/rust-logo-etc.basis")[..],
+        &include_bytes!("../../test_assets/rust-logo-uastc.basis")[..],
+    ] {
+        let mut transcoder = Transcoder::new();
+        transcoder.prepare_transcoding(basis_file).unwrap();
+
+        for level_index in 0..transcoder.image_level_count(basis_file, 0).unwrap() {
+            let description = transcoder
+                .image_level_description(basis_file, 0, level_index)
+                .unwrap();
+            let rgba32 = transcoder
+                .transcode_image_level(
+                    basis_file,
+                    TranscoderTextureFormat::RGBA32,
+                    TranscodeParameters {
+                        level_index,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            let image = transcoder
+                .decode_to_rgba_image(basis_file, 0, level_index)
+                .unwrap();
+            assert_eq!(image.width(), description.original_width);
+            assert_eq!(image.height(), description.original_height);
+            assert_eq!(image.as_raw(), &rgba32);
+
+            // The 16-bit formats lose precision, but should be close to the 8-bit result
+            for &(format, max_difference, ignore_alpha) in &[
+                (TranscoderTextureFormat::RGB565, 8, true),
+                (TranscoderTextureFormat::BGR565, 8, true),
+                (TranscoderTextureFormat::RGBA4444, 17, false),
+            ] {
+                let image = transcoder
+                    .decode_to_rgba_image_with_format(basis_file, 0, level_index, format)
+                    .unwrap();
+                assert_eq!(image.width(), description.original_width);
+                assert_eq!(image.height(), description.original_height);
+                for (expanded, expected) in image.as_raw().chunks(4).zip(rgba32.chunks(4)) {
+                    for channel in 0..3 {
+                        let difference =
+                            (expanded[channel] as i32 - expected[channel] as i32).abs();
+                        assert!(difference <= max_difference);
+                    }
+
+                    if ignore_alpha {
+                        assert_eq!(expanded[3], 255);
+                    } else {
+                        assert!((expanded[3] as i32 - expected[3] as i32).abs() <= max_difference);
+                    }
+                }
+            }
+        }
+
+        assert!(matches!(
+            transcoder.decode_to_rgba_image_with_format(
+                basis_file,
+                0,
+                0,
+                TranscoderTextureFormat::BC7_RGBA
+            ),
+            Err(TranscodeError::UncompressedFormatRequired {
+                transcode_format: TranscoderTextureFormat::BC7_RGBA
+            })
+        ));
+        assert!(matches!(
+            transcoder.decode_to_rgba_image(basis_file, 1, 0),
+            Err(TranscodeError::ImageNotFound { .. })
+        ));
+
+        transcoder.end_transcoding();
+    }
+}
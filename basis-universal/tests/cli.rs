@@ -0,0 +1,220 @@
+#![cfg(feature = "cli")]
+
+use basis_universal::dds::read_dds;
+use basis_universal::ktx::KtxTexture;
+use basis_universal::TranscoderTextureFormat;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_basisu-rs"))
+        .args(args)
+        .output()
+        .expect("failed to run basisu-rs")
+}
+
+fn run_ok(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "basisu-rs {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn test_asset(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("test_assets")
+        .join(name)
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+// Each test gets its own directory so they can run in parallel
+fn output_dir(test_name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("cli")
+        .join(test_name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_cli_info() {
+    let stdout = run_ok(&["info", &test_asset("rust-logo-uastc.basis")]);
+    assert!(stdout.contains("Texture format: UASTC4x4"));
+    assert!(stdout.contains("Total images: 1"));
+    assert!(stdout.contains("Image 0: 64x64, 7 levels"));
+    assert!(stdout.contains("Level 6: 1x1"));
+
+    let stdout = run_ok(&["info", &test_asset("rust-logo-etc.basis")]);
+    assert!(stdout.contains("Texture format: ETC1S"));
+}
+
+#[test]
+fn test_cli_validate() {
+    run_ok(&["validate", "--full", &test_asset("rust-logo-etc.basis")]);
+    run_ok(&["validate", &test_asset("rust-logo-uastc.basis")]);
+
+    // Flip a byte in the compressed data, past the header
+    let dir = output_dir("validate");
+    let mut data = std::fs::read(test_asset("rust-logo-etc.basis")).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 0xFF;
+    let corrupted = dir.join("corrupted.basis");
+    std::fs::write(&corrupted, data).unwrap();
+
+    let corrupted = corrupted.to_str().unwrap();
+    run_ok(&["validate", corrupted]);
+    let output = run(&["validate", "--full", corrupted]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error"));
+}
+
+#[test]
+fn test_cli_compress() {
+    let dir = output_dir("compress");
+    let basis_path = dir.join("rust-logo.basis");
+    let basis_path = basis_path.to_str().unwrap();
+    run_ok(&[
+        "compress",
+        &test_asset("rust-logo.png"),
+        &test_asset("rust-logo-256x256.png"),
+        "-o",
+        basis_path,
+        "--uastc",
+        "--mipmaps",
+        "--userdata0",
+        "100",
+        "--userdata1",
+        "200",
+        "--threads",
+        "2",
+    ]);
+
+    run_ok(&["validate", "--full", basis_path]);
+    let stdout = run_ok(&["info", basis_path]);
+    assert!(stdout.contains("Texture format: UASTC4x4"));
+    assert!(stdout.contains("Total images: 2"));
+    assert!(stdout.contains("Userdata: 100 200"));
+    assert!(stdout.contains("Image 0: 64x64, 7 levels"));
+    assert!(stdout.contains("Image 1: 256x256, 9 levels"));
+
+    let ktx2_path = dir.join("rust-logo.ktx2");
+    run_ok(&[
+        "compress",
+        &test_asset("rust-logo.png"),
+        "-o",
+        ktx2_path.to_str().unwrap(),
+        "--quality",
+        "64",
+    ]);
+    let ktx2 = std::fs::read(&ktx2_path).unwrap();
+    assert_eq!(&ktx2[0..12], b"\xABKTX 20\xBB\r\n\x1A\n");
+}
+
+#[test]
+fn test_cli_compress_invalid_input() {
+    let dir = output_dir("compress_invalid_input");
+    let output_path = dir.join("out.basis");
+    let output = run(&[
+        "compress",
+        &test_asset("rust-logo-etc.basis"),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(!output_path.exists());
+
+    // Quality is range checked before compressing
+    let output = run(&[
+        "compress",
+        &test_asset("rust-logo.png"),
+        "-o",
+        output_path.to_str().unwrap(),
+        "--quality",
+        "1000",
+    ]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_unpack_png() {
+    let dir = output_dir("unpack_png");
+    run_ok(&[
+        "unpack",
+        &test_asset("rust-logo-uastc.basis"),
+        "-o",
+        dir.to_str().unwrap(),
+    ]);
+
+    for level_index in 0..7 {
+        let path = dir.join(format!(
+            "rust-logo-uastc_RGBA32_image0_level{}.png",
+            level_index
+        ));
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.width(), 64 >> level_index);
+        assert_eq!(image.height(), 64 >> level_index);
+    }
+
+    run_ok(&[
+        "unpack",
+        &test_asset("rust-logo-etc.basis"),
+        "-o",
+        dir.to_str().unwrap(),
+        "--format",
+        "rgb565",
+    ]);
+    assert!(dir.join("rust-logo-etc_RGB565_image0_level0.png").exists());
+}
+
+#[test]
+fn test_cli_unpack_compressed() {
+    let dir = output_dir("unpack_compressed");
+    run_ok(&[
+        "unpack",
+        &test_asset("rust-logo-uastc.basis"),
+        "-o",
+        dir.to_str().unwrap(),
+        "--format",
+        "BC7_RGBA",
+        "--srgb",
+    ]);
+
+    let dds = std::fs::read(dir.join("rust-logo-uastc_BC7_RGBA.dds")).unwrap();
+    let (layout, _) = read_dds(&dds).unwrap();
+    assert_eq!(layout.format, TranscoderTextureFormat::BC7_RGBA);
+    assert!(layout.srgb);
+    assert_eq!((layout.width, layout.height), (64, 64));
+    assert_eq!(layout.level_count, 7);
+
+    // DDS has no ASTC format, so this falls back to KTX2
+    run_ok(&[
+        "unpack",
+        &test_asset("rust-logo-uastc.basis"),
+        "-o",
+        dir.to_str().unwrap(),
+        "--format",
+        "astc_4x4_rgba",
+    ]);
+    assert!(!dir.join("rust-logo-uastc_ASTC_4x4_RGBA.dds").exists());
+    let ktx2 = std::fs::read(dir.join("rust-logo-uastc_ASTC_4x4_RGBA.ktx2")).unwrap();
+    let texture = KtxTexture::read(&ktx2).unwrap();
+    assert_eq!(texture.format, TranscoderTextureFormat::ASTC_4x4_RGBA);
+    assert_eq!(texture.level_count(), 7);
+
+    let output = run(&[
+        "unpack",
+        &test_asset("rust-logo-uastc.basis"),
+        "-o",
+        dir.to_str().unwrap(),
+        "--format",
+        "not_a_format",
+    ]);
+    assert!(!output.status.success());
+}